@@ -1,26 +1,25 @@
-use crate::clients::etrade_xml_structs::{Account, AlertDetails, Position, TickerSearchData, TickerXML, Alert};
-use crate::clients::etrade_json_structs::{Instrument, Order, OrderType, OrderAction, EtradePreviewOrderRequest, PreviewOrderRequest, PreviewOrderResponse, Product};
+use crate::clients::etrade::TRANSACTIONS_PER_PAGE;
+use crate::clients::etrade_xml_structs::{Account, AlertDetails, Position, PositionLot, QuoteData, TickerSearchData, TickerXML, Alert};
+use crate::clients::etrade_json_structs::{CallPut, DetailFlag, ExpirationDate, OptionChainResponse, OptionPair, Instrument, Lot, Lots, Order, OrderType, OrderAction, OrderTerm, MarketSession, PriceType, EtradePlaceOrderRequest, EtradePreviewOrderRequest, PlaceOrderRequest, PlaceOrderResponse, PreviewId, PreviewOrderRequest, PreviewOrderResponse, Product, OrderSummary, StatusType, Transaction, TransactionDetails, TransactionListResponse};
 use crate::config::UserConfig;
 use crate::network::IoEvent;
-use crate::utils;
+use crate::price_history::{Bar, ChartRange};
+use crate::realized_gains::{self, Fill, GainSummary, LotMatching, LotSelections, RealizedGain, Report};
+use crate::spread::{self, OptionLeg};
+use crate::transactions::TransactionKind;
+use crate::utils::{self, HoldingTerm, MarketHours};
+use crate::watch_lists::WatchLists;
+use std::str::FromStr;
+use std::collections::{HashMap, HashSet};
 use std::sync::mpsc::Sender;
-use std::{
-    collections::HashSet,
-};
 use chrono::prelude::*;
 use serde::{Serialize, Deserialize};
 use tui::layout::Rect;
-use std::str::FromStr;
-
-pub const MAJOR_INDICES: [&str; 3] = [
-    "Nasdaq",
-    "DJIA",
-    "S&P",
-];
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum DialogContext {
   TickerDetail,
+  CancelOrder,
 }
 
 #[derive(Debug)]
@@ -49,11 +48,24 @@ pub enum RouteId {
     OrderForm,
     ConfirmOrderForm,
     AccountList,
+    Orders,
+    OptionChain,
+    Spread,
+    Portfolio,
+    TaxLots,
+    Balance,
+    Transactions,
+    RealizedGains,
+    Authorization,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum ActiveBlock {
     Analysis,
+    Balance,
+    Transactions,
+    RealizedGains,
+    Authorization,
     ConfirmOrderForm,
     Dialog(DialogContext),
     Empty,
@@ -65,18 +77,18 @@ pub enum ActiveBlock {
     NotificationDetail,
     WatchList,
     Portfolio,
+    Positions,
+    TaxLots,
     AccountList,
     OrderForm,
+    Orders,
+    OptionChain,
+    Spread,
     RecentlySearched,
     SearchResults,
     TickerDetail,
 }
 
-#[derive(Clone)]
-pub struct WatchList {
-    pub selected_index: usize,
-}
-
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Ticker {
     pub symbol: String,
@@ -84,19 +96,38 @@ pub struct Ticker {
     pub date_time: String,
     pub security_type: String,
     pub primary_exchange: String,
-    pub declared_dividend: String,
-    pub dividend: String,
+    pub declared_dividend: f64,
+    pub dividend: f64,
     pub ex_dividend_date: i64,
-    pub bid: String,
-    pub ask: String,
-    pub open: String,
-    pub high52: String,
+    pub bid: f64,
+    pub bid_size: u64,
+    pub ask: f64,
+    pub ask_size: u64,
+    pub last_trade: f64,
+    pub change_close: f64,
+    pub change_close_percentage: f64,
+    pub previous_close: f64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub total_volume: u64,
+    pub high52: f64,
     pub week52_hi_date: i64,
-    pub low52: String,
+    pub low52: f64,
     pub week52_low_date: i64,
-    pub eps: String,
-    pub pe: String,
-    pub beta: String,
+    pub eps: f64,
+    pub est_earnings: f64,
+    pub pe: f64,
+    pub beta: f64,
+    pub market_cap: f64,
+    pub shares_outstanding: f64,
+    pub next_earning_date: String,
+    // option quotes only
+    pub open_interest: u64,
+    pub days_to_expiration: u64,
+    pub intrinsic_value: f64,
+    pub time_premium: f64,
+    pub option_multiplier: f64,
 }
 
 impl Default for Ticker {
@@ -107,23 +138,119 @@ impl Default for Ticker {
             date_time: "".to_string(),
             security_type: "Equity".to_string(),
             primary_exchange: "".to_string(),
-            declared_dividend: "".to_string(),
-            dividend: "".to_string(),
+            declared_dividend: 0.0,
+            dividend: 0.0,
             ex_dividend_date: 0,
-            bid: "".to_string(),
-            ask: "".to_string(),
-            open: "".to_string(),
-            high52: "".to_string(),
+            bid: 0.0,
+            bid_size: 0,
+            ask: 0.0,
+            ask_size: 0,
+            last_trade: 0.0,
+            change_close: 0.0,
+            change_close_percentage: 0.0,
+            previous_close: 0.0,
+            open: 0.0,
+            high: 0.0,
+            low: 0.0,
+            total_volume: 0,
+            high52: 0.0,
             week52_hi_date: 0,
-            low52: "".to_string(),
+            low52: 0.0,
             week52_low_date: 0,
-            eps: "".to_string(),
-            pe: "".to_string(),
-            beta: "".to_string(),
+            eps: 0.0,
+            est_earnings: 0.0,
+            pe: 0.0,
+            beta: 0.0,
+            market_cap: 0.0,
+            shares_outstanding: 0.0,
+            next_earning_date: "".to_string(),
+            open_interest: 0,
+            days_to_expiration: 0,
+            intrinsic_value: 0.0,
+            time_premium: 0.0,
+            option_multiplier: 0.0,
         }
     }
 }
 
+impl Ticker {
+    // search results and positions come without a quote until one is fetched
+    pub fn has_quote(&self) -> bool {
+        !self.date_time.is_empty()
+    }
+
+    // The last trade, or the middle of the spread when nothing has traded yet
+    pub fn price(&self) -> f64 {
+        if self.last_trade > 0.0 {
+            self.last_trade
+        } else {
+            (self.bid + self.ask) / 2.0
+        }
+    }
+
+    // Change versus the previous close. Blocks without a previous close (Intraday) still send
+    // E*TRADE's own change, None when there is neither.
+    pub fn change(&self) -> Option<f64> {
+        if self.previous_close > 0.0 && self.price() > 0.0 {
+            Some(self.price() - self.previous_close)
+        } else if self.change_close != 0.0 {
+            Some(self.change_close)
+        } else {
+            None
+        }
+    }
+
+    pub fn percent_change(&self) -> Option<f64> {
+        if self.previous_close > 0.0 {
+            self.change().map(|change| change / self.previous_close * 100.0)
+        } else if self.change_close_percentage != 0.0 {
+            Some(self.change_close_percentage)
+        } else {
+            None
+        }
+    }
+
+    // Takes the quote fields from a refreshed quote. Whatever the detail block on screen brought
+    // (fundamentals, 52 week range, option details) stays as it was
+    pub fn update_quote(&mut self, quote: &Ticker) {
+        if self.description.is_empty() {
+            self.description = quote.description.to_owned();
+        }
+        self.date_time = quote.date_time.to_owned();
+        self.bid = quote.bid;
+        self.bid_size = quote.bid_size;
+        self.ask = quote.ask;
+        self.ask_size = quote.ask_size;
+        self.last_trade = quote.last_trade;
+        self.change_close = quote.change_close;
+        self.change_close_percentage = quote.change_close_percentage;
+        self.previous_close = quote.previous_close;
+        self.open = quote.open;
+        self.high = quote.high;
+        self.low = quote.low;
+        self.total_volume = quote.total_volume;
+    }
+
+    // Replaces everything with a newly fetched detail block, keeping the description when the block has none
+    pub fn update_detail(&mut self, detail: &Ticker) {
+        let description = if self.description.is_empty() { &detail.description } else { &self.description };
+        *self = Ticker {
+            description: description.to_owned(),
+            ..detail.clone()
+        };
+    }
+}
+
+// How long a quote that changed on a refresh stays highlighted
+const QUOTE_FLASH_MILLIS: i64 = 1500;
+
+// The quote a symbol had before a refresh changed it
+#[derive(Clone, Debug)]
+pub struct QuoteTick {
+    pub previous: Ticker,
+    pub at: DateTime<Utc>,
+}
+
 impl From<TickerSearchData> for Ticker {
     fn from(t: TickerSearchData) -> Ticker {
         Ticker {
@@ -134,45 +261,791 @@ impl From<TickerSearchData> for Ticker {
     }
 }
 
-impl From<Position> for Ticker {
-    fn from(t: Position) -> Ticker {
-        Ticker {
-            symbol: t.product.symbol,
-            description: t.symbol_description,
+// A holding in the active account, with the gains E*TRADE computed when it was fetched
+#[derive(Clone, Debug, Default)]
+pub struct PortfolioPosition {
+    pub ticker: Ticker,
+    pub position_type: String,
+    pub quantity: f64,
+    pub cost_per_share: f64,
+    pub total_cost: f64,
+    pub market_value: f64,
+    pub total_gain: f64,
+    pub total_gain_pct: f64,
+    pub days_gain: f64,
+    pub days_gain_pct: f64,
+    pub pct_of_portfolio: f64,
+    // link to the position's tax lots
+    pub lots_details: String,
+    // account_id_key of the account holding it, empty once merged across accounts
+    pub account_id_key: String,
+}
+
+impl From<Position> for PortfolioPosition {
+    fn from(p: Position) -> PortfolioPosition {
+        PortfolioPosition {
+            ticker: Ticker {
+                symbol: p.product.symbol,
+                security_type: p.product.security_type,
+                description: p.symbol_description,
+                last_trade: p.quick.last_trade,
+                change_close: p.quick.change,
+                change_close_percentage: p.quick.change_pct,
+                ..Default::default()
+            },
+            position_type: p.position_type,
+            quantity: p.quantity,
+            cost_per_share: p.cost_per_share,
+            total_cost: p.total_cost,
+            market_value: p.market_value,
+            total_gain: p.total_gain,
+            total_gain_pct: p.total_gain_pct,
+            days_gain: p.days_gain,
+            days_gain_pct: p.days_gain_pct,
+            pct_of_portfolio: p.pct_of_portfolio,
+            lots_details: p.lots_details,
             ..Default::default()
         }
     }
 }
 
+// One position per product and side across several accounts. Costs and gains are summed and the
+// percentages worked out again from the sums; tax lots belong to a single account, so positions
+// held in more than one lose their lots link and account.
+pub fn merge_positions(positions: Vec<PortfolioPosition>) -> Vec<PortfolioPosition> {
+    let mut merged: Vec<(PortfolioPosition, bool)> = vec![];
+    for position in positions {
+        // options on the same underlying share the symbol, the description tells the contracts apart
+        let existing = merged.iter_mut().find(|(m, _)| {
+            m.ticker.symbol == position.ticker.symbol
+                && m.ticker.security_type == position.ticker.security_type
+                && m.ticker.description == position.ticker.description
+                && m.position_type == position.position_type
+        });
+        match existing {
+            Some((m, combined)) => {
+                m.quantity += position.quantity;
+                m.total_cost += position.total_cost;
+                m.market_value += position.market_value;
+                m.total_gain += position.total_gain;
+                m.days_gain += position.days_gain;
+                m.lots_details.clear();
+                m.account_id_key.clear();
+                *combined = true;
+            }
+            None => merged.push((position, false)),
+        }
+    }
+
+    let percent = |part: f64, whole: f64| if whole == 0.0 { 0.0 } else { part / whole * 100.0 };
+    let market_value = merged.iter().map(|(m, _)| m.market_value).sum::<f64>();
+    merged
+        .into_iter()
+        .map(|(mut m, combined)| {
+            if combined {
+                m.cost_per_share = if m.quantity == 0.0 { 0.0 } else { m.total_cost / m.quantity };
+                m.total_gain_pct = percent(m.total_gain, m.total_cost);
+                m.days_gain_pct = percent(m.days_gain, m.market_value - m.days_gain);
+            }
+            m.pct_of_portfolio = percent(m.market_value, market_value);
+            m
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaxLot {
+    pub id: i64,
+    pub acquired: NaiveDate,
+    pub quantity: f64,
+    // shares not already tied up in open sell orders
+    pub available: f64,
+    pub price: f64,
+    pub total_cost: f64,
+    pub market_value: f64,
+    pub total_gain: f64,
+    pub term: HoldingTerm,
+}
+
+impl TaxLot {
+    pub fn new(lot: PositionLot, today: NaiveDate) -> Self {
+        let acquired = utils::date_eastern(lot.acquired_date);
+        TaxLot {
+            id: lot.position_lot_id,
+            acquired,
+            quantity: lot.remaining_qty,
+            available: lot.available_qty,
+            price: lot.price,
+            total_cost: lot.total_cost,
+            market_value: lot.market_value,
+            total_gain: lot.total_gain,
+            term: utils::holding_term(acquired, today),
+        }
+    }
+}
+
+// The tax lots of one position, and the ones picked to sell
+#[derive(Clone, Debug)]
+pub struct PositionLots {
+    pub symbol: String,
+    // the account holding the position, lot sells go to it
+    pub account_id_key: String,
+    pub lots: Vec<TaxLot>,
+    pub selected_index: usize,
+    pub sell_lot_ids: Vec<i64>,
+    pub message: Option<String>,
+}
+
+impl PositionLots {
+    pub fn new(symbol: String, account_id_key: String, lots: Vec<TaxLot>) -> Self {
+        Self {
+            symbol,
+            account_id_key,
+            lots,
+            selected_index: 0,
+            sell_lot_ids: vec![],
+            message: None,
+        }
+    }
+
+    pub fn toggle_selected(&mut self) {
+        self.message = None;
+        if let Some(lot) = self.lots.get(self.selected_index) {
+            let id = lot.id;
+            if self.sell_lot_ids.contains(&id) {
+                self.sell_lot_ids.retain(|i| *i != id);
+            } else {
+                self.sell_lot_ids.push(id);
+            }
+        }
+    }
+
+    // The picked lots, or the highlighted one when none are picked, with what each can sell
+    pub fn lots_to_sell(&self) -> Vec<Lot> {
+        let lots = if self.sell_lot_ids.is_empty() {
+            self.lots.get(self.selected_index).into_iter().collect::<Vec<&TaxLot>>()
+        } else {
+            self.lots.iter().filter(|lot| self.sell_lot_ids.contains(&lot.id)).collect()
+        };
+
+        lots.into_iter()
+            .filter(|lot| lot.available > 0.0)
+            .map(|lot| Lot { id: lot.id, size: lot.available })
+            .collect()
+    }
+}
+
+// One page of the active account's transactions, newest first, and what it is filtered on
+#[derive(Clone, Debug, Default)]
+pub struct TransactionsView {
+    pub transactions: Vec<Transaction>,
+    pub selected_index: usize,
+    // None leaves the range to E*TRADE
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    // None shows every type. E*TRADE can't filter, so a filtered page is as many pages as it takes
+    // to find a page's worth
+    pub kind: Option<TransactionKind>,
+    // the marker of the page on screen, of the pages before it, and of the next one
+    pub marker: Option<String>,
+    pub previous_markers: Vec<Option<String>>,
+    pub next_marker: Option<String>,
+    // fetched for the page on screen, a filter stops at MAX_FILTERED_PAGES and the next page carries on from there
+    pub pages: usize,
+    pub details: Option<TransactionDetails>,
+    pub message: Option<String>,
+}
+
+// a filter matching little in a long history would otherwise fetch all of it at once
+pub const MAX_FILTERED_PAGES: usize = 10;
+
+impl TransactionsView {
+    pub fn visible(&self) -> Vec<&Transaction> {
+        self.transactions
+            .iter()
+            .filter(|transaction| self.kind.is_none_or(|kind| kind.matches(&transaction.transaction_type)))
+            .collect()
+    }
+
+    pub fn selected(&self) -> Option<&Transaction> {
+        self.visible().get(self.selected_index).copied()
+    }
+
+    pub fn set_page(&mut self, response: TransactionListResponse) {
+        self.transactions = response.transactions;
+        self.next_marker = if response.more_transactions { response.marker } else { None };
+        self.pages = 1;
+        self.selected_index = 0;
+        self.details = None;
+        self.message = None;
+    }
+
+    // Adds the page after the last one fetched, when the filter leaves too few to fill the screen
+    pub fn extend_page(&mut self, response: TransactionListResponse) {
+        self.transactions.extend(response.transactions);
+        self.next_marker = if response.more_transactions { response.marker } else { None };
+        self.pages += 1;
+    }
+
+    pub fn needs_more(&self) -> bool {
+        self.kind.is_some()
+            && self.next_marker.is_some()
+            && self.pages < MAX_FILTERED_PAGES
+            && self.visible().len() < TRANSACTIONS_PER_PAGE as usize
+    }
+
+    // Moves the marker along, the caller fetches the page it points at
+    pub fn next_page(&mut self) -> bool {
+        match self.next_marker.take() {
+            Some(next_marker) => {
+                let marker = self.marker.replace(next_marker);
+                self.previous_markers.push(marker);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn previous_page(&mut self) -> bool {
+        match self.previous_markers.pop() {
+            Some(marker) => {
+                self.marker = marker;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // A new range starts over from the first page
+    pub fn set_date_range(&mut self, start_date: NaiveDate, end_date: NaiveDate) {
+        self.start_date = Some(start_date);
+        self.end_date = Some(end_date);
+        self.marker = None;
+        self.previous_markers.clear();
+        self.next_marker = None;
+    }
+
+    pub fn cycle_kind(&mut self) {
+        self.kind = match self.kind {
+            None => Some(TransactionKind::ALL[0]),
+            Some(kind) => {
+                let index = TransactionKind::ALL.iter().position(|k| *k == kind).unwrap_or(0);
+                TransactionKind::ALL.get(index + 1).copied()
+            }
+        };
+        self.selected_index = 0;
+        self.details = None;
+        // pages are cut differently with another filter, the caller fetches the first one again
+        self.marker = None;
+        self.previous_markers.clear();
+        self.next_marker = None;
+    }
+}
+
+// The active account's sales matched to their buys, summarized per year and symbol
+#[derive(Clone, Debug, Default)]
+pub struct RealizedGainsView {
+    // None is as far back as E*TRADE keeps history
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub matching: LotMatching,
+    pub fills: Vec<Fill>,
+    // share movements that aren't trades it can match, the gains are off by them
+    pub skipped: Vec<String>,
+    pub selections: LotSelections,
+    pub report: Report,
+    pub summaries: Vec<GainSummary>,
+    pub selected_index: usize,
+    pub message: Option<String>,
+}
+
+impl RealizedGainsView {
+    pub fn set_fills(&mut self, fills: Vec<Fill>, selections: LotSelections, skipped: Vec<String>) {
+        self.fills = fills;
+        self.skipped = skipped;
+        self.selections = selections;
+        self.message = None;
+        self.recompute();
+    }
+
+    // Matching happens locally, switching methods doesn't refetch anything
+    pub fn toggle_matching(&mut self) {
+        self.matching = match self.matching {
+            LotMatching::Fifo => LotMatching::SpecificLots,
+            LotMatching::SpecificLots => LotMatching::Fifo,
+        };
+        self.recompute();
+    }
+
+    fn recompute(&mut self) {
+        self.report = realized_gains::realize(&self.fills, self.matching, &self.selections);
+        self.summaries = realized_gains::summarize(&self.report.gains);
+        if self.selected_index >= self.summaries.len() {
+            self.selected_index = 0;
+        }
+    }
+
+    pub fn selected(&self) -> Option<&GainSummary> {
+        self.summaries.get(self.selected_index)
+    }
+
+    // The lots behind the selected row, a year row covers every symbol sold that year
+    pub fn selected_gains(&self) -> Vec<&RealizedGain> {
+        match self.selected() {
+            Some(summary) => self.report.gains
+                .iter()
+                .filter(|gain| gain.sold.year() == summary.year)
+                .filter(|gain| summary.symbol.as_ref().is_none_or(|symbol| *symbol == gain.symbol))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn set_date_range(&mut self, start_date: NaiveDate, end_date: NaiveDate) {
+        self.start_date = Some(start_date);
+        self.end_date = Some(end_date);
+        self.selected_index = 0;
+    }
+}
+
+// E*TRADE wants the app authorized again mid-session, the verification code goes in the input box
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AuthorizationView {
+    pub authorize_url: String,
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PortfolioTotals {
+    pub total_cost: f64,
+    pub market_value: f64,
+    pub total_gain: f64,
+    pub total_gain_pct: f64,
+    pub days_gain: f64,
+}
+
+impl PortfolioTotals {
+    pub fn new(positions: &[PortfolioPosition]) -> Self {
+        let total_cost = positions.iter().map(|p| p.total_cost).sum::<f64>();
+        let total_gain = positions.iter().map(|p| p.total_gain).sum::<f64>();
+        Self {
+            total_cost,
+            market_value: positions.iter().map(|p| p.market_value).sum(),
+            total_gain,
+            total_gain_pct: if total_cost != 0.0 { total_gain / total_cost.abs() * 100.0 } else { 0.0 },
+            days_gain: positions.iter().map(|p| p.days_gain).sum(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PositionSort {
+    Symbol,
+    Quantity,
+    MarketValue,
+    DaysGain,
+    TotalGain,
+    TotalGainPct,
+}
+
+impl PositionSort {
+    pub const ALL: [PositionSort; 6] = [
+        PositionSort::Symbol,
+        PositionSort::Quantity,
+        PositionSort::MarketValue,
+        PositionSort::DaysGain,
+        PositionSort::TotalGain,
+        PositionSort::TotalGainPct,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PositionSort::Symbol => "Symbol",
+            PositionSort::Quantity => "Qty",
+            PositionSort::MarketValue => "Market Value",
+            PositionSort::DaysGain => "Day's Gain",
+            PositionSort::TotalGain => "Total Gain",
+            PositionSort::TotalGainPct => "Total Gain %",
+        }
+    }
+
+    fn value(&self, position: &PortfolioPosition) -> f64 {
+        match self {
+            PositionSort::Symbol => 0.0,
+            PositionSort::Quantity => position.quantity,
+            PositionSort::MarketValue => position.market_value,
+            PositionSort::DaysGain => position.days_gain,
+            PositionSort::TotalGain => position.total_gain,
+            PositionSort::TotalGainPct => position.total_gain_pct,
+        }
+    }
+
+    pub fn sort(&self, positions: &mut [PortfolioPosition], descending: bool) {
+        positions.sort_by(|a, b| {
+            let ordering = match self {
+                PositionSort::Symbol => a.ticker.symbol.cmp(&b.ticker.symbol),
+                _ => self.value(a).partial_cmp(&self.value(b)).unwrap_or(std::cmp::Ordering::Equal),
+            };
+            if descending { ordering.reverse() } else { ordering }
+        });
+    }
+}
+
+// Each option contract covers 100 shares of the underlying
+pub const OPTION_MULTIPLIER: f64 = 100.0;
+
+// A single option contract picked from the option chain
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionContract {
+    pub call_put: CallPut,
+    pub strike_price: f64,
+    pub expiration_date: ExpirationDate,
+    pub display_symbol: String,
+    pub bid: f64,
+    pub ask: f64,
+}
+
+impl OptionContract {
+    pub fn product(&self, symbol: &str) -> Product {
+        Product {
+            symbol: symbol.to_string(),
+            security_type: "OPTN".to_string(),
+            call_put: Some(self.call_put.to_string()),
+            expiry_year: Some(self.expiration_date.year),
+            expiry_month: Some(self.expiration_date.month),
+            expiry_day: Some(self.expiration_date.day),
+            strike_price: Some(self.strike_price),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct PreviewOrder {
     pub account_id: String,
+    // the account previewed and placed against
+    pub account_id_key: String,
+    // E*TRADE requires the same client_order_id on preview and place
+    pub client_order_id: String,
     pub quantity: String,
     pub symbol: String,
     pub order_type: OrderType,
     pub order_action: OrderAction,
+    pub price_type: PriceType,
+    pub order_term: OrderTerm,
+    pub market_session: MarketSession,
+    pub limit_price: String,
+    pub stop_price: String,
+    // set when changing an existing open order instead of placing a new one
+    pub order_id: Option<i64>,
+    // set for option orders, symbol is then the underlying
+    pub option: Option<OptionContract>,
+    // set for multi-leg option orders instead of option
+    pub legs: Vec<OptionLeg>,
+    // tax lots picked to sell, in the order they should be relieved
+    pub lots: Vec<Lot>,
 }
 
-// impl From<PreviewOrderRequest> for PreviewOrder {
-//     fn from(t: PreviewOrderRequest) -> PreviewOrder {
-//         PreviewOrder {
-//             account_id: t.order[0].account_id.to_owned(),
-//             quantity: t.order[0].instrument[0].quantity.to_owned(),
-//             symbol: t.order[0].instrument[0].product.symbol.to_owned(),
-//             order_type: OrderType::from_str(&t.order_type).unwrap(),
-//             order_action: OrderAction::from_str(&t.order[0].instrument[0].order_action.to_owned()),
-//         }
-//     }
-// }
+impl PreviewOrder {
+    // Pre-fill a change request from an order in the blotter
+    pub fn from_open_order(account: &Account, order: &OrderSummary) -> Result<PreviewOrder, String> {
+        let detail = match order.order_detail.first() {
+            Some(detail) if detail.status == "OPEN" || detail.status == "PARTIAL" => detail,
+            _ => return Err(format!("Order #{} is not open", order.order_id)),
+        };
+        let instrument = match detail.instrument.as_slice() {
+            [instrument] => instrument,
+            _ => return Err(format!("Order #{} has more than one leg", order.order_id)),
+        };
+        let price_type = PriceType::from_str(&detail.price_type)
+            .map_err(|_| format!("{} orders can't be changed here", detail.price_type))?;
+        let order_action = OrderAction::from_str(&instrument.order_action)
+            .map_err(|_| format!("{} orders can't be changed here", instrument.order_action))?;
+        let option = match (&instrument.product.call_put, instrument.product.strike_price) {
+            (Some(call_put), Some(strike_price)) => Some(OptionContract {
+                call_put: CallPut::from_str(call_put).unwrap_or(CallPut::CALL),
+                strike_price,
+                expiration_date: ExpirationDate {
+                    year: instrument.product.expiry_year.unwrap_or_default(),
+                    month: instrument.product.expiry_month.unwrap_or_default(),
+                    day: instrument.product.expiry_day.unwrap_or_default(),
+                    expiry_type: "".to_string(),
+                },
+                display_symbol: instrument.product.display(),
+                bid: 0.0,
+                ask: 0.0,
+            }),
+            _ => None,
+        };
+        let price = |price: Option<f64>| match price {
+            Some(price) if price > 0.0 => price.to_string(),
+            _ => "".to_string(),
+        };
+
+        Ok(PreviewOrder {
+            account_id: account.account_id.to_owned(),
+            account_id_key: account.account_id_key.to_owned(),
+            client_order_id: utils::simple_id(),
+            quantity: (instrument.ordered_quantity as u32).to_string(),
+            symbol: instrument.product.symbol.to_owned(),
+            order_type: OrderType::from_str(&order.order_type).unwrap_or(OrderType::EQ),
+            order_action,
+            price_type,
+            order_term: OrderTerm::from_str(&detail.order_term).unwrap_or(OrderTerm::GOOD_FOR_DAY),
+            market_session: MarketSession::from_str(&detail.market_session).unwrap_or(MarketSession::REGULAR),
+            limit_price: price(detail.limit_price),
+            stop_price: price(detail.stop_price),
+            order_id: Some(order.order_id),
+            option,
+            legs: vec![],
+            lots: vec![],
+        })
+    }
+
+    // (field, before, after) for every field that differs from the original order
+    pub fn changes(&self, original: &PreviewOrder) -> Vec<(&'static str, String, String)> {
+        let fields = [
+            ("Quantity", original.quantity.to_owned(), self.quantity.to_owned()),
+            ("Price type", original.price_type.to_string(), self.price_type.to_string()),
+            ("Limit price", original.limit_price.to_owned(), self.limit_price.to_owned()),
+            ("Stop price", original.stop_price.to_owned(), self.stop_price.to_owned()),
+            ("Term", original.order_term.to_string(), self.order_term.to_string()),
+            ("Session", original.market_session.to_string(), self.market_session.to_string()),
+        ];
+
+        fields.iter()
+            .filter(|(_, before, after)| before != after)
+            .cloned()
+            .collect()
+    }
+
+    fn order(&self) -> Order {
+        Order {
+            // account_id: "".to_string(),
+            // all_or_none: false,
+            price_type: self.price_type.to_string(),
+            order_term: self.order_term.to_string(),
+            market_session: self.market_session.to_string(),
+            limit_price: self.limit_price.to_owned(),
+            stop_price: self.stop_price.to_owned(),
+            instrument: self.instruments(),
+            ..Default::default()
+        }
+    }
+
+    // quantity is the number of spreads for multi-leg orders, each leg multiplies it by its ratio
+    fn instruments(&self) -> Vec<Instrument> {
+        if !self.legs.is_empty() {
+            let quantity = self.quantity.parse::<u32>().unwrap_or_default();
+            return self.legs
+                .iter()
+                .map(|leg| Instrument {
+                    quantity: (quantity * leg.ratio).to_string(),
+                    quantity_type: "QUANTITY".to_string(),
+                    order_action: leg.order_action.to_string(),
+                    product: leg.contract.product(&leg.symbol),
+                    lots: None,
+                })
+                .collect();
+        }
+
+        vec![
+            Instrument {
+                quantity: self.quantity.to_owned(),
+                quantity_type: "QUANTITY".to_string(),
+                order_action: self.order_action.to_string(),
+                product: self.product(),
+                lots: self.allocated_lots(),
+
+                // cancel_quantity: None,
+                // reserve_order: None,
+                // symbol_description: None,
+            }
+        ]
+    }
 
-impl From<PreviewOrderResponse> for PreviewOrder {
-    fn from(t: PreviewOrderResponse) -> PreviewOrder {
-        PreviewOrder {
-            account_id: "".to_string(),// t.order[0].account_id.to_owned(),
-            quantity: t.order[0].instrument[0].quantity.to_owned(),
-            symbol: t.order[0].instrument[0].product.symbol.to_owned(),
-            order_type: OrderType::from_str(&t.order_type).unwrap(),
-            order_action: OrderAction::from_str(&t.order[0].instrument[0].order_action.to_owned()).unwrap(),
+    // Spreads the quantity over the picked lots in order, the last one may only be partly sold
+    fn allocated_lots(&self) -> Option<Lots> {
+        let mut remaining = self.quantity.parse::<f64>().unwrap_or_default();
+        let mut lot = vec![];
+        for picked in &self.lots {
+            if remaining <= 0.0 {
+                break;
+            }
+            let size = picked.size.min(remaining);
+            lot.push(Lot { id: picked.id, size });
+            remaining -= size;
+        }
+
+        if lot.is_empty() { None } else { Some(Lots { lot }) }
+    }
+
+    // What the quantity counts
+    pub fn quantity_unit(&self) -> &'static str {
+        if !self.legs.is_empty() {
+            "spreads"
+        } else if self.option.is_some() {
+            "contracts"
+        } else {
+            "shares"
+        }
+    }
+
+    // Shares available in the picked lots, None when the account's default lot relief is used
+    pub fn lots_quantity(&self) -> Option<f64> {
+        if self.lots.is_empty() {
+            return None;
+        }
+        Some(self.lots.iter().map(|lot| lot.size).sum())
+    }
+
+    fn product(&self) -> Product {
+        match self.option {
+            Some(ref option) => option.product(&self.symbol),
+            None => Product {
+                symbol: self.symbol.to_owned(),
+                security_type: "EQ".to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn is_option(&self) -> bool {
+        self.option.is_some() || !self.legs.is_empty()
+    }
+
+    pub fn multiplier(&self) -> f64 {
+        if self.is_option() { OPTION_MULTIPLIER } else { 1.0 }
+    }
+
+    pub fn price_types(&self) -> &'static [PriceType] {
+        if self.legs.is_empty() { &PriceType::ALL } else { &PriceType::SPREADS }
+    }
+
+    // Prices for an option order are checked against the contract's quote, not the underlying
+    pub fn option_quote(&self) -> Option<Ticker> {
+        self.option.as_ref().map(|option| Ticker {
+            symbol: option.display_symbol.to_owned(),
+            bid: option.bid,
+            ask: option.ask,
+            ..Default::default()
+        })
+    }
+
+    // contracts x multiplier x price, priced at the limit if there is one or else the side of the
+    // quote a market order would take
+    pub fn estimated_premium(&self) -> Option<f64> {
+        let quantity = self.quantity.parse::<f64>().ok()?;
+        let natural = if !self.legs.is_empty() {
+            spread::net_price(&self.legs).abs()
+        } else {
+            let option = self.option.as_ref()?;
+            if self.is_buy() { option.ask } else { option.bid }
+        };
+        let price = self.limit_price.parse::<f64>().unwrap_or(natural);
+        Some(quantity * self.multiplier() * price)
+    }
+
+    pub fn is_buy(&self) -> bool {
+        matches!(self.order_action, OrderAction::BUY | OrderAction::BUY_TO_COVER | OrderAction::BUY_OPEN | OrderAction::BUY_CLOSE)
+    }
+
+    // Sanity check a limit or stop price against the current quote before E*TRADE sees it.
+    // Err rejects the price, Ok(Some(_)) accepts it with a warning for the user.
+    pub fn check_price(&self, state: &OrderFormState, price: f64, ticker: &Ticker) -> Result<Option<String>, String> {
+        // NaN and inf parse as prices too
+        if !price.is_finite() || price <= 0.0 {
+            return Err("Price must be greater than 0".to_string());
+        }
+
+        // an unquoted side comes through as 0
+        let bid = Some(ticker.bid).filter(|bid| *bid > 0.0);
+        let ask = Some(ticker.ask).filter(|ask| *ask > 0.0);
+
+        match (state, self.price_type) {
+            (OrderFormState::LimitPrice, PriceType::LIMIT) => {
+                match (self.is_buy(), bid, ask) {
+                    (true, _, Some(ask)) if price >= ask => {
+                        Ok(Some(format!("Limit is at or above the ask of ${:.2} and will likely fill immediately", ask)))
+                    }
+                    (false, Some(bid), _) if price <= bid => {
+                        Ok(Some(format!("Limit is at or below the bid of ${:.2} and will likely fill immediately", bid)))
+                    }
+                    _ => Ok(None),
+                }
+            }
+            (OrderFormState::StopPrice, PriceType::TRAILING_STOP_CNST) => {
+                let current = if self.is_buy() { ask } else { bid };
+                match current {
+                    Some(current) if price >= current => {
+                        Err(format!("Trail amount must be less than the current price of ${:.2}", current))
+                    }
+                    _ => Ok(None),
+                }
+            }
+            (OrderFormState::StopPrice, _) => {
+                match (self.is_buy(), bid, ask) {
+                    (true, _, Some(ask)) if price <= ask => {
+                        return Err(format!("Buy stop must be above the ask of ${:.2}", ask));
+                    }
+                    (false, Some(bid), _) if price >= bid => {
+                        return Err(format!("Sell stop must be below the bid of ${:.2}", bid));
+                    }
+                    _ => {}
+                }
+
+                if let (PriceType::STOP_LIMIT, Ok(limit)) = (self.price_type, self.limit_price.parse::<f64>()) {
+                    if self.is_buy() && limit < price {
+                        return Err(format!("Buy stop limit needs the stop at or below the limit of ${:.2}", limit));
+                    } else if !self.is_buy() && limit > price {
+                        return Err(format!("Sell stop limit needs the stop at or above the limit of ${:.2}", limit));
+                    }
+                }
+
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // E*TRADE only accepts day limit orders in the extended session
+    pub fn check_session(&self) -> Result<(), String> {
+        match self.market_session {
+            MarketSession::EXTENDED if self.is_option() => {
+                Err("Option orders only trade in the regular session".to_string())
+            }
+            MarketSession::EXTENDED if self.price_type != PriceType::LIMIT => {
+                Err("Extended hours orders must be limit orders".to_string())
+            }
+            MarketSession::EXTENDED if self.order_term != OrderTerm::GOOD_FOR_DAY => {
+                Err("Extended hours orders must be GOOD_FOR_DAY".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn session_warning(&self, market_hours: MarketHours) -> Option<String> {
+        match (self.market_session, market_hours) {
+            (MarketSession::REGULAR, MarketHours::Regular) => None,
+            (MarketSession::REGULAR, _) => {
+                Some("Regular session is closed, order will wait for the next open".to_string())
+            }
+            (MarketSession::EXTENDED, MarketHours::PreMarket) | (MarketSession::EXTENDED, MarketHours::AfterHours) => None,
+            (MarketSession::EXTENDED, _) => {
+                Some("Extended session is closed (pre-market 7:00-9:25am, after-hours 4:05-8:00pm ET)".to_string())
+            }
+        }
+    }
+
+    // The place request must echo the previewed order along with the preview ids E*TRADE handed
+    // back
+    pub fn place_order_request(&self, preview_ids: Vec<PreviewId>) -> PlaceOrderRequest {
+        PlaceOrderRequest {
+            place_order_request: EtradePlaceOrderRequest {
+                order_type: self.order_type.to_string(),
+                client_order_id: self.client_order_id.to_owned(),
+                order: vec![self.order()],
+                preview_ids,
+            }
         }
     }
 }
@@ -182,56 +1055,99 @@ impl From<PreviewOrder> for PreviewOrderRequest {
         PreviewOrderRequest {
             preview_order_request: EtradePreviewOrderRequest {
                 order_type: t.order_type.to_string(),
-                client_order_id: utils::simple_id(),
-                order: vec![Order {
-                    // account_id: "".to_string(),
-                    // all_or_none: false,
-                    price_type: "MARKET".to_string(),
-                    order_term: "GOOD_FOR_DAY".to_string(),
-                    market_session: "REGULAR".to_string(),
-                    // stop_price: "".to_string(),
-                    // limit_price: "".to_string(),
-                    instrument: vec![
-                        Instrument {
-                            quantity: t.quantity,
-                            quantity_type: "QUANTITY".to_string(),
-                            order_action: t.order_action.to_string(),
-                            product: Product {
-                                symbol: t.symbol,
-                                security_type: "EQ".to_string(),
-                            },
-
-                            // cancel_quantity: None,
-                            // reserve_order: None,
-                            // symbol_description: None,
-                        }
-                    ],
-                    ..Default::default()
-                }]
+                client_order_id: t.client_order_id.to_owned(),
+                order: vec![t.order()],
             }
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum OrderFormState {
     Initial,
+    OptionAction,
     Quantity,
+    PriceType,
+    LimitPrice,
+    StopPrice,
+    OrderTerm,
+    MarketSession,
     Submit,
 }
 
+impl OrderFormState {
+    // steps that read their value from the input box
+    pub fn is_input(&self) -> bool {
+        matches!(self, OrderFormState::Quantity | OrderFormState::LimitPrice | OrderFormState::StopPrice)
+    }
+
+    // steps that pick from a fixed list with up/down
+    pub fn is_select(&self) -> bool {
+        matches!(self, OrderFormState::OptionAction | OrderFormState::PriceType | OrderFormState::OrderTerm | OrderFormState::MarketSession)
+    }
+}
+
 #[derive(Clone, Debug)]
-pub struct OptionChain {}
+pub struct OptionChain {
+    pub symbol: String,
+    pub expiration_dates: Vec<ExpirationDate>,
+    pub selected_expiry_index: usize,
+    pub near_price: f64,
+    pub quote_type: String,
+    pub option_pairs: Vec<OptionPair>,
+}
+
+impl OptionChain {
+    pub fn new(symbol: String, expiration_dates: Vec<ExpirationDate>) -> Self {
+        Self {
+            symbol,
+            expiration_dates,
+            selected_expiry_index: 0,
+            near_price: 0.0,
+            quote_type: "".to_string(),
+            option_pairs: vec![],
+        }
+    }
+
+    pub fn selected_expiry(&self) -> Option<&ExpirationDate> {
+        self.expiration_dates.get(self.selected_expiry_index)
+    }
+
+    pub fn update(&mut self, option_chain_response: OptionChainResponse) {
+        self.near_price = option_chain_response.near_price;
+        self.quote_type = option_chain_response.quote_type;
+        self.option_pairs = option_chain_response.option_pairs;
+    }
+
+    // first strike at or above the underlying so the table opens around the money
+    pub fn near_the_money_index(&self) -> usize {
+        self.option_pairs
+            .iter()
+            .position(|pair| pair.strike_price() >= self.near_price)
+            .unwrap_or(0)
+    }
+}
 
 pub enum SearchType {
     Ticker,
-    OptionChain, // not Option b/c language keyword
+}
+
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, forward: bool) -> T {
+    let index = options.iter().position(|o| *o == current).unwrap_or(0);
+    let len = options.len();
+    let next_index = if forward { (index + 1) % len } else { (index + len - 1) % len };
+    options[next_index]
+}
+
+// Open accounts that can hold positions, older responses without institutionType count too
+fn is_brokerage(account: &Account) -> bool {
+    account.account_status != "CLOSED"
+        && (account.institution_type.is_empty() || account.institution_type == "BROKERAGE")
 }
 
 #[derive(Debug)]
 pub struct SearchResult {
     pub tickers: Option<Vec<Ticker>>,
-    pub option_chains: Option<Vec<OptionChain>>,
     pub selected_ticker_index: Option<usize>,
 }
 
@@ -239,7 +1155,6 @@ impl SearchResult {
     pub fn tickers(tickers: Vec<Ticker>) -> Self {
         Self {
             tickers: Some(tickers),
-            option_chains: None,
             selected_ticker_index: Some(0),
         }
     }
@@ -262,57 +1177,145 @@ impl SearchResult {
 pub struct SelectedTicker {
     pub ticker: Ticker,
     pub selected_index: usize,
+    pub detail_flag: DetailFlag,
+}
+
+impl From<QuoteData> for Ticker {
+    fn from(t: QuoteData) -> Ticker {
+        let info = t.all.or(t.fundamental).or(t.intraday).or(t.option).unwrap_or_default();
+        Ticker {
+            symbol: t.product.symbol,
+            description: info.company_name,
+            date_time: t.date_time,
+            security_type: t.product.security_type,
+            primary_exchange: info.primary_exchange,
+            declared_dividend: info.declared_dividend,
+            dividend: info.dividend,
+            ex_dividend_date: info.ex_dividend_date,
+            bid: info.bid,
+            bid_size: info.bid_size,
+            ask: info.ask,
+            ask_size: info.ask_size,
+            last_trade: info.last_trade,
+            change_close: info.change_close,
+            change_close_percentage: info.change_close_percentage,
+            previous_close: info.previous_close,
+            open: info.open,
+            high: info.high,
+            low: info.low,
+            total_volume: info.total_volume,
+            high52: info.high52,
+            week52_hi_date: info.week52_hi_date,
+            low52: info.low52,
+            week52_low_date: info.week52_low_date,
+            pe: info.pe,
+            eps: info.eps,
+            est_earnings: info.est_earnings,
+            beta: info.beta,
+            market_cap: info.market_cap,
+            shares_outstanding: info.shares_outstanding,
+            next_earning_date: info.next_earning_date,
+            open_interest: info.open_interest,
+            days_to_expiration: info.days_to_expiration,
+            intrinsic_value: info.intrinsic_value,
+            time_premium: info.time_premium,
+            option_multiplier: info.option_multiplier,
+        }
+    }
 }
 
 impl From<TickerXML> for SelectedTicker {
     fn from(t: TickerXML) -> SelectedTicker {
-        let ticker = Ticker {
-            symbol: t.quote_data.product.symbol,
-            date_time: t.quote_data.date_time,
-            security_type: t.quote_data.product.security_type,
-            primary_exchange: t.quote_data.info.primary_exchange,
-            declared_dividend: t.quote_data.info.declared_dividend,
-            dividend: t.quote_data.info.dividend,
-            ex_dividend_date: t.quote_data.info.ex_dividend_date,
-            bid: t.quote_data.info.bid,
-            ask: t.quote_data.info.ask,
-            open: t.quote_data.info.open,
-            high52: t.quote_data.info.high52,
-            week52_hi_date: t.quote_data.info.week52_hi_date,
-            low52: t.quote_data.info.low52,
-            week52_low_date: t.quote_data.info.week52_low_date,
-            pe: t.quote_data.info.pe,
-            eps: t.quote_data.info.eps,
-            beta: t.quote_data.info.beta,
-            ..Default::default()
-        };
-
         SelectedTicker {
-            ticker,
+            ticker: t.quote_data.into(),
             selected_index: 0,
+            detail_flag: DetailFlag::ALL,
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct SelectedOptionChain {
     pub option_chain: OptionChain,
     pub selected_index: usize,
+    // which side of the selected strike an order would be for
+    pub call_put: CallPut,
+}
+
+// Price history for the selected ticker's chart
+#[derive(Clone, Debug)]
+pub struct PriceChart {
+    pub symbol: String,
+    pub range: ChartRange,
+    pub bars: Vec<Bar>,
+    // why there is nothing to chart
+    pub message: Option<String>,
+}
+
+impl PriceChart {
+    pub fn new(symbol: String, range: ChartRange) -> Self {
+        Self {
+            symbol,
+            range,
+            bars: vec![],
+            message: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Indicator {
+    Sma,
+    Ema,
+    Bollinger,
+    Rsi,
+    Macd,
+}
+
+impl Indicator {
+    pub const ALL: [Indicator; 5] = [
+        Indicator::Sma,
+        Indicator::Ema,
+        Indicator::Bollinger,
+        Indicator::Rsi,
+        Indicator::Macd,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Indicator::Sma => "SMA",
+            Indicator::Ema => "EMA",
+            Indicator::Bollinger => "Bollinger",
+            Indicator::Rsi => "RSI",
+            Indicator::Macd => "MACD",
+        }
+    }
+
+    // key that toggles it on the analysis screen
+    pub fn key(&self) -> char {
+        match self {
+            Indicator::Sma => 's',
+            Indicator::Ema => 'e',
+            Indicator::Bollinger => 'b',
+            Indicator::Rsi => 'r',
+            Indicator::Macd => 'm',
+        }
+    }
 }
 
 // Watch Lists
 #[derive(Clone)]
 pub struct Library {
     pub selected_index: usize,
-    pub saved_tickers: Vec<Ticker>,
-    pub saved_option_chains: Vec<OptionChain>,
+    pub watch_lists: WatchLists,
+    // the input box is naming a new watch list rather than searching
+    pub naming_watch_list: bool,
 }
 
 pub struct User {}
 
 pub struct App {
     pub user_config: UserConfig,
-    pub major_indices: WatchList,
     navigation_stack: Vec<Route>,
     pub api_error: String,
     // Inputs:
@@ -324,8 +1327,6 @@ pub struct App {
     pub input: Vec<char>,
     pub input_idx: usize,
     pub input_cursor_position: u16,
-    pub liked_ticker_ids_set: HashSet<String>,
-    pub saved_ticker_ids_set: HashSet<String>,
 
     pub active_ticker_index: Option<usize>,
     pub selected_watch_list_index: Option<usize>,
@@ -334,20 +1335,55 @@ pub struct App {
     pub selected_notification_index: usize,
 
     pub selected_ticker: Option<SelectedTicker>,
+    pub price_chart: Option<PriceChart>,
+    pub chart_range: ChartRange,
+    // daily bars behind the analysis screen
+    pub analysis: Option<PriceChart>,
+    pub indicators: Vec<Indicator>,
 
     pub user_accounts: Option<Vec<Account>>,
     pub selected_account_index: Option<usize>,
     pub active_account_index: Option<usize>,
+    // the portfolio merges every brokerage account, orders still go to the active one
+    pub all_accounts: bool,
 
     pub notifications: Option<Vec<Alert>>,
     pub total_notifications: Option<u32>,
 
     pub library: Library,
-    pub portfolio_tickers: Option<Vec<Ticker>>,
+    pub positions: Option<Vec<PortfolioPosition>>,
+    pub positions_sort: PositionSort,
+    pub positions_sort_descending: bool,
+    pub selected_position_index: usize,
+    pub position_lots: Option<PositionLots>,
+    pub transactions: TransactionsView,
+    pub realized_gains: RealizedGainsView,
+    // latest polled quote for each visible symbol
+    pub quotes: HashMap<String, Ticker>,
+    pub quote_ticks: HashMap<String, QuoteTick>,
+    pub quotes_updated_at: Option<DateTime<Utc>>,
 
     pub preview_order_form: Option<PreviewOrder>,
+    // the open order as it was before changing it
+    pub original_order_form: Option<PreviewOrder>,
     pub preview_order_ticker: Option<String>,
+    pub preview_order_response: Option<PreviewOrderResponse>,
+    pub place_order_response: Option<PlaceOrderResponse>,
+    // set from Enter on the confirm screen until the place request comes back, so the order only goes out once
+    pub placing_order: bool,
     pub order_form_state: OrderFormState,
+    pub order_form_error: Option<String>,
+    pub order_form_warning: Option<String>,
+
+    pub orders: Option<Vec<OrderSummary>>,
+    pub selected_order_index: usize,
+    pub orders_status_filter: Option<StatusType>,
+    pub orders_symbol_filter: Option<String>,
+    pub orders_message: Option<String>,
+
+    pub selected_option_chain: Option<SelectedOptionChain>,
+    pub spread_legs: Vec<OptionLeg>,
+    pub selected_spread_leg_index: usize,
 
     pub large_search_limit: u32,
     pub search_results: SearchResult,
@@ -362,8 +1398,8 @@ pub struct App {
     pub help_menu_offset: u32,
     pub is_loading: bool,
     io_tx: Option<Sender<IoEvent>>,
-    pub etrade_token_expiry: DateTime<Utc>,
     pub dialog: Option<String>,
+    pub authorization: Option<AuthorizationView>,
     pub confirm: bool,
 
 }
@@ -374,26 +1410,51 @@ impl Default for App {
             user_config: UserConfig::new(),
             recently_searched: Default::default(),
             size: Rect::default(),
-            major_indices: WatchList {
-                selected_index: 0,
-            },
-
             library: Library {
                 selected_index: 0,
-                saved_tickers: vec![],
-                saved_option_chains: vec![],
+                watch_lists: WatchLists::default(),
+                naming_watch_list: false,
             },
 
-            portfolio_tickers: None,
+            positions: None,
+            positions_sort: PositionSort::MarketValue,
+            positions_sort_descending: true,
+            selected_position_index: 0,
+            position_lots: None,
+            transactions: TransactionsView::default(),
+            realized_gains: RealizedGainsView::default(),
+            quotes: HashMap::new(),
+            quote_ticks: HashMap::new(),
+            quotes_updated_at: None,
 
             preview_order_form: None,
+            original_order_form: None,
             preview_order_ticker: None,
+            preview_order_response: None,
+            place_order_response: None,
+            placing_order: false,
             order_form_state: OrderFormState::Initial,
+            order_form_error: None,
+            order_form_warning: None,
+
+            orders: None,
+            selected_order_index: 0,
+            orders_status_filter: Some(StatusType::OPEN),
+            orders_symbol_filter: None,
+            orders_message: None,
+
+            selected_option_chain: None,
+            spread_legs: vec![],
+            selected_spread_leg_index: 0,
 
             selected_notification: None,
             selected_notification_index: 0,
 
             selected_ticker: None,
+            price_chart: None,
+            chart_range: ChartRange::OneMonth,
+            analysis: None,
+            indicators: vec![Indicator::Sma, Indicator::Rsi],
 
             active_ticker_index: None,
             selected_watch_list_index: None,
@@ -401,6 +1462,7 @@ impl Default for App {
             user_accounts: None,
             active_account_index: None,
             selected_account_index: None,
+            all_accounts: false,
 
             notifications: None,
             total_notifications: None,
@@ -412,13 +1474,10 @@ impl Default for App {
             input: vec![],
             input_idx: 0,
             input_cursor_position: 0,
-            liked_ticker_ids_set: HashSet::new(),
-            saved_ticker_ids_set: HashSet::new(),
             search_term: "".to_string(),
             search_results: SearchResult {
                 selected_ticker_index: None,
                 tickers: None,
-                option_chains: None,
             },
             user: None,
             help_docs_size: 0,
@@ -427,8 +1486,8 @@ impl Default for App {
             help_menu_offset: 0,
             is_loading: false,
             io_tx: None,
-            etrade_token_expiry: utils::now_eastern(),
             dialog: None,
+            authorization: None,
             confirm: false,
         }
     }
@@ -439,32 +1498,234 @@ impl App {
     pub fn new(
         io_tx: Sender<IoEvent>,
         user_config: UserConfig,
-        etrade_token_expiry: DateTime<Utc>,
     ) -> Self {
         Self {
             io_tx: Some(io_tx),
             user_config,
-            etrade_token_expiry,
             ..App::default()
         }
     }
 
     pub fn new_preview_order(&mut self, order_type: OrderType, order_action: OrderAction) {
-        if let Some(active_account_index) = self.active_account_index {
-            let account_id = &self.user_accounts.as_ref().unwrap()[active_account_index].account_id;
-            self.preview_order_form = Some(PreviewOrder {
-                account_id: account_id.to_string(),
-                order_type,
-                order_action,
-                symbol: "".to_string(),
-                quantity: "".to_string(),
-            })
+        if let Some(account) = self.active_account() {
+            let (account_id, account_id_key) = (account.account_id.to_owned(), account.account_id_key.to_owned());
+            self.new_preview_order_in(account_id, account_id_key, order_type, order_action);
+        }
+    }
+
+    fn new_preview_order_in(&mut self, account_id: String, account_id_key: String, order_type: OrderType, order_action: OrderAction) {
+        self.preview_order_form = Some(PreviewOrder {
+            account_id,
+            account_id_key,
+            client_order_id: utils::simple_id(),
+            order_type,
+            order_action,
+            price_type: PriceType::MARKET,
+            order_term: OrderTerm::GOOD_FOR_DAY,
+            market_session: MarketSession::REGULAR,
+            symbol: "".to_string(),
+            quantity: "".to_string(),
+            limit_price: "".to_string(),
+            stop_price: "".to_string(),
+            order_id: None,
+            option: None,
+            legs: vec![],
+            lots: vec![],
+        })
+    }
+
+    pub fn selected_option_contract(&self) -> Option<OptionContract> {
+        let selected = self.selected_option_chain.as_ref()?;
+        let option_chain = &selected.option_chain;
+        let pair = option_chain.option_pairs.get(selected.selected_index)?;
+        let details = match selected.call_put {
+            CallPut::CALL => pair.call.as_ref(),
+            CallPut::PUT => pair.put.as_ref(),
+        }?;
+        let expiration_date = option_chain.selected_expiry()?.clone();
+        let display_symbol = if details.display_symbol.is_empty() {
+            format!("{} {} ${:.2} {}", option_chain.symbol, expiration_date.to_string(), details.strike_price, selected.call_put.to_string())
+        } else {
+            details.display_symbol.to_owned()
+        };
+
+        Some(OptionContract {
+            call_put: selected.call_put,
+            strike_price: details.strike_price,
+            expiration_date,
+            display_symbol,
+            bid: details.bid,
+            ask: details.ask,
+        })
+    }
+
+    // Start an order for the contract selected in the option chain
+    pub fn new_option_order(&mut self, order_action: OrderAction) {
+        let (symbol, option) = match (&self.selected_option_chain, self.selected_option_contract()) {
+            (Some(selected), Some(option)) => (selected.option_chain.symbol.to_owned(), option),
+            _ => return,
+        };
+
+        self.new_preview_order(OrderType::OPTN, order_action);
+        match self.preview_order_form {
+            Some(ref mut order_form) => {
+                order_form.symbol = symbol;
+                order_form.option = Some(option.clone());
+            }
+            None => return,
+        }
+        self.preview_order_ticker = Some(option.display_symbol);
+        self.order_form_state = OrderFormState::OptionAction;
+
+        // Dbl push so Esc "back" works
+        self.push_navigation_stack(RouteId::OrderForm, ActiveBlock::OrderForm);
+        self.push_navigation_stack(RouteId::OrderForm, ActiveBlock::OrderForm);
+    }
+
+    // Show a newly loaded chain, the spread builder starts over when it's for another underlying
+    pub fn set_option_chain(&mut self, option_chain: OptionChain) {
+        if self.spread_legs.iter().any(|leg| leg.symbol != option_chain.symbol) {
+            self.spread_legs = vec![];
+            self.selected_spread_leg_index = 0;
+        }
+        let selected_index = option_chain.near_the_money_index();
+        self.selected_option_chain = Some(SelectedOptionChain { option_chain, selected_index, call_put: CallPut::CALL });
+    }
+
+    pub fn add_spread_leg(&mut self) {
+        let symbol = match self.selected_option_chain {
+            Some(ref selected) => selected.option_chain.symbol.to_owned(),
+            None => return,
+        };
+        if let Some(contract) = self.selected_option_contract() {
+            self.spread_legs.push(OptionLeg::new(symbol, contract));
+            self.selected_spread_leg_index = self.spread_legs.len() - 1;
+        }
+    }
+
+    pub fn remove_spread_leg(&mut self) {
+        if self.selected_spread_leg_index < self.spread_legs.len() {
+            self.spread_legs.remove(self.selected_spread_leg_index);
+            self.selected_spread_leg_index = self.selected_spread_leg_index.min(self.spread_legs.len().saturating_sub(1));
+        }
+    }
+
+    pub fn cycle_spread_leg_action(&mut self) {
+        if let Some(leg) = self.spread_legs.get_mut(self.selected_spread_leg_index) {
+            leg.order_action = cycle(&OrderAction::OPTIONS, leg.order_action, true);
         }
     }
 
+    pub fn change_spread_leg_ratio(&mut self, increase: bool) {
+        if let Some(leg) = self.spread_legs.get_mut(self.selected_spread_leg_index) {
+            leg.ratio = if increase { leg.ratio + 1 } else { std::cmp::max(leg.ratio - 1, 1) };
+        }
+    }
+
+    // Start a multi-leg order for the legs in the spread builder
+    pub fn new_spread_order(&mut self) {
+        let legs = self.spread_legs.clone();
+        // every leg has to be on the same underlying
+        let symbol = match legs.first() {
+            Some(first) if legs.len() > 1 && legs.iter().all(|leg| leg.symbol == first.symbol) => first.symbol.to_owned(),
+            _ => return,
+        };
+        let order_type = spread::order_type(&legs);
+
+        self.new_preview_order(order_type.clone(), legs[0].order_action);
+        match self.preview_order_form {
+            Some(ref mut order_form) => {
+                order_form.symbol = symbol.to_owned();
+                order_form.price_type = if spread::net_price(&legs) >= 0.0 { PriceType::NET_DEBIT } else { PriceType::NET_CREDIT };
+                order_form.legs = legs;
+            }
+            None => return,
+        }
+        self.preview_order_ticker = Some(format!("{} {}", symbol, order_type.to_string()));
+        self.order_form_state = OrderFormState::Quantity;
+
+        // Dbl push so Esc "back" works
+        self.push_navigation_stack(RouteId::OrderForm, ActiveBlock::OrderForm);
+        self.push_navigation_stack(RouteId::OrderForm, ActiveBlock::Input);
+    }
+
+    pub fn toggle_option_side(&mut self) {
+        if let Some(ref mut selected) = self.selected_option_chain {
+            selected.call_put = match selected.call_put {
+                CallPut::CALL => CallPut::PUT,
+                CallPut::PUT => CallPut::CALL,
+            };
+        }
+    }
+
+    // Load an open order from the blotter into the order form so it can be changed
+    pub fn change_order(&mut self, order_id: i64) -> Result<(), String> {
+        let account = match self.active_account() {
+            Some(account) => account,
+            None => return Err("No active account".to_string()),
+        };
+        let order = match self.orders.as_ref().and_then(|orders| orders.iter().find(|o| o.order_id == order_id)) {
+            Some(order) => order,
+            None => return Err(format!("Order #{} not found", order_id)),
+        };
+        let order_form = PreviewOrder::from_open_order(account, order)?;
+
+        self.preview_order_ticker = Some(order_form.symbol.to_owned());
+        self.prefill_input(&order_form.quantity);
+        self.original_order_form = Some(order_form.clone());
+        self.preview_order_form = Some(order_form);
+        self.order_form_state = OrderFormState::Quantity;
+
+        // Dbl push so Esc "back" works
+        self.push_navigation_stack(RouteId::OrderForm, ActiveBlock::OrderForm);
+        self.push_navigation_stack(RouteId::OrderForm, ActiveBlock::Input);
+        Ok(())
+    }
+
+    pub fn order_form_changes(&self) -> Vec<(&'static str, String, String)> {
+        match (&self.preview_order_form, &self.original_order_form) {
+            (Some(order_form), Some(original)) => order_form.changes(original),
+            _ => vec![],
+        }
+    }
+
+    fn prefill_input(&mut self, value: &str) {
+        self.input = value.chars().collect();
+        self.input_idx = self.input.len();
+        self.input_cursor_position = self.input.len() as u16;
+    }
+
     pub fn cancel_preview_order(&mut self) {
         // TODO: confirm modal
         self.preview_order_form = None;
+        self.original_order_form = None;
+        self.preview_order_response = None;
+        self.place_order_response = None;
+        self.order_form_error = None;
+        self.order_form_warning = None;
+    }
+
+    // Leave the order flow entirely once an order has been placed, landing back on whatever
+    // screen the order was started from
+    pub fn finish_order(&mut self) {
+        // a changed order should show up with its new values in the blotter
+        let changed = self.original_order_form.is_some() && self.place_order_response.is_some();
+        // the spread builder starts over once its legs have been placed
+        if matches!(self.preview_order_form, Some(ref order_form) if !order_form.legs.is_empty()) && self.place_order_response.is_some() {
+            self.spread_legs = vec![];
+            self.selected_spread_leg_index = 0;
+        }
+        self.cancel_preview_order();
+        self.preview_order_ticker = None;
+        self.order_form_state = OrderFormState::Initial;
+        while let RouteId::OrderForm | RouteId::ConfirmOrderForm = self.get_current_route().id {
+            if self.pop_navigation_stack().is_none() {
+                break;
+            }
+        }
+        if changed {
+            self.dispatch(IoEvent::GetOrders);
+        }
     }
 
     pub fn add_next_order_field(&mut self, key: &str, value: String) {
@@ -479,10 +1740,393 @@ impl App {
                     order_form.quantity = value;
                 }
             }
+            "limit_price" => {
+                if let Some(ref mut order_form) = self.preview_order_form {
+                    order_form.limit_price = value;
+                }
+            }
+            "stop_price" => {
+                if let Some(ref mut order_form) = self.preview_order_form {
+                    order_form.stop_price = value;
+                }
+            }
             _ => {},
         }
     }
 
+    // Each order form step gets its own route so Esc walks back one step at a time
+    pub fn next_order_form_state(&mut self) {
+        let price_type = match self.preview_order_form {
+            Some(ref order_form) => order_form.price_type,
+            None => return,
+        };
+
+        let next_state = match self.order_form_state {
+            OrderFormState::Initial | OrderFormState::OptionAction => OrderFormState::Quantity,
+            OrderFormState::Quantity => OrderFormState::PriceType,
+            OrderFormState::PriceType if price_type.needs_limit_price() => OrderFormState::LimitPrice,
+            OrderFormState::PriceType | OrderFormState::LimitPrice if price_type.needs_stop_price() => OrderFormState::StopPrice,
+            OrderFormState::PriceType | OrderFormState::LimitPrice | OrderFormState::StopPrice => OrderFormState::OrderTerm,
+            OrderFormState::OrderTerm => OrderFormState::MarketSession,
+            _ => OrderFormState::Submit,
+        };
+
+        self.order_form_state = next_state;
+        self.order_form_error = None;
+        let prefill = match (next_state, &self.preview_order_form) {
+            (OrderFormState::LimitPrice, Some(order_form)) => order_form.limit_price.to_owned(),
+            (OrderFormState::StopPrice, Some(order_form)) => order_form.stop_price.to_owned(),
+            _ => "".to_string(),
+        };
+        if !prefill.is_empty() {
+            self.prefill_input(&prefill);
+        }
+        if next_state.is_input() {
+            self.push_navigation_stack(RouteId::OrderForm, ActiveBlock::Input);
+        } else {
+            self.push_navigation_stack(RouteId::OrderForm, ActiveBlock::OrderForm);
+        }
+    }
+
+    pub fn previous_order_form_state(&mut self) {
+        let (price_type, is_option) = match self.preview_order_form {
+            Some(ref order_form) => (order_form.price_type, order_form.option.is_some()),
+            None => (PriceType::MARKET, false),
+        };
+
+        self.order_form_state = match self.order_form_state {
+            OrderFormState::Submit => OrderFormState::MarketSession,
+            OrderFormState::MarketSession => OrderFormState::OrderTerm,
+            OrderFormState::OrderTerm if price_type.needs_stop_price() => OrderFormState::StopPrice,
+            OrderFormState::OrderTerm | OrderFormState::StopPrice if price_type.needs_limit_price() => OrderFormState::LimitPrice,
+            OrderFormState::OrderTerm | OrderFormState::StopPrice | OrderFormState::LimitPrice => OrderFormState::PriceType,
+            OrderFormState::PriceType => OrderFormState::Quantity,
+            OrderFormState::Quantity if is_option => OrderFormState::OptionAction,
+            _ => OrderFormState::Initial,
+        };
+        self.order_form_error = None;
+        self.order_form_warning = None;
+        self.pop_navigation_stack();
+    }
+
+    // Move the selection for whichever choice the current order form step asks for
+    pub fn cycle_order_form_option(&mut self, forward: bool) {
+        if let Some(ref mut order_form) = self.preview_order_form {
+            match self.order_form_state {
+                OrderFormState::OptionAction => {
+                    order_form.order_action = cycle(&OrderAction::OPTIONS, order_form.order_action, forward);
+                }
+                OrderFormState::PriceType => {
+                    order_form.price_type = cycle(order_form.price_types(), order_form.price_type, forward);
+                    order_form.limit_price = "".to_string();
+                    order_form.stop_price = "".to_string();
+                }
+                OrderFormState::OrderTerm => {
+                    order_form.order_term = cycle(&OrderTerm::ALL, order_form.order_term, forward);
+                }
+                OrderFormState::MarketSession => {
+                    order_form.market_session = cycle(&MarketSession::ALL, order_form.market_session, forward);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn selected_order(&self) -> Option<&OrderSummary> {
+        self.orders.as_ref().and_then(|orders| orders.get(self.selected_order_index))
+    }
+
+    // Returns true when the expiry moved and the chain needs to be fetched again
+    pub fn cycle_option_expiry(&mut self, forward: bool) -> bool {
+        if let Some(ref mut selected) = self.selected_option_chain {
+            let option_chain = &mut selected.option_chain;
+            let len = option_chain.expiration_dates.len();
+            if len > 1 {
+                let index = option_chain.selected_expiry_index;
+                option_chain.selected_expiry_index = if forward { (index + 1) % len } else { (index + len - 1) % len };
+                selected.selected_index = 0;
+                return true;
+            }
+        }
+        false
+    }
+
+    // Symbols on screen that the quote refresher keeps up to date
+    pub fn visible_symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.library.watch_lists.active_symbols().to_vec();
+        if let Some(positions) = &self.positions {
+            symbols.extend(positions.iter().map(|p| p.ticker.symbol.to_owned()));
+        }
+        if let Some(selected_ticker) = &self.selected_ticker {
+            symbols.push(selected_ticker.ticker.symbol.to_owned());
+        }
+
+        let mut seen = HashSet::new();
+        symbols.retain(|symbol| !symbol.is_empty() && seen.insert(symbol.to_uppercase()));
+        symbols
+    }
+
+    pub fn update_quotes(&mut self, quotes: Vec<Ticker>, now: DateTime<Utc>) {
+        for quote in quotes {
+            let key = quote.symbol.to_uppercase();
+            if let Some(previous) = self.quotes.get(&key) {
+                if previous.bid != quote.bid || previous.ask != quote.ask || previous.last_trade != quote.last_trade {
+                    let tick = QuoteTick { previous: previous.clone(), at: now };
+                    self.quote_ticks.insert(key.to_owned(), tick);
+                }
+            }
+
+            if let Some(ref mut selected_ticker) = self.selected_ticker {
+                if selected_ticker.ticker.symbol.eq_ignore_ascii_case(&quote.symbol) {
+                    selected_ticker.ticker.update_quote(&quote);
+                }
+            }
+            for position in self.positions.iter_mut().flatten() {
+                if position.ticker.symbol.eq_ignore_ascii_case(&quote.symbol) {
+                    position.ticker.update_quote(&quote);
+                }
+            }
+            self.quotes.insert(key, quote);
+        }
+        self.quotes_updated_at = Some(now);
+    }
+
+    // Some(true) when the value went up on the latest refresh, Some(false) when it went down,
+    // for as long as the change should stay highlighted
+    pub fn quote_flash(&self, symbol: &str, value: fn(&Ticker) -> f64, now: DateTime<Utc>) -> Option<bool> {
+        let key = symbol.to_uppercase();
+        let tick = self.quote_ticks.get(&key)?;
+        if now - tick.at > chrono::Duration::milliseconds(QUOTE_FLASH_MILLIS) {
+            return None;
+        }
+
+        let current = value(self.quotes.get(&key)?);
+        let previous = value(&tick.previous);
+        if current > previous {
+            Some(true)
+        } else if current < previous {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub fn selected_watch_list_symbol(&self) -> Option<String> {
+        self.library.watch_lists.active_symbols().get(self.library.selected_index).cloned()
+    }
+
+    pub fn cycle_watch_list(&mut self, forward: bool) {
+        self.library.watch_lists.cycle(forward);
+        self.library.selected_index = 0;
+        self.dispatch(IoEvent::SaveWatchLists);
+    }
+
+    // Adds the symbol to the active watch list, or removes it when it is already there
+    pub fn toggle_watch_list_symbol(&mut self, symbol: String) {
+        if self.library.watch_lists.contains(&symbol) {
+            self.dispatch(IoEvent::CurrentUserSavedTickerDelete(symbol));
+        } else {
+            self.dispatch(IoEvent::CurrentUserSavedTickerAdd(symbol));
+        }
+    }
+
+    // The next quote detail block for the ticker detail screen, None without a selected ticker
+    pub fn next_detail_flag(&self) -> Option<DetailFlag> {
+        let selected_ticker = self.selected_ticker.as_ref()?;
+        Some(cycle(&DetailFlag::FLAGS, selected_ticker.detail_flag, true))
+    }
+
+    pub fn next_chart_range(&self, forward: bool) -> ChartRange {
+        cycle(&ChartRange::ALL, self.chart_range, forward)
+    }
+
+    pub fn toggle_indicator(&mut self, indicator: Indicator) {
+        if self.indicators.contains(&indicator) {
+            self.indicators.retain(|i| *i != indicator);
+        } else {
+            self.indicators.push(indicator);
+        }
+    }
+
+    // Rows of the account list, None being "All accounts" when there is more than one to merge
+    pub fn account_choices(&self) -> Vec<Option<usize>> {
+        let accounts = self.user_accounts.as_deref().unwrap_or_default();
+        let mut choices = (0..accounts.len()).map(Some).collect::<Vec<Option<usize>>>();
+        if accounts.iter().filter(|account| is_brokerage(account)).count() > 1 {
+            choices.push(None);
+        }
+        choices
+    }
+
+    // A fresh account list, from startup or an authorization mid-session. The active and selected accounts stay
+    // put while they're in it, the first one stands in otherwise
+    pub fn set_user_accounts(&mut self, accounts: Vec<Account>) {
+        let old_accounts = self.user_accounts.take().unwrap_or_default();
+        let index_of = |index: Option<usize>| {
+            let account_id = &old_accounts.get(index?)?.account_id;
+            accounts.iter().position(|account| &account.account_id == account_id)
+        };
+        let first = if accounts.is_empty() { None } else { Some(0) };
+        self.active_account_index = index_of(self.active_account_index).or(first);
+        self.selected_account_index = index_of(self.selected_account_index).or(first);
+        self.user_accounts = Some(accounts);
+    }
+
+    pub fn active_account(&self) -> Option<&Account> {
+        self.active_account_index.and_then(|index| self.user_accounts.as_ref()?.get(index))
+    }
+
+    pub fn is_active_account(&self, choice: Option<usize>) -> bool {
+        match choice {
+            Some(_) => !self.all_accounts && self.active_account_index == choice,
+            None => self.all_accounts,
+        }
+    }
+
+    // Makes an account, or all of them, active and reloads whatever was shown for the old one
+    pub fn switch_account(&mut self, choice: Option<usize>) {
+        match choice {
+            Some(index) => {
+                self.active_account_index = Some(index);
+                self.all_accounts = false;
+            }
+            None => self.all_accounts = true,
+        }
+
+        self.positions = None;
+        self.position_lots = None;
+        self.selected_position_index = 0;
+        self.dispatch(IoEvent::GetPortfolio);
+        self.dispatch(IoEvent::GetAccountBalance);
+        if self.orders.is_some() {
+            self.orders = None;
+            self.selected_order_index = 0;
+            self.dispatch(IoEvent::GetOrders);
+        }
+        // the filters carry over, the pages belong to the old account
+        self.transactions = TransactionsView {
+            start_date: self.transactions.start_date,
+            end_date: self.transactions.end_date,
+            kind: self.transactions.kind,
+            ..Default::default()
+        };
+        self.realized_gains = RealizedGainsView {
+            start_date: self.realized_gains.start_date,
+            end_date: self.realized_gains.end_date,
+            matching: self.realized_gains.matching,
+            ..Default::default()
+        };
+    }
+
+    // account_id_keys of the accounts the portfolio is made of
+    pub fn portfolio_account_keys(&self) -> Vec<String> {
+        let accounts = self.user_accounts.as_deref().unwrap_or_default();
+        if self.all_accounts {
+            return accounts
+                .iter()
+                .filter(|account| is_brokerage(account))
+                .map(|account| account.account_id_key.to_owned())
+                .collect();
+        }
+
+        self.active_account_index
+            .and_then(|index| accounts.get(index))
+            .map(|account| account.account_id_key.to_owned())
+            .into_iter()
+            .collect()
+    }
+
+    pub fn portfolio_label(&self) -> String {
+        if self.all_accounts {
+            return "All accounts".to_string();
+        }
+        match self.active_account() {
+            Some(account) if !account.account_desc.is_empty() => {
+                format!("{} {}", account.account_desc, account.account_id)
+            }
+            Some(account) => account.account_id.to_owned(),
+            None => String::new(),
+        }
+    }
+
+    pub fn set_positions(&mut self, positions: Vec<PortfolioPosition>) {
+        self.positions = Some(positions);
+        self.sort_positions();
+    }
+
+    fn sort_positions(&mut self) {
+        if let Some(positions) = self.positions.as_mut() {
+            self.positions_sort.sort(positions, self.positions_sort_descending);
+            self.selected_position_index = self.selected_position_index.min(positions.len().saturating_sub(1));
+        }
+    }
+
+    // Picking the column already sorted on flips the direction
+    pub fn sort_positions_by(&mut self, sort: PositionSort) {
+        if self.positions_sort == sort {
+            self.positions_sort_descending = !self.positions_sort_descending;
+        } else {
+            self.positions_sort = sort;
+            self.positions_sort_descending = sort != PositionSort::Symbol;
+        }
+        self.sort_positions();
+    }
+
+    pub fn next_positions_sort(&self) -> PositionSort {
+        cycle(&PositionSort::ALL, self.positions_sort, true)
+    }
+
+    pub fn selected_position(&self) -> Option<&PortfolioPosition> {
+        self.positions.as_ref()?.get(self.selected_position_index)
+    }
+
+    // Starts a SELL of the picked tax lots, the quantity defaults to everything in them. It goes to the
+    // account holding them, which isn't the active one when all accounts are shown
+    pub fn sell_position_lots(&mut self) -> Result<(), String> {
+        let (symbol, account_id_key, lots) = match &self.position_lots {
+            Some(position_lots) => (position_lots.symbol.to_owned(), position_lots.account_id_key.to_owned(), position_lots.lots_to_sell()),
+            None => return Err("No tax lots loaded".to_string()),
+        };
+        if lots.is_empty() {
+            return Err("No shares available to sell in the selected lots".to_string());
+        }
+        let account_id = match self.user_accounts.iter().flatten().find(|account| account.account_id_key == account_id_key) {
+            Some(account) => account.account_id.to_owned(),
+            None => return Err(format!("The account holding {} isn't loaded", symbol)),
+        };
+
+        self.new_preview_order_in(account_id, account_id_key, OrderType::EQ, OrderAction::SELL);
+        let quantity = match self.preview_order_form {
+            Some(ref mut order_form) => {
+                order_form.symbol = symbol.to_owned();
+                order_form.lots = lots;
+                order_form.lots_quantity().unwrap_or_default()
+            }
+            None => return Err("No active account".to_string()),
+        };
+
+        self.preview_order_ticker = Some(symbol);
+        self.prefill_input(&format!("{}", quantity.floor()));
+        self.order_form_state = OrderFormState::Quantity;
+
+        // Dbl push so Esc "back" works
+        self.push_navigation_stack(RouteId::OrderForm, ActiveBlock::OrderForm);
+        self.push_navigation_stack(RouteId::OrderForm, ActiveBlock::Input);
+        Ok(())
+    }
+
+    // None means every status
+    pub fn cycle_orders_status_filter(&mut self) {
+        self.orders_status_filter = match self.orders_status_filter {
+            None => Some(StatusType::FILTERS[0]),
+            Some(status) => {
+                let index = StatusType::FILTERS.iter().position(|s| *s == status).unwrap_or(0);
+                StatusType::FILTERS.get(index + 1).copied()
+            }
+        };
+        self.selected_order_index = 0;
+    }
+
     // Send a network event to the network thread
     pub fn dispatch(&mut self, action: IoEvent) {
         // `is_loading` will be set to false again after the async action has finished in network.rs
@@ -538,6 +2182,22 @@ impl App {
         }
     }
 
+    // Takes over from whatever failed for want of a token, the error it left says nothing more
+    pub fn open_authorization(&mut self, authorize_url: String) {
+        while self.get_current_route().id == RouteId::Error && self.pop_navigation_stack().is_some() {}
+        if self.get_current_route().id != RouteId::Authorization {
+            self.push_navigation_stack(RouteId::Authorization, ActiveBlock::Authorization);
+        }
+        self.authorization = Some(AuthorizationView { authorize_url, message: None });
+    }
+
+    pub fn close_authorization(&mut self) {
+        self.authorization = None;
+        if self.get_current_route().id == RouteId::Authorization {
+            self.pop_navigation_stack();
+        }
+    }
+
     pub fn handle_error(&mut self, e: anyhow::Error) {
         self.push_navigation_stack(RouteId::Error, ActiveBlock::Error);
         self.api_error = e.to_string();
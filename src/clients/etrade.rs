@@ -1,15 +1,20 @@
 use super::etrade_xml_structs;
 use super::etrade_json_structs;
 use derive_builder::Builder;
-use crate::app::{Ticker};
 use crate::config::ClientConfig;
 use crate::stonks_error::RuntimeError;
 use crate::session::{Credentials, Session};
 use crate::store::Store;
+use chrono::NaiveDate;
+use hyper::{body::Bytes, Body, Method, Response, StatusCode};
+use log::debug;
 use serde_json::json;
 
 pub type ClientResult<T> = Result<T, RuntimeError>;
 
+const MAX_QUOTE_SYMBOLS: usize = 25;
+pub const TRANSACTIONS_PER_PAGE: u32 = 50;
+
 // pub struct EtradeTokenInfo {
 //     pub expires_at: Option<DateTime<Utc>>
 // }
@@ -20,191 +25,418 @@ struct AC {
     realTimeNAV: String,
 }
 
+#[derive(oauth::Request)]
+struct OrdersQuery {
+    count: u32,
+    status: Option<String>,
+    symbol: Option<String>,
+}
+
+#[derive(oauth::Request)]
+struct TransactionsQuery {
+    #[oauth1(rename = "startDate")]
+    start_date: Option<String>,
+    #[oauth1(rename = "endDate")]
+    end_date: Option<String>,
+    #[oauth1(rename = "sortOrder")]
+    sort_order: String,
+    marker: Option<String>,
+    count: u32,
+}
+
+#[derive(oauth::Request)]
+struct OptionChainsQuery {
+    symbol: String,
+    #[oauth1(rename = "expiryYear")]
+    expiry_year: i32,
+    #[oauth1(rename = "expiryMonth")]
+    expiry_month: u32,
+    #[oauth1(rename = "expiryDay")]
+    expiry_day: u32,
+    #[oauth1(rename = "includeWeekly")]
+    include_weekly: bool,
+    #[oauth1(rename = "noOfStrikes")]
+    no_of_strikes: u32,
+    #[oauth1(rename = "chainType")]
+    chain_type: String,
+}
+
+#[derive(oauth::Request)]
+struct OptionExpireDateQuery {
+    symbol: String,
+    #[oauth1(rename = "expiryType")]
+    expiry_type: String,
+}
+
+#[derive(oauth::Request)]
+struct QuoteQuery {
+    #[oauth1(rename = "detailFlag")]
+    detail_flag: String,
+}
+
 #[derive(Builder, Clone)]
 pub struct Etrade {
     client_creds: Credentials,
+    // to renew the access token when a request comes back unauthorized
+    client_config: ClientConfig,
 }
 
 impl Etrade {
     pub fn new(client_config: ClientConfig) -> Self {
-        let client_creds = Credentials::new(client_config.consumer_key.to_string(), client_config.consumer_secret.to_string());
+        let client_creds = client_config.consumer_creds();
         Self {
             client_creds,
+            client_config,
         }
     }
 }
 
 impl Etrade {
-    fn build_authorization_header<T: Store>(&self, uri: &str, session: &Session<T>) -> String {
-        let authorization_header = oauth::Builder::<_, _>::new(self.client_creds.clone().into(), oauth::HmacSha1)
-            .token(Some(self.get_access_creds(&session)))
-            .get(uri, &());
+    fn get_access_creds<T: Store>(&self, session: &Session<T>) -> oauth::Credentials {
+        let access_creds: Option<&Credentials> = session.store.tokens().map(|tokens| &tokens.access_creds);
+        access_creds.unwrap().clone().into()
+    }
 
-        authorization_header
+    // Signs and sends one request. `uri` goes out without a query, `query` is appended and signed with it
+    async fn send<T: Store, R: oauth::Request>(&self, session: &Session<T>, method: &Method, uri: &str, query: &R, body: Option<&str>) -> Result<Response<Body>, hyper::Error> {
+        let authorization_header = oauth::Builder::<_, _>::new(self.client_creds.clone().into(), oauth::HmacSha1)
+            .token(Some(self.get_access_creds(session)))
+            .build(method.as_str(), uri, query);
+        let uri = oauth::to_uri_query(uri.to_string(), query);
+
+        match *method {
+            Method::POST => session.send_post_request(&uri, authorization_header, body.unwrap_or_default().to_string()).await,
+            Method::PUT => session.send_put_request(&uri, authorization_header, body.unwrap_or_default().to_string()).await,
+            _ => session.send_request(&uri, authorization_header).await,
+        }
     }
 
-    fn get_access_creds<T: Store>(&self, session: &Session<T>) -> oauth::Credentials {
-        let access_creds: Option<&Credentials> = session.store.get(self.client_creds.key.to_string());
-        access_creds.unwrap().clone().into()
+    // Every request goes through here. E*TRADE answers 401 once the access token has been idle for two hours
+    // or expired at midnight, so the token is renewed, or the whole OAuth flow run again, and the request
+    // sent one more time with the new token
+    async fn execute<T: Store, R: oauth::Request>(&self, session: &mut Session<T>, method: Method, uri: &str, query: &R, body: Option<&str>) -> ClientResult<(StatusCode, Bytes)> {
+        let mut resp = self.send(session, &method, uri, query, body).await?;
+        if resp.status() == StatusCode::UNAUTHORIZED {
+            debug!("{} {} was unauthorized, renewing the access token", method, uri);
+            session.reauthenticate(self.client_config.clone()).await?;
+            resp = self.send(session, &method, uri, query, body).await?;
+        }
+        if resp.status() != StatusCode::UNAUTHORIZED {
+            session.record_activity()?;
+        }
+
+        let status = resp.status();
+        let bytes = hyper::body::to_bytes(resp.into_body()).await?;
+        Ok((status, bytes))
     }
 
-    pub async fn ticker<T: Store>(&self, session: &Session<T>, symbol: &str) -> ClientResult<etrade_xml_structs::TickerXML> {
+    pub async fn ticker<T: Store>(&self, session: &mut Session<T>, symbol: &str, detail_flag: etrade_json_structs::DetailFlag) -> ClientResult<etrade_xml_structs::TickerXML> {
+        let req = QuoteQuery {
+            detail_flag: detail_flag.to_string(),
+        };
         let uri = session.urls.etrade_ticker_url(symbol, &session.mode);
-        let authorization_header = self.build_authorization_header(&uri, &session);
 
-        let resp = session.send_request(&uri, authorization_header).await.unwrap();
-        if resp.status().as_u16() / 100 == 2 {
-            let bd = resp.into_body();
-            let bytes = hyper::body::to_bytes(bd).await?;
+        let (status, bytes) = self.execute(session, Method::GET, &uri, &req, None).await?;
+        if status.as_u16() / 100 == 2 {
             let ticker: etrade_xml_structs::TickerXML = serde_xml_rs::from_reader(&bytes[..])?;
             Ok(ticker)
-        } else if resp.status().as_u16() == 401 {
-            // retry auth and rety
-            // if let Some(cached_creds) = session.get_creds_from_cache() {
-            //     session.renew_access_token(self.client_creds.clone().into(), cached_creds).await?;
-            // }
-            return Err(RuntimeError { message: "request failed".to_string() });
         } else {
-            return Err(RuntimeError { message: "request failed".to_string() });
+            Err(RuntimeError { message: "request failed".to_string() })
+        }
+    }
+
+    // Quotes for many symbols at once, in batches of the most E*TRADE takes per request
+    pub async fn quotes<T: Store>(&self, session: &mut Session<T>, symbols: &[String]) -> ClientResult<Vec<etrade_xml_structs::QuoteData>> {
+        let mut quotes = vec![];
+        for batch in symbols.chunks(MAX_QUOTE_SYMBOLS) {
+            let uri = session.urls.etrade_quotes_url(batch, &session.mode);
+
+            let (status, bytes) = self.execute(session, Method::GET, &uri, &(), None).await?;
+            if status.as_u16() / 100 == 2 {
+                let results: etrade_xml_structs::QuotesXML = serde_xml_rs::from_reader(&bytes[..])?;
+                quotes.extend(results.quote_data);
+            } else {
+                return Err(RuntimeError { message: "quote request failed".to_string() });
+            }
         }
+
+        Ok(quotes)
     }
 
-    pub async fn portfolio<T: Store>(&self, account_id_key: &str, session: &Session<T>) -> ClientResult<etrade_xml_structs::PortfolioXML> {
+    // An account without holdings comes back as 204 No Content
+    pub async fn portfolio<T: Store>(&self, account_id_key: &str, session: &mut Session<T>) -> ClientResult<Vec<etrade_xml_structs::Position>> {
         let uri = session.urls.etrade_portfolio_url(account_id_key, &session.mode);
-        let authorization_header = self.build_authorization_header(&uri, &session);
 
-        let resp = session.send_request(&uri, authorization_header).await.unwrap();
-        if resp.status().as_u16() / 100 == 2 {
-            let bd = resp.into_body();
-            let bytes = hyper::body::to_bytes(bd).await?;
+        let (status, bytes) = self.execute(session, Method::GET, &uri, &(), None).await?;
+        if status.as_u16() == 204 {
+            Ok(vec![])
+        } else if status.as_u16() / 100 == 2 {
             let results: etrade_xml_structs::PortfolioXML = serde_xml_rs::from_reader(&bytes[..])?;
-            Ok(results)
-        } else if resp.status().as_u16() == 401 {
-            return Err(RuntimeError { message: "request failed".to_string() });
+            Ok(results.account_portfolio.positions)
+        } else {
+            Err(RuntimeError { message: "request failed".to_string() })
+        }
+    }
+
+    // lots_url is the lotsDetails link sent with each position
+    pub async fn position_lots<T: Store>(&self, lots_url: &str, session: &mut Session<T>) -> ClientResult<Vec<etrade_xml_structs::PositionLot>> {
+        let (status, bytes) = self.execute(session, Method::GET, lots_url, &(), None).await?;
+        if status.as_u16() == 204 {
+            Ok(vec![])
+        } else if status.as_u16() / 100 == 2 {
+            let results: etrade_xml_structs::PositionLotsXML = serde_xml_rs::from_reader(&bytes[..])?;
+            Ok(results.position_lots)
         } else {
-            return Err(RuntimeError { message: "request failed".to_string() });
+            Err(RuntimeError { message: "Request for tax lots failed".to_string() })
         }
     }
 
-    pub async fn search<T: Store>(&self, session: &Session<T>, search_term: &String) -> ClientResult<Vec<etrade_xml_structs::TickerSearchData>> {
+    pub async fn search<T: Store>(&self, session: &mut Session<T>, search_term: &String) -> ClientResult<Vec<etrade_xml_structs::TickerSearchData>> {
         let uri = session.urls.etrade_search_url(search_term, &session.mode);
-        let authorization_header = self.build_authorization_header(&uri, &session);
 
-        let resp = session.send_request(&uri, authorization_header).await.unwrap();
-        if resp.status().as_u16() / 100 == 2 {
-            let bd = resp.into_body();
-            let bytes = hyper::body::to_bytes(bd).await?;
+        let (status, bytes) = self.execute(session, Method::GET, &uri, &(), None).await?;
+        if status.as_u16() / 100 == 2 {
             let results: etrade_xml_structs::SearchXML = serde_xml_rs::from_reader(&bytes[..])?;
             Ok(results.items)
-        } else if resp.status().as_u16() == 401 {
-            return Err(RuntimeError { message: "request failed".to_string() });
         } else {
-            return Err(RuntimeError { message: "request failed".to_string() });
+            Err(RuntimeError { message: "request failed".to_string() })
         }
     }
 
-    pub async fn accounts_list<T: Store>(&mut self, session: &Session<T>) -> Result<etrade_xml_structs::AccountsListXML, RuntimeError> {
+    pub async fn accounts_list<T: Store>(&self, session: &mut Session<T>) -> Result<etrade_xml_structs::AccountsListXML, RuntimeError> {
         let uri = session.urls.accounts_list(&session.mode);
-        let authorization_header = self.build_authorization_header(&uri, &session);
 
-        let resp = session.send_request(&uri, authorization_header).await?;
-        let bd = resp.into_body();
-        let bytes = hyper::body::to_bytes(bd).await?;
+        let (_, bytes) = self.execute(session, Method::GET, &uri, &(), None).await?;
         // std::fs::write("res-account.txt", &std::str::from_utf8(&bytes).unwrap());
         let results: etrade_xml_structs::AccountsListXML = serde_xml_rs::from_reader(&bytes[..])?;
 
         Ok(results)
     }
 
-    pub async fn account_balance<T: Store>(&mut self, account_id_key: &str, session: &Session<T>) -> Result<etrade_xml_structs::AccountBalance, RuntimeError> {
+    pub async fn account_balance<T: Store>(&self, account_id_key: &str, session: &mut Session<T>) -> Result<etrade_xml_structs::AccountBalance, RuntimeError> {
         let req = AC {
             instType: "BROKERAGE".to_string(),
             realTimeNAV: "true".to_string()
         };
-        let uri = session.urls.account_balance(account_id_key, &session.mode);
-        let authorization_header = oauth::Builder::<_, _>::new(self.client_creds.clone().into(), oauth::HmacSha1)
-            .token(Some(self.get_access_creds(&session)))
-            .get(&session.urls.account_balance_base(&account_id_key, &session.mode), &req);
+        let uri = session.urls.account_balance_base(account_id_key, &session.mode);
 
-        let resp = session.send_request(&uri, authorization_header).await?;
-        let bd = resp.into_body();
-        let bytes = hyper::body::to_bytes(bd).await?;
+        let (_, bytes) = self.execute(session, Method::GET, &uri, &req, None).await?;
         let results: etrade_xml_structs::AccountBalance = serde_xml_rs::from_reader(&bytes[..])?;
 
         Ok(results)
     }
 
-    pub async fn preview_order_request<T: Store>(&self, account_id_key: &str, session: &Session<T>, preview_order_request: etrade_json_structs::PreviewOrderRequest) -> ClientResult<etrade_json_structs::PreviewOrderResponse> {
+    pub async fn preview_order_request<T: Store>(&self, account_id_key: &str, session: &mut Session<T>, preview_order_request: etrade_json_structs::PreviewOrderRequest) -> ClientResult<etrade_json_structs::PreviewOrderResponse> {
         let uri = session.urls.etrade_order_preview_url(account_id_key, &session.mode);
         // OAuth specification explicitly states that only form-encoded data should be included,
         // not JSON body
-        let authorization_header = oauth::Builder::<_, _>::new(self.client_creds.clone().into(), oauth::HmacSha1)
-            .token(Some(self.get_access_creds(&session)))
-            .post(&uri, &());
+        let body = json!(preview_order_request).to_string();
 
-        let body = json!(preview_order_request);
-        let resp = session.send_post_request(&uri, authorization_header, body.to_string()).await?;
-        let status = resp.status();
-        let bd = resp.into_body();
-        let bytes = hyper::body::to_bytes(bd).await?;
+        let (status, bytes) = self.execute(session, Method::POST, &uri, &(), Some(&body)).await?;
         // std::fs::write("res.txt", &std::str::from_utf8(&bytes).unwrap());
         if status.as_u16() / 100 == 2 {
-            // let bd = resp.into_body();
-            // let bytes = hyper::body::to_bytes(bd).await?;
-            let results: etrade_json_structs::PreviewOrderResponse = serde_json::from_reader(&bytes[..])?;
-            Ok(results)
+            let results: etrade_json_structs::PreviewOrderResponseJSON = serde_json::from_reader(&bytes[..])?;
+            Ok(results.preview_order_response)
         } else {
-            return Err(RuntimeError { message: "Request for Preview Order failed".to_string() });
+            Err(order_error(&bytes, "Request for Preview Order failed"))
         }
     }
 
-    pub async fn place_order_request<T: Store>(&self, account_id_key: &str, session: &Session<T>) -> ClientResult<etrade_json_structs::PlaceOrderResponse> {
-        let uri = session.urls.etrade_order_preview_url(account_id_key, &session.mode);
+    pub async fn place_order_request<T: Store>(&self, account_id_key: &str, session: &mut Session<T>, place_order_request: etrade_json_structs::PlaceOrderRequest) -> ClientResult<etrade_json_structs::PlaceOrderResponse> {
+        let uri = session.urls.etrade_order_place_url(account_id_key, &session.mode);
+        let body = json!(place_order_request).to_string();
+
+        let (status, bytes) = self.execute(session, Method::POST, &uri, &(), Some(&body)).await?;
+        if status.as_u16() / 100 == 2 {
+            let results: etrade_json_structs::PlaceOrderResponseJSON = serde_json::from_reader(&bytes[..])?;
+            Ok(results.place_order_response)
+        } else {
+            Err(order_error(&bytes, "Request for Place Order failed"))
+        }
+    }
 
-        let authorization_header = self.build_authorization_header(&uri, &session);
-        let resp = session.send_request(&uri, authorization_header).await?;
-        let bd = resp.into_body();
-        let bytes = hyper::body::to_bytes(bd).await?;
-        let results: etrade_json_structs::PlaceOrderResponse = serde_xml_rs::from_reader(&bytes[..])?;
+    // https://apisb.etrade.com/docs/api/order/api-order-v1.html#/definitions/changePreviewOrder
+    pub async fn change_preview_order_request<T: Store>(&self, account_id_key: &str, order_id: i64, session: &mut Session<T>, preview_order_request: etrade_json_structs::PreviewOrderRequest) -> ClientResult<etrade_json_structs::PreviewOrderResponse> {
+        let uri = session.urls.etrade_change_order_preview_url(account_id_key, order_id, &session.mode);
+        let body = json!(preview_order_request).to_string();
 
-        Ok(results)
+        let (status, bytes) = self.execute(session, Method::PUT, &uri, &(), Some(&body)).await?;
+        if status.as_u16() / 100 == 2 {
+            let results: etrade_json_structs::PreviewOrderResponseJSON = serde_json::from_reader(&bytes[..])?;
+            Ok(results.preview_order_response)
+        } else {
+            Err(order_error(&bytes, "Request for Change Order Preview failed"))
+        }
     }
 
-    pub async fn alerts<T: Store>(&mut self, session: &Session<T>) -> Result<etrade_xml_structs::AlertsXML, RuntimeError> {
+    // https://apisb.etrade.com/docs/api/order/api-order-v1.html#/definitions/placeChangedOrder
+    pub async fn change_place_order_request<T: Store>(&self, account_id_key: &str, order_id: i64, session: &mut Session<T>, place_order_request: etrade_json_structs::PlaceOrderRequest) -> ClientResult<etrade_json_structs::PlaceOrderResponse> {
+        let uri = session.urls.etrade_change_order_place_url(account_id_key, order_id, &session.mode);
+        let body = json!(place_order_request).to_string();
+
+        let (status, bytes) = self.execute(session, Method::PUT, &uri, &(), Some(&body)).await?;
+        if status.as_u16() / 100 == 2 {
+            let results: etrade_json_structs::PlaceOrderResponseJSON = serde_json::from_reader(&bytes[..])?;
+            Ok(results.place_order_response)
+        } else {
+            Err(order_error(&bytes, "Request for Change Order failed"))
+        }
+    }
+
+    // https://apisb.etrade.com/docs/api/market/api-market-v1.html#/definitions/getOptionExpireDates
+    pub async fn option_expire_dates<T: Store>(&self, session: &mut Session<T>, symbol: &str) -> ClientResult<Vec<etrade_json_structs::ExpirationDate>> {
+        let req = OptionExpireDateQuery {
+            symbol: symbol.to_string(),
+            expiry_type: "ALL".to_string(),
+        };
+        let uri = session.urls.etrade_option_expire_date_url(&session.mode);
+
+        let (status, bytes) = self.execute(session, Method::GET, &uri, &req, None).await?;
+        if status.as_u16() / 100 == 2 {
+            let results: etrade_json_structs::OptionExpireDateResponseJSON = serde_json::from_reader(&bytes[..])?;
+            Ok(results.option_expire_date_response.expiration_dates)
+        } else {
+            Err(RuntimeError { message: "Request for Option Expire Dates failed".to_string() })
+        }
+    }
+
+    // https://apisb.etrade.com/docs/api/market/api-market-v1.html#/definitions/getOptionChains
+    pub async fn option_chains<T: Store>(&self, session: &mut Session<T>, symbol: &str, expiration_date: &etrade_json_structs::ExpirationDate, strikes: u32) -> ClientResult<etrade_json_structs::OptionChainResponse> {
+        let req = OptionChainsQuery {
+            symbol: symbol.to_string(),
+            expiry_year: expiration_date.year,
+            expiry_month: expiration_date.month,
+            expiry_day: expiration_date.day,
+            include_weekly: true,
+            no_of_strikes: strikes,
+            chain_type: "CALLPUT".to_string(),
+        };
+        let uri = session.urls.etrade_option_chains_url(&session.mode);
+
+        let (status, bytes) = self.execute(session, Method::GET, &uri, &req, None).await?;
+        if status.as_u16() / 100 == 2 {
+            let results: etrade_json_structs::OptionChainResponseJSON = serde_json::from_reader(&bytes[..])?;
+            Ok(results.option_chain_response)
+        } else {
+            Err(RuntimeError { message: "Request for Option Chains failed".to_string() })
+        }
+    }
+
+    pub async fn orders<T: Store>(&self, account_id_key: &str, session: &mut Session<T>, status: Option<etrade_json_structs::StatusType>, symbol: Option<String>) -> ClientResult<etrade_json_structs::OrdersResponse> {
+        let req = OrdersQuery {
+            count: 100,
+            status: status.map(|s| s.to_string()),
+            symbol,
+        };
+        let uri = session.urls.etrade_orders_url(account_id_key, &session.mode);
+
+        let (status, bytes) = self.execute(session, Method::GET, &uri, &req, None).await?;
+        if status.as_u16() == 204 {
+            // no orders match the filters
+            Ok(etrade_json_structs::OrdersResponse { marker: None, next: None, orders: vec![] })
+        } else if status.as_u16() / 100 == 2 {
+            let results: etrade_json_structs::OrdersResponseJSON = serde_json::from_reader(&bytes[..])?;
+            Ok(results.orders_response)
+        } else {
+            Err(order_error(&bytes, "Request for Orders failed"))
+        }
+    }
+
+    pub async fn cancel_order<T: Store>(&self, account_id_key: &str, session: &mut Session<T>, order_id: i64) -> ClientResult<etrade_json_structs::CancelOrderResponse> {
+        let uri = session.urls.etrade_cancel_order_url(account_id_key, &session.mode);
+        let body = json!(etrade_json_structs::CancelOrderRequest {
+            cancel_order_request: etrade_json_structs::EtradeCancelOrderRequest { order_id },
+        }).to_string();
+
+        let (status, bytes) = self.execute(session, Method::PUT, &uri, &(), Some(&body)).await?;
+        if status.as_u16() / 100 == 2 {
+            let results: etrade_json_structs::CancelOrderResponseJSON = serde_json::from_reader(&bytes[..])?;
+            Ok(results.cancel_order_response)
+        } else {
+            Err(order_error(&bytes, "Request to Cancel Order failed"))
+        }
+    }
+
+    // Newest first, the way the transactions screen shows them
+    pub async fn transactions<T: Store>(&self, account_id_key: &str, session: &mut Session<T>, start_date: Option<NaiveDate>, end_date: Option<NaiveDate>, marker: Option<String>) -> ClientResult<etrade_json_structs::TransactionListResponse> {
+        self.transactions_page(account_id_key, session, start_date, end_date, marker, "DESC").await
+    }
+
+    // A page of TRANSACTIONS_PER_PAGE. Dates go out as MMDDYYYY, E*TRADE picks the range when they are None
+    async fn transactions_page<T: Store>(&self, account_id_key: &str, session: &mut Session<T>, start_date: Option<NaiveDate>, end_date: Option<NaiveDate>, marker: Option<String>, sort_order: &str) -> ClientResult<etrade_json_structs::TransactionListResponse> {
+        let req = TransactionsQuery {
+            start_date: start_date.map(|date| date.format("%m%d%Y").to_string()),
+            end_date: end_date.map(|date| date.format("%m%d%Y").to_string()),
+            sort_order: sort_order.to_string(),
+            marker,
+            count: TRANSACTIONS_PER_PAGE,
+        };
+        let uri = session.urls.etrade_transactions_url(account_id_key, &session.mode);
+
+        let (status, bytes) = self.execute(session, Method::GET, &uri, &req, None).await?;
+        if status.as_u16() == 204 {
+            // nothing in the date range
+            Ok(etrade_json_structs::TransactionListResponse::default())
+        } else if status.as_u16() / 100 == 2 {
+            let results: etrade_json_structs::TransactionListResponseJSON = serde_json::from_reader(&bytes[..])?;
+            Ok(results.transaction_list_response)
+        } else {
+            Err(order_error(&bytes, "Request for Transactions failed"))
+        }
+    }
+
+    // Every page in the range, following the markers. Oldest first, so trades on the same day come in
+    // the order they happened
+    pub async fn all_transactions<T: Store>(&self, account_id_key: &str, session: &mut Session<T>, start_date: Option<NaiveDate>, end_date: Option<NaiveDate>) -> ClientResult<Vec<etrade_json_structs::Transaction>> {
+        let mut transactions = vec![];
+        let mut marker = None;
+        loop {
+            let page = self.transactions_page(account_id_key, session, start_date, end_date, marker, "ASC").await?;
+            transactions.extend(page.transactions);
+            match page.marker {
+                Some(next_marker) if page.more_transactions => marker = Some(next_marker),
+                _ => return Ok(transactions),
+            }
+        }
+    }
+
+    pub async fn transaction_details<T: Store>(&self, account_id_key: &str, session: &mut Session<T>, transaction_id: i64) -> ClientResult<etrade_json_structs::TransactionDetails> {
+        let uri = session.urls.etrade_transaction_details_url(account_id_key, transaction_id, &session.mode);
+
+        let (status, bytes) = self.execute(session, Method::GET, &uri, &(), None).await?;
+        if status.as_u16() / 100 == 2 {
+            let results: etrade_json_structs::TransactionDetailsResponseJSON = serde_json::from_reader(&bytes[..])?;
+            Ok(results.transaction_details_response)
+        } else {
+            Err(order_error(&bytes, "Request for Transaction Details failed"))
+        }
+    }
+
+    pub async fn alerts<T: Store>(&self, session: &mut Session<T>) -> Result<etrade_xml_structs::AlertsXML, RuntimeError> {
         let uri = session.urls.alerts(&session.mode);
-        let authorization_header = self.build_authorization_header(&uri, &session);
 
-        let resp = session.send_request(&uri, authorization_header).await?;
-        let bd = resp.into_body();
-        let bytes = hyper::body::to_bytes(bd).await?;
+        let (_, bytes) = self.execute(session, Method::GET, &uri, &(), None).await?;
         // std::fs::write("res-account.txt", &std::str::from_utf8(&bytes).unwrap());
         let results: etrade_xml_structs::AlertsXML = serde_xml_rs::from_reader(&bytes[..])?;
 
         Ok(results)
     }
 
-    pub async fn alert<T: Store>(&mut self, notification_id: &str, session: &Session<T>) -> Result<etrade_xml_structs::AlertDetails, RuntimeError> {
+    pub async fn alert<T: Store>(&self, notification_id: &str, session: &mut Session<T>) -> Result<etrade_xml_structs::AlertDetails, RuntimeError> {
         let uri = session.urls.alert(notification_id, &session.mode);
-        let authorization_header = self.build_authorization_header(&uri, &session);
 
-        let resp = session.send_request(&uri, authorization_header).await?;
-        let bd = resp.into_body();
-        let bytes = hyper::body::to_bytes(bd).await?;
+        let (_, bytes) = self.execute(session, Method::GET, &uri, &(), None).await?;
         let results: etrade_xml_structs::AlertDetails = serde_xml_rs::from_reader(&bytes[..])?;
 
         Ok(results)
     }
+}
 
-    pub async fn current_user_saved_tickers(&self, search_limit: u32, offset: Option<u32>) -> ClientResult<Vec<Ticker>> {
-        todo!();
-    }
-
-    pub async fn current_user_saved_tickers_add(&self, ticker_symbols: &[String]) -> ClientResult<()> {
-        todo!();
-    }
-
-    pub async fn current_user_saved_tickers_delete(&self, ticker_symbols: &[String]) -> ClientResult<()> {
-        todo!();
+// order endpoints explain rejections (insufficient funds, bad symbol, etc.) in the error body
+fn order_error(bytes: &[u8], default_message: &str) -> RuntimeError {
+    match serde_json::from_slice::<etrade_json_structs::ErrorResponse>(bytes) {
+        Ok(error_response) => RuntimeError { message: error_response.error.message },
+        Err(_) => RuntimeError { message: default_message.to_string() },
     }
 }
 
@@ -222,3 +454,251 @@ impl Etrade {
 // pub fn get_token() {
 
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{Mode, TestClock, TokenLifecycle, Verifier};
+    use crate::store::{EncryptedStore, LocalCredsData};
+    use crate::test_helpers;
+    use chrono::{Duration, TimeZone, Utc};
+    use secstr::SecUtf8;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{header::AUTHORIZATION, Request, Server};
+    use std::convert::Infallible;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    const ACCOUNTS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<AccountListResponse>
+  <Accounts>
+    <Account>
+      <accountId>83405188</accountId>
+      <accountIdKey>dBZOKt9xDrtRSAOl4MSiiA</accountIdKey>
+      <accountMode>MARGIN</accountMode>
+      <accountDesc>INDIVIDUAL</accountDesc>
+      <accountName>Brokerage</accountName>
+      <accountType>INDIVIDUAL</accountType>
+      <institutionType>BROKERAGE</institutionType>
+      <accountStatus>ACTIVE</accountStatus>
+    </Account>
+  </Accounts>
+</AccountListResponse>"#;
+
+    // Answers 401 to any token but an active one. Renewal activates the token it's signed with when `renews`
+    // is set, and the full flow hands out a new one when `authorizes` is. Returns its base url and the paths
+    // requested
+    async fn mock_etrade(renews: bool, authorizes: bool) -> (String, Arc<Mutex<Vec<String>>>) {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let seen = Arc::clone(&requests);
+        let active = Arc::new(Mutex::new(vec!["access-token".to_string()]));
+        let make_service = make_service_fn(move |_| {
+            let seen = Arc::clone(&seen);
+            let active = Arc::clone(&active);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                    let path = req.uri().path().to_string();
+                    seen.lock().unwrap().push(path.to_owned());
+                    let token = req.headers()
+                        .get(AUTHORIZATION)
+                        .and_then(|header| header.to_str().ok())
+                        .and_then(|header| header.split(r#"oauth_token=""#).nth(1))
+                        .and_then(|rest| rest.split('"').next())
+                        .map(|token| token.to_string());
+                    let mut active = active.lock().unwrap();
+                    let authorized = token.as_ref().is_some_and(|token| active.contains(token));
+                    let (status, body) = match (path.as_str(), token) {
+                        ("/oauth/renew_token", Some(token)) if renews => {
+                            active.push(token);
+                            (200, "Access Token has been renewed")
+                        }
+                        ("/oauth/request_token", _) if authorizes => (200, "oauth_token=request-token-2&oauth_token_secret=request-secret-2"),
+                        ("/oauth/access_token", _) if authorizes => {
+                            active.push("new-token".to_string());
+                            (200, "oauth_token=new-token&oauth_token_secret=new-secret")
+                        }
+                        ("/v1/accounts/list", _) if authorized => (200, ACCOUNTS),
+                        _ => (401, ""),
+                    };
+                    async move { Response::builder().status(status).body(Body::from(body)) }
+                }))
+            }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let base_url = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+
+        (base_url, requests)
+    }
+
+    fn leak(url: String) -> &'static str {
+        Box::leak(url.into_boxed_str())
+    }
+
+    // A session whose cached access token went idle, E*TRADE doesn't accept it until it's renewed
+    fn idle_session(base_url: &str, name: &str) -> (Session<EncryptedStore>, ClientConfig, PathBuf) {
+        let dir = test_helpers::temp_dir(name);
+        let config_paths = test_helpers::config_paths(&dir);
+        let cached = LocalCredsData {
+            access_creds: Credentials::new(SecUtf8::from("idle-token"), SecUtf8::from("idle-secret")),
+            request_token_creds: Credentials::new(SecUtf8::from("request-token"), SecUtf8::from("request-secret")),
+            verification_code: SecUtf8::from("12345"),
+            expires_at: Utc::now(),
+            last_request_timestamp: Utc::now(),
+        };
+        // plaintext, as left by versions before encryption
+        std::fs::write(&config_paths.token_cache_path, serde_json::to_string(&cached).unwrap()).unwrap();
+
+        let client_config = ClientConfig {
+            consumer_key: "consumer-key".to_string(),
+            consumer_secret: SecUtf8::from("consumer-secret"),
+        };
+        let mut session = Session::new(Mode::Live, test_helpers::store(&dir), config_paths);
+        session.urls.accounts_list_url = leak(format!("{}/v1/accounts/list", base_url));
+        session.urls.renew_token_url = leak(format!("{}/oauth/renew_token", base_url));
+        session.urls.request_token_url = leak(format!("{}/oauth/request_token", base_url));
+        session.urls.access_token_url = leak(format!("{}/oauth/access_token", base_url));
+
+        (session, client_config, dir)
+    }
+
+    #[tokio::test]
+    async fn renews_the_token_and_retries_on_401() {
+        let (base_url, requests) = mock_etrade(true, false).await;
+        let (mut session, client_config, dir) = idle_session(&base_url, "renews");
+        let etrade = Etrade::new(client_config);
+
+        let accounts = etrade.accounts_list(&mut session).await.unwrap();
+        assert_eq!(accounts.accounts.accounts[0].account_id, "83405188");
+        assert_eq!(*requests.lock().unwrap(), vec!["/v1/accounts/list", "/oauth/renew_token", "/v1/accounts/list"]);
+
+        // the same token works again from here on, and the next run knows it was renewed. The retry right after is
+        // held back, it's less than a minute later
+        let cached = session.store.tokens().unwrap();
+        assert_eq!(cached.access_creds.key.unsecure(), "idle-token");
+        let next_run = test_helpers::store(&dir);
+        let renewed = next_run.tokens().unwrap();
+        assert_eq!(renewed.access_creds, cached.access_creds);
+        assert!(cached.last_request_timestamp - renewed.last_request_timestamp < Duration::minutes(1));
+        etrade.accounts_list(&mut session).await.unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 4);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn gives_up_when_the_token_cannot_be_renewed() {
+        let (base_url, requests) = mock_etrade(false, false).await;
+        let (mut session, client_config, dir) = idle_session(&base_url, "gives-up");
+        let etrade = Etrade::new(client_config);
+
+        let error = etrade.accounts_list(&mut session).await.unwrap_err();
+        assert_eq!(error.message, "request_token failed");
+        // renewal falls back to the full flow, which fails at its first step, and nothing is retried
+        assert_eq!(*requests.lock().unwrap(), vec!["/v1/accounts/list", "/oauth/renew_token", "/oauth/request_token"]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn leaves_the_verification_code_to_the_dialog() {
+        let (base_url, requests) = mock_etrade(false, true).await;
+        let (mut session, client_config, dir) = idle_session(&base_url, "dialog");
+        session.verifier = Verifier::Dialog;
+        let etrade = Etrade::new(client_config.clone());
+
+        let error = etrade.accounts_list(&mut session).await.unwrap_err();
+        assert_eq!(error.message, "E*TRADE needs you to authorize stonks-terminal again");
+        let pending = session.pending_authorization.clone().unwrap();
+        assert_eq!(pending.authorize_url, "https://us.etrade.com/e/t/etws/authorize?key=consumer-key&token=request-token-2");
+
+        // requests while the link is on screen don't ask for another one
+        etrade.accounts_list(&mut session).await.unwrap_err();
+        assert_eq!(requests.lock().unwrap().iter().filter(|path| *path == "/oauth/request_token").count(), 1);
+
+        session.finish_authorization(client_config, pending, SecUtf8::from("4XK2P")).await.unwrap();
+        assert!(session.pending_authorization.is_none());
+        etrade.accounts_list(&mut session).await.unwrap();
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    // The idle session, but with an active token, issued at 10am EDT by the test's clock
+    fn active_session(base_url: &str, name: &str) -> (Session<EncryptedStore>, ClientConfig, PathBuf, Arc<TestClock>) {
+        let (mut session, client_config, dir) = idle_session(base_url, name);
+        let clock = TestClock::at(Utc.ymd(2021, 7, 1).and_hms(14, 0, 0));
+        session.token_lifecycle = TokenLifecycle::new(clock.clone());
+        let cached = session.store.tokens().cloned().unwrap();
+        let active = Credentials::new(SecUtf8::from("access-token"), SecUtf8::from("access-secret"));
+        session.store.save_tokens(cached.request_token_creds, active, cached.verification_code, session.token_lifecycle.now()).unwrap();
+
+        (session, client_config, dir, clock)
+    }
+
+    #[tokio::test]
+    async fn renews_the_token_before_it_goes_idle() {
+        let (base_url, requests) = mock_etrade(true, false).await;
+        let (mut session, client_config, dir, clock) = active_session(&base_url, "idle");
+        let etrade = Etrade::new(client_config.clone());
+
+        clock.advance(Duration::hours(1));
+        etrade.accounts_list(&mut session).await.unwrap();
+        // an hour and a half since the last request
+        clock.advance(Duration::minutes(90));
+        session.maintain_token(client_config.clone()).await.unwrap();
+        assert_eq!(*requests.lock().unwrap(), vec!["/v1/accounts/list"]);
+
+        clock.advance(Duration::minutes(20));
+        session.maintain_token(client_config.clone()).await.unwrap();
+        assert_eq!(*requests.lock().unwrap(), vec!["/v1/accounts/list", "/oauth/renew_token"]);
+        assert_eq!(session.store.tokens().unwrap().last_request_timestamp, session.token_lifecycle.now());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn authorizes_again_at_midnight_eastern() {
+        let (base_url, requests) = mock_etrade(false, true).await;
+        let (mut session, client_config, dir, clock) = active_session(&base_url, "midnight");
+        session.verifier = Verifier::Dialog;
+
+        // in use until 11:59pm EDT
+        clock.advance(Duration::minutes(13 * 60 + 59));
+        session.record_activity().unwrap();
+        session.maintain_token(client_config.clone()).await.unwrap();
+        assert!(requests.lock().unwrap().is_empty());
+
+        clock.advance(Duration::minutes(1));
+        let error = session.maintain_token(client_config.clone()).await.unwrap_err();
+        assert_eq!(error.message, "E*TRADE needs you to authorize stonks-terminal again");
+        // and waits on the code from there
+        session.maintain_token(client_config.clone()).await.unwrap();
+        assert_eq!(*requests.lock().unwrap(), vec!["/oauth/request_token"]);
+
+        let pending = session.pending_authorization.clone().unwrap();
+        session.finish_authorization(client_config, pending, SecUtf8::from("4XK2P")).await.unwrap();
+        assert_eq!(session.store.tokens().unwrap().expires_at, Utc.ymd(2021, 7, 3).and_hms(4, 0, 0));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn waits_a_minute_after_a_failed_renewal() {
+        let (base_url, requests) = mock_etrade(false, false).await;
+        let (mut session, client_config, dir, clock) = active_session(&base_url, "retry");
+
+        clock.advance(Duration::hours(2));
+        session.maintain_token(client_config.clone()).await.unwrap_err();
+        let attempt = vec!["/oauth/renew_token", "/oauth/request_token"];
+        assert_eq!(*requests.lock().unwrap(), attempt);
+
+        clock.advance(Duration::seconds(59));
+        session.maintain_token(client_config.clone()).await.unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 2);
+        clock.advance(Duration::seconds(1));
+        session.maintain_token(client_config).await.unwrap_err();
+        assert_eq!(*requests.lock().unwrap(), [attempt.clone(), attempt].concat());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
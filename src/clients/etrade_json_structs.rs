@@ -1,4 +1,18 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+
+// E*TRADE sends numeric fields (quantity, prices) as JSON numbers in responses, but we send them
+// as strings in requests
+fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    match value {
+        serde_json::Value::String(s) => Ok(s),
+        serde_json::Value::Null => Ok("".to_string()),
+        v => Ok(v.to_string()),
+    }
+}
 
 // ORDER
 #[derive(strum_macros::ToString, strum_macros::EnumString, Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -15,7 +29,7 @@ pub enum OrderType {
     MMF
 }
 
-#[derive(strum_macros::ToString, Clone, Debug, strum_macros::EnumString, Deserialize, Serialize, PartialEq)]
+#[derive(strum_macros::ToString, Clone, Copy, Debug, strum_macros::EnumString, Deserialize, Serialize, PartialEq)]
 pub enum OrderAction {
     BUY,
     SELL,
@@ -28,33 +42,194 @@ pub enum OrderAction {
     EXCHANGE
 }
 
-#[derive(strum_macros::ToString, Clone, Debug, Deserialize, Serialize, PartialEq)]
+impl OrderAction {
+    // open/close actions an option order can take
+    pub const OPTIONS: [OrderAction; 4] = [
+        OrderAction::BUY_OPEN,
+        OrderAction::SELL_CLOSE,
+        OrderAction::SELL_OPEN,
+        OrderAction::BUY_CLOSE,
+    ];
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(strum_macros::ToString, strum_macros::EnumString, Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum CallPut {
+    CALL,
+    PUT,
+}
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(strum_macros::ToString, strum_macros::EnumString, Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum PriceType {
+    MARKET,
+    LIMIT,
+    STOP,
+    STOP_LIMIT,
+    TRAILING_STOP_CNST,
+    NET_DEBIT,
+    NET_CREDIT,
+    NET_EVEN,
+}
+
+impl PriceType {
+    pub const ALL: [PriceType; 5] = [
+        PriceType::MARKET,
+        PriceType::LIMIT,
+        PriceType::STOP,
+        PriceType::STOP_LIMIT,
+        PriceType::TRAILING_STOP_CNST,
+    ];
+
+    // multi-leg orders are priced on the net of all legs
+    pub const SPREADS: [PriceType; 4] = [
+        PriceType::NET_DEBIT,
+        PriceType::NET_CREDIT,
+        PriceType::NET_EVEN,
+        PriceType::MARKET,
+    ];
+
+    // net debit/credit orders send the net price as the limit price
+    pub fn needs_limit_price(&self) -> bool {
+        matches!(self, PriceType::LIMIT | PriceType::STOP_LIMIT | PriceType::NET_DEBIT | PriceType::NET_CREDIT)
+    }
+
+    // trailing stops send the trail amount as the stop price
+    pub fn needs_stop_price(&self) -> bool {
+        matches!(self, PriceType::STOP | PriceType::STOP_LIMIT | PriceType::TRAILING_STOP_CNST)
+    }
+}
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(strum_macros::ToString, strum_macros::EnumString, Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum OrderTerm {
+    GOOD_FOR_DAY,
+    GOOD_UNTIL_CANCEL,
+    IMMEDIATE_OR_CANCEL,
+    FILL_OR_KILL,
+}
+
+impl OrderTerm {
+    pub const ALL: [OrderTerm; 4] = [
+        OrderTerm::GOOD_FOR_DAY,
+        OrderTerm::GOOD_UNTIL_CANCEL,
+        OrderTerm::IMMEDIATE_OR_CANCEL,
+        OrderTerm::FILL_OR_KILL,
+    ];
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(strum_macros::ToString, strum_macros::EnumString, Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum MarketSession {
+    REGULAR,
+    EXTENDED,
+}
+
+impl MarketSession {
+    pub const ALL: [MarketSession; 2] = [
+        MarketSession::REGULAR,
+        MarketSession::EXTENDED,
+    ];
+}
+
+// Which detail block the quote endpoint returns
+#[allow(clippy::upper_case_acronyms)]
+#[derive(strum_macros::ToString, strum_macros::EnumString, Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum DetailFlag {
+    ALL,
+    FUNDAMENTAL,
+    INTRADAY,
+    OPTIONS,
+}
+
+impl DetailFlag {
+    pub const FLAGS: [DetailFlag; 4] = [
+        DetailFlag::ALL,
+        DetailFlag::FUNDAMENTAL,
+        DetailFlag::INTRADAY,
+        DetailFlag::OPTIONS,
+    ];
+}
+
+#[derive(strum_macros::ToString, strum_macros::EnumString, Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
 pub enum StatusType {
     OPEN, EXECUTED, CANCELLED, INDIVIDUAL_FILLS, CANCEL_REQUESTED, EXPIRED, REJECTED, PARTIAL, DO_NOT_EXERCISE, DONE_TRADE_EXECUTED
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+impl StatusType {
+    // statuses the list orders endpoint accepts as a filter
+    pub const FILTERS: [StatusType; 7] = [
+        StatusType::OPEN,
+        StatusType::EXECUTED,
+        StatusType::CANCELLED,
+        StatusType::INDIVIDUAL_FILLS,
+        StatusType::CANCEL_REQUESTED,
+        StatusType::EXPIRED,
+        StatusType::REJECTED,
+    ];
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Product {
     pub symbol: String,
     pub security_type: String,
+    // the rest only apply to options (security_type OPTN)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call_put: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry_year: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry_month: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry_day: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strike_price: Option<f64>,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+impl Product {
+    // e.g. "IBM 2018-08-17 $125.00 CALL" for options, otherwise just the symbol
+    pub fn display(&self) -> String {
+        match (&self.call_put, self.expiry_year, self.expiry_month, self.expiry_day, self.strike_price) {
+            (Some(call_put), Some(year), Some(month), Some(day), Some(strike_price)) => {
+                format!("{} {}-{:02}-{:02} ${:.2} {}", self.symbol, year, month, day, strike_price, call_put)
+            }
+            _ => self.symbol.to_owned(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Instrument {
     // pub symbol_description: Option<String>,
     pub order_action: String,
+    #[serde(deserialize_with = "string_or_number")]
     pub quantity: String,
     pub quantity_type: String,
     // pub cancel_quantity: Option<String>,
     // pub reserve_order: Option<bool>,
     #[serde(rename = "Product")]
     pub product: Product,
+    // specific tax lots to sell instead of the account's default lot relief
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lots: Option<Lots>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Lot {
+    pub id: i64,
+    pub size: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Lots {
+    #[serde(rename = "Lot")]
+    pub lot: Vec<Lot>,
 }
 
 // https://apisb.etrade.com/docs/api/order/api-order-v1.html#/definitions/OrderDetail
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Order {
     // pub all_or_none: bool,
@@ -68,13 +243,18 @@ pub struct Order {
     // pub preview_id: String,
     pub price_type: String,
     // pub price_value: String,
-    // pub limit_price: String,
+    #[serde(default, deserialize_with = "string_or_number", skip_serializing_if = "String::is_empty")]
+    pub limit_price: String,
+    #[serde(default, deserialize_with = "string_or_number", skip_serializing_if = "String::is_empty")]
     pub stop_price: String,
     // pub net_price: String,
     // pub net_bid: String,
     // pub net_ask: String,
-    // pub estimated_total_amount: String,
-    // pub estimated_commission: String,
+    // only present on preview and place responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_total_amount: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_commission: Option<f64>,
     #[serde(rename = "Instrument")]
     pub instrument: Vec<Instrument>,
 }
@@ -93,24 +273,57 @@ impl Default for Order {
             // preview_id: "".to_string(),
             price_type: "".to_string(),
             // price_value: "".to_string(),
-            // limit_price: "".to_string(),
+            limit_price: "".to_string(),
             stop_price: "".to_string(),
             // net_price: "".to_string(),
             // net_bid: "".to_string(),
             // net_ask: "".to_string(),
-            // estimated_total_amount: "".to_string(),
-            // estimated_commission: "".to_string(),
+            estimated_total_amount: None,
+            estimated_commission: None,
             instrument: vec![],
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct PreviewId {
-    pub preview_id: String,
+    pub preview_id: i64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderId {
+    pub order_id: i64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    pub description: String,
+    pub code: i64,
+    #[serde(rename = "type")]
+    pub message_type: String,
 }
 
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct MessageList {
+    #[serde(rename = "Message", default)]
+    pub messages: Vec<Message>,
+}
+
+// https://apisb.etrade.com/docs/api/order/api-order-v1.html#/definitions/Error
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct ErrorResponse {
+    #[serde(rename = "Error")]
+    pub error: ErrorDetail,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct ErrorDetail {
+    pub code: i64,
+    pub message: String,
+}
 // #[derive(Debug, Deserialize, Serialize, PartialEq)]
 // #[serde(rename_all = "camelCase")]
 // pub struct OrderRequest {
@@ -138,40 +351,431 @@ pub struct EtradePreviewOrderRequest {
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct PreviewOrderResponseJSON {
+    #[serde(rename = "PreviewOrderResponse")]
+    pub preview_order_response: PreviewOrderResponse,
+}
+
+// https://apisb.etrade.com/docs/api/order/api-order-v1.html#/definitions/PreviewOrderResponse
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct PreviewOrderResponse {
     pub order_type: String,
     pub account_id: String,
+    #[serde(default)]
     pub client_order_id: String,
-    pub preview_time: String,
+    pub preview_time: i64,
+    #[serde(rename = "Order")]
     pub order: Vec<Order>,
+    #[serde(rename = "PreviewIds")]
     pub preview_ids: Vec<PreviewId>,
-    pub total_commission: String,
-    pub commission_message: String,
-    pub total_order_value: String,
+    #[serde(default)]
+    pub total_commission: f64,
+    #[serde(default)]
+    pub commission_message: Option<String>,
+    #[serde(default)]
+    pub total_order_value: f64,
+    #[serde(rename = "MessageList", default)]
+    pub message_list: MessageList,
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
-#[serde(rename_all = "camelCase")]
 pub struct PlaceOrderRequest {
-    pub order_type: OrderType,
+    #[serde(rename = "PlaceOrderRequest")]
+    pub place_order_request: EtradePlaceOrderRequest,
+}
+
+// https://apisb.etrade.com/docs/api/order/api-order-v1.html#/definitions/PlaceOrderRequest
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EtradePlaceOrderRequest {
+    pub order_type: String,
     pub client_order_id: String,
+    #[serde(rename = "Order")]
     pub order: Vec<Order>,
+    #[serde(rename = "PreviewIds")]
     pub preview_ids: Vec<PreviewId>,
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct PlaceOrderResponseJSON {
+    #[serde(rename = "PlaceOrderResponse")]
+    pub place_order_response: PlaceOrderResponse,
+}
+
+// https://apisb.etrade.com/docs/api/order/api-order-v1.html#/definitions/PlaceOrderResponse
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct PlaceOrderResponse {
-    pub order_type: OrderType,
+    pub order_type: String,
     pub account_id: String,
+    #[serde(default)]
     pub client_order_id: String,
-    pub preview_time: String,
-    pub message_list: Vec<String>,
+    #[serde(default)]
+    pub placed_time: i64,
+    #[serde(rename = "MessageList", default)]
+    pub message_list: MessageList,
+    #[serde(rename = "Order")]
     pub order: Vec<Order>,
-    pub order_id: String,
-    pub preview_ids: Vec<PreviewId>,
-    pub total_commission: String,
-    pub commission_message: String,
-    pub total_order_value: String,
+    #[serde(rename = "OrderIds", default)]
+    pub order_ids: Vec<OrderId>,
+    #[serde(default)]
+    pub order_id: i64,
+    #[serde(default)]
+    pub total_commission: f64,
+    #[serde(default)]
+    pub total_order_value: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct OrdersResponseJSON {
+    #[serde(rename = "OrdersResponse")]
+    pub orders_response: OrdersResponse,
+}
+
+// https://apisb.etrade.com/docs/api/order/api-order-v1.html#/definitions/OrdersResponse
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrdersResponse {
+    #[serde(default)]
+    pub marker: Option<String>,
+    #[serde(default)]
+    pub next: Option<String>,
+    #[serde(rename = "Order", default)]
+    pub orders: Vec<OrderSummary>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderSummary {
+    pub order_id: i64,
+    pub order_type: String,
+    #[serde(rename = "OrderDetail")]
+    pub order_detail: Vec<OrderDetail>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderDetail {
+    #[serde(default)]
+    pub placed_time: Option<i64>,
+    #[serde(default)]
+    pub executed_time: Option<i64>,
+    #[serde(default)]
+    pub order_value: Option<f64>,
+    pub status: String,
+    pub order_term: String,
+    pub price_type: String,
+    #[serde(default)]
+    pub limit_price: Option<f64>,
+    #[serde(default)]
+    pub stop_price: Option<f64>,
+    pub market_session: String,
+    #[serde(rename = "Instrument")]
+    pub instrument: Vec<OrderInstrument>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderInstrument {
+    #[serde(rename = "Product")]
+    pub product: Product,
+    #[serde(default)]
+    pub symbol_description: String,
+    pub order_action: String,
+    pub quantity_type: String,
+    #[serde(default)]
+    pub ordered_quantity: f64,
+    #[serde(default)]
+    pub filled_quantity: f64,
+    #[serde(default)]
+    pub average_execution_price: Option<f64>,
+    #[serde(default)]
+    pub estimated_commission: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct CancelOrderRequest {
+    #[serde(rename = "CancelOrderRequest")]
+    pub cancel_order_request: EtradeCancelOrderRequest,
+}
+
+// https://apisb.etrade.com/docs/api/order/api-order-v1.html#/definitions/CancelOrderRequest
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EtradeCancelOrderRequest {
+    pub order_id: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct CancelOrderResponseJSON {
+    #[serde(rename = "CancelOrderResponse")]
+    pub cancel_order_response: CancelOrderResponse,
+}
+
+// https://apisb.etrade.com/docs/api/order/api-order-v1.html#/definitions/CancelOrderResponse
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelOrderResponse {
+    pub account_id: String,
+    pub order_id: i64,
+    #[serde(default)]
+    pub cancel_time: i64,
+    #[serde(rename = "Messages", default)]
+    pub messages: MessageList,
+}
+
+// MARKET
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct OptionExpireDateResponseJSON {
+    #[serde(rename = "OptionExpireDateResponse")]
+    pub option_expire_date_response: OptionExpireDateResponse,
+}
+
+// https://apisb.etrade.com/docs/api/market/api-market-v1.html#/definitions/OptionExpireDateResponse
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct OptionExpireDateResponse {
+    #[serde(rename = "ExpirationDate", default)]
+    pub expiration_dates: Vec<ExpirationDate>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpirationDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    #[serde(default)]
+    pub expiry_type: String,
+}
+
+impl ToString for ExpirationDate {
+    fn to_string(&self) -> String {
+        format!("{}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct OptionChainResponseJSON {
+    #[serde(rename = "OptionChainResponse")]
+    pub option_chain_response: OptionChainResponse,
+}
+
+// https://apisb.etrade.com/docs/api/market/api-market-v1.html#/definitions/OptionChainResponse
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionChainResponse {
+    #[serde(rename = "OptionPair", default)]
+    pub option_pairs: Vec<OptionPair>,
+    #[serde(default)]
+    pub time_stamp: i64,
+    #[serde(default)]
+    pub quote_type: String,
+    #[serde(default)]
+    pub near_price: f64,
+    #[serde(rename = "SelectedED")]
+    pub selected_ed: Option<ExpirationDate>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct OptionPair {
+    #[serde(rename = "Call")]
+    pub call: Option<OptionDetails>,
+    #[serde(rename = "Put")]
+    pub put: Option<OptionDetails>,
+}
+
+impl OptionPair {
+    pub fn strike_price(&self) -> f64 {
+        self.call.as_ref().or(self.put.as_ref()).map(|o| o.strike_price).unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionDetails {
+    #[serde(default)]
+    pub option_category: String,
+    #[serde(default)]
+    pub option_root_symbol: String,
+    #[serde(default)]
+    pub display_symbol: String,
+    pub option_type: String,
+    pub strike_price: f64,
+    pub symbol: String,
+    #[serde(default)]
+    pub bid: f64,
+    #[serde(default)]
+    pub ask: f64,
+    #[serde(default)]
+    pub bid_size: i64,
+    #[serde(default)]
+    pub ask_size: i64,
+    #[serde(default)]
+    pub in_the_money: String,
+    #[serde(default)]
+    pub volume: i64,
+    #[serde(default)]
+    pub open_interest: i64,
+    #[serde(default)]
+    pub net_change: f64,
+    #[serde(default)]
+    pub last_price: f64,
+    #[serde(default)]
+    pub osi_key: String,
+    #[serde(rename = "OptionGreeks", default)]
+    pub option_greeks: OptionGreeks,
+}
+
+impl OptionDetails {
+    pub fn is_in_the_money(&self) -> bool {
+        self.in_the_money == "y"
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionGreeks {
+    #[serde(default)]
+    pub rho: f64,
+    #[serde(default)]
+    pub vega: f64,
+    #[serde(default)]
+    pub theta: f64,
+    #[serde(default)]
+    pub delta: f64,
+    #[serde(default)]
+    pub gamma: f64,
+    #[serde(default)]
+    pub iv: f64,
+    #[serde(default)]
+    pub current_value: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct TransactionListResponseJSON {
+    #[serde(rename = "TransactionListResponse")]
+    pub transaction_list_response: TransactionListResponse,
+}
+
+// https://apisb.etrade.com/docs/api/account/api-transaction-v1.html#/definitions/TransactionListResponse
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionListResponse {
+    // pass back as `marker` for the next page
+    #[serde(default)]
+    pub marker: Option<String>,
+    #[serde(default)]
+    pub more_transactions: bool,
+    #[serde(rename = "Transaction", default)]
+    pub transactions: Vec<Transaction>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub transaction_id: i64,
+    // epoch milliseconds
+    #[serde(default)]
+    pub transaction_date: i64,
+    #[serde(default)]
+    pub amount: f64,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub transaction_type: String,
+    #[serde(default)]
+    pub brokerage: Option<TransactionBrokerage>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionProduct {
+    #[serde(default)]
+    pub symbol: String,
+    #[serde(default)]
+    pub security_type: String,
+    // options only
+    #[serde(default)]
+    pub call_put: Option<String>,
+    #[serde(default)]
+    pub expiry_year: Option<i32>,
+    #[serde(default)]
+    pub expiry_month: Option<u32>,
+    #[serde(default)]
+    pub expiry_day: Option<u32>,
+    #[serde(default)]
+    pub strike_price: Option<f64>,
+}
+
+impl TransactionProduct {
+    // the contract for options, otherwise just the symbol
+    pub fn display(&self) -> String {
+        Product {
+            symbol: self.symbol.to_owned(),
+            security_type: self.security_type.to_owned(),
+            call_put: self.call_put.to_owned(),
+            expiry_year: self.expiry_year,
+            expiry_month: self.expiry_month,
+            expiry_day: self.expiry_day,
+            strike_price: self.strike_price,
+        }
+        .display()
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionBrokerage {
+    #[serde(default)]
+    pub product: TransactionProduct,
+    #[serde(default)]
+    pub quantity: f64,
+    #[serde(default)]
+    pub price: f64,
+    #[serde(default)]
+    pub fee: f64,
+    #[serde(default)]
+    pub settlement_date: Option<i64>,
+    // only sent by the details endpoint
+    #[serde(default)]
+    pub transaction_type: Option<String>,
+    #[serde(default)]
+    pub order_no: Option<String>,
+    #[serde(default)]
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct TransactionDetailsResponseJSON {
+    #[serde(rename = "TransactionDetailsResponse")]
+    pub transaction_details_response: TransactionDetails,
+}
+
+// https://apisb.etrade.com/docs/api/account/api-transaction-v1.html#/definitions/TransactionDetailsResponse
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionDetails {
+    pub transaction_id: i64,
+    #[serde(default)]
+    pub transaction_date: i64,
+    #[serde(default)]
+    pub post_date: Option<i64>,
+    #[serde(default)]
+    pub amount: f64,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub category: Option<TransactionCategory>,
+    #[serde(default)]
+    pub brokerage: Option<TransactionBrokerage>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionCategory {
+    #[serde(default)]
+    pub category_name: String,
+    #[serde(default)]
+    pub parent_name: String,
 }
@@ -1,22 +1,43 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "camelCase")]
+// The All, Fundamental, Intraday and Option detail blocks of a quote share their field names,
+// each block just sends a different subset of them
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
 pub struct Info {
+    pub company_name: String,
     pub primary_exchange: String,
-    pub declared_dividend: String,
-    pub dividend: String,
+    pub declared_dividend: f64,
+    pub dividend: f64,
     pub ex_dividend_date: i64,
-    pub bid: String,
-    pub ask: String,
-    pub open: String,
-    pub high52: String,
+    pub bid: f64,
+    pub bid_size: u64,
+    pub ask: f64,
+    pub ask_size: u64,
+    pub last_trade: f64,
+    pub change_close: f64,
+    pub change_close_percentage: f64,
+    pub previous_close: f64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub total_volume: u64,
+    pub high52: f64,
     pub week52_hi_date: i64,
-    pub low52: String,
+    pub low52: f64,
     pub week52_low_date: i64,
-    pub eps: String,
-    pub pe: String,
-    pub beta: String,
+    pub eps: f64,
+    pub est_earnings: f64,
+    pub pe: f64,
+    pub beta: f64,
+    pub market_cap: f64,
+    pub shares_outstanding: f64,
+    pub next_earning_date: String,
+    pub open_interest: u64,
+    pub days_to_expiration: u64,
+    pub intrinsic_value: f64,
+    pub time_premium: f64,
+    pub option_multiplier: f64,
 }
 
 #[derive(Debug, Deserialize, PartialEq)]
@@ -28,8 +49,15 @@ pub struct Product {
 
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct QuoteData {
+    // only the block asked for with detailFlag is sent
     #[serde(rename = "All")]
-    pub info: Info,
+    pub all: Option<Info>,
+    #[serde(rename = "Fundamental")]
+    pub fundamental: Option<Info>,
+    #[serde(rename = "Intraday")]
+    pub intraday: Option<Info>,
+    #[serde(rename = "Option")]
+    pub option: Option<Info>,
     #[serde(rename = "Product")]
     pub product: Product,
 
@@ -45,6 +73,13 @@ pub struct TickerXML {
     pub quote_data: QuoteData,
 }
 
+// multi-symbol quote response, one QuoteData per symbol
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct QuotesXML {
+    #[serde(rename = "QuoteData", default)]
+    pub quote_data: Vec<QuoteData>,
+}
+
 
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct TickerSearchData {
@@ -71,6 +106,9 @@ pub struct Account {
     pub account_name: String,
     pub account_type: String,
     pub account_status: String,
+    // BROKERAGE, BANK, ...
+    #[serde(default)]
+    pub institution_type: String,
     // custom client side field for display
     pub account_balance: Option<AccountBalance>,
 }
@@ -94,6 +132,15 @@ pub struct AccountProduct {
     pub security_type: String,
 }
 
+// the price snapshot sent with each position in the default QUICK view
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PositionQuick {
+    pub last_trade: f64,
+    pub change: f64,
+    pub change_pct: f64,
+}
+
 #[derive(Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Position {
@@ -101,13 +148,69 @@ pub struct Position {
     #[serde(rename = "Product")]
     pub product: AccountProduct,
     pub symbol_description: String,
+    // LONG or SHORT
+    #[serde(default)]
+    pub position_type: String,
+    #[serde(default)]
+    pub quantity: f64,
+    #[serde(default)]
+    pub price_paid: f64,
+    #[serde(default)]
+    pub cost_per_share: f64,
+    #[serde(default)]
+    pub total_cost: f64,
+    #[serde(default)]
+    pub market_value: f64,
+    #[serde(default)]
+    pub total_gain: f64,
+    #[serde(default)]
+    pub total_gain_pct: f64,
+    #[serde(default)]
+    pub days_gain: f64,
+    #[serde(default)]
+    pub days_gain_pct: f64,
+    #[serde(default)]
+    pub pct_of_portfolio: f64,
+    #[serde(rename = "Quick", default)]
+    pub quick: PositionQuick,
+    // url of the position's tax lots
+    #[serde(default)]
+    pub lots_details: String,
+}
+
+// One tax lot of a position, from the position's lotsDetails link
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionLot {
+    pub position_lot_id: i64,
+    #[serde(default)]
+    pub price: f64,
+    // epoch millis
+    #[serde(default)]
+    pub acquired_date: i64,
+    #[serde(default)]
+    pub remaining_qty: f64,
+    #[serde(default)]
+    pub available_qty: f64,
+    #[serde(default)]
+    pub total_cost: f64,
+    #[serde(default)]
+    pub market_value: f64,
+    #[serde(default)]
+    pub total_gain: f64,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct PositionLotsXML {
+    #[serde(rename = "PositionLot", default)]
+    pub position_lots: Vec<PositionLot>,
 }
 
 #[derive(Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountPortfolio {
     pub account_id: String,
-    #[serde(rename = "Position")]
+    #[serde(rename = "Position", default)]
     pub positions: Vec<Position>,
 }
 
@@ -141,10 +244,32 @@ pub struct ComputedBalance {
     pub cash_available_for_withdrawal: String,
     pub net_cash: String,
     pub cash_balance: String,
+    // the buying power and RegT fields only come back for margin accounts
+    #[serde(default)]
+    pub cash_buying_power: Option<String>,
+    #[serde(default)]
+    pub margin_buying_power: Option<String>,
+    #[serde(default)]
+    pub dt_margin_buying_power: Option<String>,
+    #[serde(default)]
+    pub margin_balance: Option<String>,
+    #[serde(default)]
+    pub regt_equity: Option<String>,
+    #[serde(default)]
+    pub regt_equity_percent: Option<String>,
     #[serde(rename = "RealTimeValues")]
     pub real_time_values: RealTimeValues,
 }
 
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MarginBalance {
+    #[serde(default)]
+    pub dt_cash_open_order_reserve: Option<String>,
+    #[serde(default)]
+    pub dt_margin_open_order_reserve: Option<String>,
+}
+
 #[derive(Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountBalance {
@@ -153,6 +278,8 @@ pub struct AccountBalance {
     pub cash: Cash,
     #[serde(rename = "Computed")]
     pub computed: Option<ComputedBalance>,
+    #[serde(rename = "Margin", default)]
+    pub margin: Option<MarginBalance>,
 }
 
 #[derive(Debug, Deserialize, PartialEq)]
@@ -1,10 +1,12 @@
 use crate::stonks_error::RuntimeError;
 use crate::session::{Credentials, Mode};
+use crate::store::Store;
 use crate::ui::key::Key;
 use serde::{Serialize, Deserialize};
+use secstr::SecUtf8;
 use std::{
     fs,
-    io::{stdin, Write},
+    io::stdin,
     path::{Path, PathBuf}
 };
 use tui::style::{Color};
@@ -35,6 +37,24 @@ const SANDBOX_SEARCH_URL: &str = "https://apisb.etrade.com/v1/market/lookup";
 const PORTFOLIO_URL: &str = "https://api.etrade.com/v1/accounts/{}/portfolio";
 const SANDBOX_PORTFOLIO_URL: &str = "https://apisb.etrade.com/v1/accounts/{}/portfolio";
 
+const ORDERS_URL: &str = "https://api.etrade.com/v1/accounts/{}/orders.json";
+const SANDBOX_ORDERS_URL: &str = "https://apisb.etrade.com/v1/accounts/{}/orders.json";
+
+const OPTION_CHAINS_URL: &str = "https://api.etrade.com/v1/market/optionchains.json";
+const SANDBOX_OPTION_CHAINS_URL: &str = "https://apisb.etrade.com/v1/market/optionchains.json";
+
+const OPTION_EXPIRE_DATE_URL: &str = "https://api.etrade.com/v1/market/optionexpiredate.json";
+const SANDBOX_OPTION_EXPIRE_DATE_URL: &str = "https://apisb.etrade.com/v1/market/optionexpiredate.json";
+
+const CANCEL_ORDER_URL: &str = "https://api.etrade.com/v1/accounts/{}/orders/cancel.json";
+const SANDBOX_CANCEL_ORDER_URL: &str = "https://apisb.etrade.com/v1/accounts/{}/orders/cancel.json";
+
+const TRANSACTIONS_URL: &str = "https://api.etrade.com/v1/accounts/{}/transactions.json";
+const SANDBOX_TRANSACTIONS_URL: &str = "https://apisb.etrade.com/v1/accounts/{}/transactions.json";
+
+const TRANSACTION_DETAILS_URL: &str = "https://api.etrade.com/v1/accounts/{}/transactions/{}.json";
+const SANDBOX_TRANSACTION_DETAILS_URL: &str = "https://apisb.etrade.com/v1/accounts/{}/transactions/{}.json";
+
 
 // const DEFAULT_PORT: u16 = 8888;
 const FILE_NAME: &str = "client.yml";
@@ -43,6 +63,15 @@ const CONFIG_DIR: &str = ".config";
 const APP_CONFIG_DIR: &str = "stonks-terminal";
 const TOKEN_CACHE_FILE: &str = ".stonks_terminal_token_cache.json";
 const SANDBOX_TOKEN_CACHE_FILE: &str = ".stonks_terminal_token_cache-sandbox.json";
+const WATCH_LISTS_FILE: &str = "watch_lists.yml";
+// the buys each sell closed for specific lot matching, the format is in the README
+const LOT_SELECTIONS_FILE: &str = "lot_selections.yml";
+const KEY_FILE: &str = ".stonks_terminal.key";
+const SALT_FILE: &str = ".stonks_terminal.salt";
+const HISTORY_DIR: &str = "history";
+const EXPORTS_DIR: &str = "exports";
+const DEFAULT_QUOTE_REFRESH_INTERVAL: u64 = 5;
+const DEFAULT_CLOSED_QUOTE_REFRESH_INTERVAL: u64 = 300;
 
 #[derive(Clone)]
 pub struct KeyBindings {
@@ -66,6 +95,8 @@ pub struct Theme {
   pub selected: Color,
   pub text: Color,
   pub header: Color,
+  pub gain: Color,
+  pub loss: Color,
 }
 
 impl Default for Theme {
@@ -81,6 +112,36 @@ impl Default for Theme {
       selected: Color::LightCyan,
       text: Color::Reset,
       header: Color::Reset,
+      gain: Color::Green,
+      loss: Color::Red,
+    }
+  }
+}
+
+// Lookback periods for the analysis screen, in daily bars
+#[derive(Copy, Clone, Debug)]
+pub struct IndicatorPeriods {
+  pub sma: usize,
+  pub ema: usize,
+  pub rsi: usize,
+  pub macd_fast: usize,
+  pub macd_slow: usize,
+  pub macd_signal: usize,
+  pub bollinger: usize,
+  pub bollinger_std_devs: f64,
+}
+
+impl Default for IndicatorPeriods {
+  fn default() -> Self {
+    IndicatorPeriods {
+      sma: 20,
+      ema: 50,
+      rsi: 14,
+      macd_fast: 12,
+      macd_slow: 26,
+      macd_signal: 9,
+      bollinger: 20,
+      bollinger_std_devs: 2.0,
     }
   }
 }
@@ -90,6 +151,11 @@ pub struct UserConfig {
     pub path_to_config: Option<PathBuf>,
     pub keys: KeyBindings,
     pub theme: Theme,
+    // seconds between quote refreshes, 0 turns polling off
+    pub quote_refresh_interval: u64,
+    // slower polling while the market is closed
+    pub closed_quote_refresh_interval: u64,
+    pub indicator_periods: IndicatorPeriods,
 }
 
 impl UserConfig {
@@ -105,7 +171,9 @@ impl UserConfig {
                 submit: Key::Enter,
                 basic_view: Key::Char('B'),
             },
-
+            quote_refresh_interval: DEFAULT_QUOTE_REFRESH_INTERVAL,
+            closed_quote_refresh_interval: DEFAULT_CLOSED_QUOTE_REFRESH_INTERVAL,
+            indicator_periods: IndicatorPeriods::default(),
         }
     }
 }
@@ -128,6 +196,18 @@ pub struct UrlConfig<'a> {
     pub sandbox_accounts_list_url: &'a str,
     pub alerts_url: &'a str,
     pub sandbox_alerts_url: &'a str,
+    pub orders_url: &'a str,
+    pub sandbox_orders_url: &'a str,
+    pub cancel_order_url: &'a str,
+    pub sandbox_cancel_order_url: &'a str,
+    pub option_chains_url: &'a str,
+    pub sandbox_option_chains_url: &'a str,
+    pub option_expire_date_url: &'a str,
+    pub sandbox_option_expire_date_url: &'a str,
+    pub transactions_url: &'a str,
+    pub sandbox_transactions_url: &'a str,
+    pub transaction_details_url: &'a str,
+    pub sandbox_transaction_details_url: &'a str,
 }
 
 impl<'a> Default for UrlConfig<'a> {
@@ -149,12 +229,24 @@ impl<'a> Default for UrlConfig<'a> {
             sandbox_accounts_list_url: SANDBOX_ACCOUNTS_LIST_URL,
             alerts_url: ALERTS_URL,
             sandbox_alerts_url: SANDBOX_ALERTS_URL,
+            orders_url: ORDERS_URL,
+            sandbox_orders_url: SANDBOX_ORDERS_URL,
+            cancel_order_url: CANCEL_ORDER_URL,
+            sandbox_cancel_order_url: SANDBOX_CANCEL_ORDER_URL,
+            option_chains_url: OPTION_CHAINS_URL,
+            sandbox_option_chains_url: SANDBOX_OPTION_CHAINS_URL,
+            option_expire_date_url: OPTION_EXPIRE_DATE_URL,
+            sandbox_option_expire_date_url: SANDBOX_OPTION_EXPIRE_DATE_URL,
+            transactions_url: TRANSACTIONS_URL,
+            sandbox_transactions_url: SANDBOX_TRANSACTIONS_URL,
+            transaction_details_url: TRANSACTION_DETAILS_URL,
+            sandbox_transaction_details_url: SANDBOX_TRANSACTION_DETAILS_URL,
         }
     }
 }
 
 impl<'a> UrlConfig<'a> {
-    pub fn authorize_url(&self, key: &String, token: &String) -> String {
+    pub fn authorize_url(&self, key: &str, token: &str) -> String {
         format!(
             "https://us.etrade.com/e/t/etws/authorize?key={}&token={}",
             key,
@@ -189,15 +281,6 @@ impl<'a> UrlConfig<'a> {
         url.to_string()
     }
 
-    pub fn account_balance(&self, account_id_key: &str, mode: &Mode) -> String {
-        let url = match mode {
-            Mode::Sandbox => format!("https://apisb.etrade.com/v1/accounts/{}/balance?instType={}&realTimeNAV=true", account_id_key, "BROKERAGE"),
-            Mode::Live => format!("https://api.etrade.com/v1/accounts/{}/balance?instType={}&realTimeNAV=true", account_id_key, "BROKERAGE"),
-        };
-
-        url
-    }
-
     pub fn account_balance_base(&self, account_id_key: &str, mode: &Mode) -> String {
         let url = match mode {
             Mode::Sandbox => format!("https://apisb.etrade.com/v1/accounts/{}/balance", account_id_key),
@@ -207,6 +290,10 @@ impl<'a> UrlConfig<'a> {
         url
     }
 
+    pub fn etrade_quotes_url(&self, symbols: &[String], mode: &Mode) -> String {
+        self.etrade_ticker_url(&symbols.join(","), mode)
+    }
+
     pub fn etrade_ticker_url(&self, symbol: &str, mode: &Mode) -> String {
         let url = match mode {
             Mode::Sandbox => self.sandbox_quote_url,
@@ -249,6 +336,78 @@ impl<'a> UrlConfig<'a> {
         url
     }
 
+    pub fn etrade_change_order_preview_url(&self, account_id_key: &str, order_id: i64, mode: &Mode) -> String {
+        let url = match mode {
+            Mode::Sandbox => format!("https://apisb.etrade.com/v1/accounts/{}/orders/{}/change/preview.json", account_id_key, order_id),
+            Mode::Live => format!("https://api.etrade.com/v1/accounts/{}/orders/{}/change/preview.json", account_id_key, order_id),
+        };
+
+        url
+    }
+
+    pub fn etrade_change_order_place_url(&self, account_id_key: &str, order_id: i64, mode: &Mode) -> String {
+        let url = match mode {
+            Mode::Sandbox => format!("https://apisb.etrade.com/v1/accounts/{}/orders/{}/change/place.json", account_id_key, order_id),
+            Mode::Live => format!("https://api.etrade.com/v1/accounts/{}/orders/{}/change/place.json", account_id_key, order_id),
+        };
+
+        url
+    }
+
+    pub fn etrade_orders_url(&self, account_id_key: &str, mode: &Mode) -> String {
+        let url = match mode {
+            Mode::Sandbox => self.sandbox_orders_url,
+            Mode::Live => self.orders_url,
+        };
+
+        url.replacen("{}", account_id_key, 1)
+    }
+
+    pub fn etrade_cancel_order_url(&self, account_id_key: &str, mode: &Mode) -> String {
+        let url = match mode {
+            Mode::Sandbox => self.sandbox_cancel_order_url,
+            Mode::Live => self.cancel_order_url,
+        };
+
+        url.replacen("{}", account_id_key, 1)
+    }
+
+    pub fn etrade_transactions_url(&self, account_id_key: &str, mode: &Mode) -> String {
+        let url = match mode {
+            Mode::Sandbox => self.sandbox_transactions_url,
+            Mode::Live => self.transactions_url,
+        };
+
+        url.replacen("{}", account_id_key, 1)
+    }
+
+    pub fn etrade_transaction_details_url(&self, account_id_key: &str, transaction_id: i64, mode: &Mode) -> String {
+        let url = match mode {
+            Mode::Sandbox => self.sandbox_transaction_details_url,
+            Mode::Live => self.transaction_details_url,
+        };
+
+        url.replacen("{}", account_id_key, 1).replacen("{}", &transaction_id.to_string(), 1)
+    }
+
+    pub fn etrade_option_chains_url(&self, mode: &Mode) -> String {
+        let url = match mode {
+            Mode::Sandbox => self.sandbox_option_chains_url,
+            Mode::Live => self.option_chains_url,
+        };
+
+        url.to_string()
+    }
+
+    pub fn etrade_option_expire_date_url(&self, mode: &Mode) -> String {
+        let url = match mode {
+            Mode::Sandbox => self.sandbox_option_expire_date_url,
+            Mode::Live => self.option_expire_date_url,
+        };
+
+        url.to_string()
+    }
+
     pub fn etrade_order_place_url(&self, account_id_key: &str, mode: &Mode) -> String {
         let url = match mode {
             Mode::Sandbox => format!("https://apisb.etrade.com/v1/accounts/{}/orders/place.json", account_id_key),
@@ -263,38 +422,46 @@ impl<'a> UrlConfig<'a> {
 pub struct ConfigPaths {
     pub config_file_path: PathBuf,
     pub token_cache_path: PathBuf,
+    pub watch_lists_path: PathBuf,
+    // lots picked for each sale when matching realized gains by specific lot
+    pub lot_selections_path: PathBuf,
+    // csv price history for charts
+    pub history_dir: PathBuf,
+    // where csv exports are written
+    pub exports_dir: PathBuf,
+    // encrypts client.yml and the token cache, unless a passphrase is set
+    pub key_path: PathBuf,
+    // salt for the key derived from a passphrase
+    pub salt_path: PathBuf,
 }
 
-#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ClientConfig {
     pub consumer_key: String,
-    pub consumer_secret: String,
+    pub consumer_secret: SecUtf8,
 }
 
 impl ClientConfig {
     pub fn new() -> Self {
         Self {
             consumer_key: "".to_string(),
-            consumer_secret: "".to_string(),
+            consumer_secret: SecUtf8::from(""),
         }
     }
 
-    pub fn load_config(&mut self, mode: &Mode) -> Result<ConfigPaths, RuntimeError> {
-        let paths = self.get_or_build_paths(&mode)?;
-        if paths.config_file_path.exists() {
+    pub fn consumer_creds(&self) -> Credentials {
+        Credentials::new(SecUtf8::from(self.consumer_key.as_str()), self.consumer_secret.clone())
+    }
+
+    // client.yml goes through the store, which keeps it encrypted
+    pub fn load_config(&mut self, paths: &ConfigPaths, store: &impl Store) -> Result<(), RuntimeError> {
+        if let Some(config_string) = store.read_file(&paths.config_file_path)? {
             debug!("Loading keys from config");
 
-            let config_string = fs::read_to_string(&paths.config_file_path)?;
-            let config_yaml: ClientConfig = serde_yaml::from_str(&config_string)?;
-
-            self.consumer_key = config_yaml.consumer_key
-                .strip_suffix("\n")
-                .unwrap_or(&config_yaml.consumer_key)
-                .to_string();
-            self.consumer_secret = config_yaml.consumer_secret
-                .strip_suffix("\n")
-                .unwrap_or(&config_yaml.consumer_secret)
-                .to_string();
+            let config_yaml: ClientConfig = serde_yaml::from_str(config_string.unsecure())?;
+
+            self.consumer_key = config_yaml.consumer_key.trim_end().to_string();
+            self.consumer_secret = SecUtf8::from(config_yaml.consumer_secret.unsecure().trim_end());
         } else {
             debug!("Config will be saved to {}", paths.config_file_path.display());
 
@@ -306,21 +473,19 @@ impl ClientConfig {
 
             let client_config = Self {
                 consumer_key,
-                consumer_secret,
+                consumer_secret: SecUtf8::from(consumer_secret),
             };
 
-            let client_yaml = serde_yaml::to_string(&client_config)?;
-            let mut new_config = fs::File::create(&paths.config_file_path)?;
-            write!(new_config, "{}", client_yaml)?;
+            let client_yaml = SecUtf8::from(serde_yaml::to_string(&client_config)?);
+            store.write_file(&paths.config_file_path, &client_yaml)?;
 
-            self.consumer_key = client_config.consumer_key.trim().to_string();
-            self.consumer_secret = client_config.consumer_secret.trim().to_string();
+            *self = client_config;
         }
 
-        Ok(paths)
+        Ok(())
     }
 
-    fn get_or_build_paths(&self, mode: &Mode) -> Result<ConfigPaths, RuntimeError> {
+    pub fn config_paths(mode: &Mode) -> Result<ConfigPaths, RuntimeError> {
         match dirs::home_dir() {
             Some(home) => {
                 let path = Path::new(&home);
@@ -345,10 +510,19 @@ impl ClientConfig {
                     Mode::Sandbox => SANDBOX_TOKEN_CACHE_FILE,
                 };
                 let token_cache_path = &app_config_dir.join(token_file);
+                // watch lists are shared between live and sandbox
+                let watch_lists_path = &app_config_dir.join(WATCH_LISTS_FILE);
 
                 let paths = ConfigPaths {
                     config_file_path: config_file_path.to_path_buf(),
                     token_cache_path: token_cache_path.to_path_buf(),
+                    watch_lists_path: watch_lists_path.to_path_buf(),
+                    lot_selections_path: app_config_dir.join(LOT_SELECTIONS_FILE),
+                    history_dir: app_config_dir.join(HISTORY_DIR),
+                    exports_dir: app_config_dir.join(EXPORTS_DIR),
+                    // one key for live and sandbox
+                    key_path: app_config_dir.join(KEY_FILE),
+                    salt_path: app_config_dir.join(SALT_FILE),
                 };
 
                 Ok(paths)
@@ -369,7 +543,7 @@ impl ClientConfig {
 impl From<Credentials> for ClientConfig {
     fn from(t: Credentials) -> ClientConfig {
         Self {
-            consumer_key: t.key,
+            consumer_key: t.key.into_unsecure(),
             consumer_secret: t.secret,
         }
     }
@@ -0,0 +1,209 @@
+// Technical indicators over a series of closes, oldest first. Every function returns one value per
+// input so results line up with the bars they came from, None until there is enough history.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Macd {
+    pub macd: Vec<Option<f64>>,
+    pub signal: Vec<Option<f64>>,
+    pub histogram: Vec<Option<f64>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bollinger {
+    pub upper: Vec<Option<f64>>,
+    pub middle: Vec<Option<f64>>,
+    pub lower: Vec<Option<f64>>,
+}
+
+pub fn sma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    if period == 0 {
+        return vec![None; values.len()];
+    }
+
+    let mut result = vec![None; values.len()];
+    let mut sum = 0.0;
+    for (i, value) in values.iter().enumerate() {
+        sum += value;
+        if i >= period {
+            sum -= values[i - period];
+        }
+        if i + 1 >= period {
+            result[i] = Some(sum / period as f64);
+        }
+    }
+
+    result
+}
+
+// Seeded with the SMA of the first `period` values, the way most charting packages do it
+pub fn ema(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if period == 0 || values.len() < period {
+        return result;
+    }
+
+    let k = 2.0 / (period as f64 + 1.0);
+    let mut previous = values[..period].iter().sum::<f64>() / period as f64;
+    result[period - 1] = Some(previous);
+    for i in period..values.len() {
+        previous = (values[i] - previous) * k + previous;
+        result[i] = Some(previous);
+    }
+
+    result
+}
+
+// Wilder's RSI, gains and losses smoothed with a 1/period moving average
+pub fn rsi(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if period == 0 || values.len() <= period {
+        return result;
+    }
+
+    let changes = values.windows(2).map(|w| w[1] - w[0]).collect::<Vec<f64>>();
+    let mut gain = changes[..period].iter().map(|c| c.max(0.0)).sum::<f64>() / period as f64;
+    let mut loss = changes[..period].iter().map(|c| (-c).max(0.0)).sum::<f64>() / period as f64;
+    result[period] = Some(rsi_value(gain, loss));
+
+    for i in period..changes.len() {
+        gain = (gain * (period - 1) as f64 + changes[i].max(0.0)) / period as f64;
+        loss = (loss * (period - 1) as f64 + (-changes[i]).max(0.0)) / period as f64;
+        result[i + 1] = Some(rsi_value(gain, loss));
+    }
+
+    result
+}
+
+fn rsi_value(gain: f64, loss: f64) -> f64 {
+    if loss == 0.0 {
+        return 100.0;
+    }
+    100.0 - 100.0 / (1.0 + gain / loss)
+}
+
+pub fn macd(values: &[f64], fast: usize, slow: usize, signal: usize) -> Macd {
+    let fast = ema(values, fast);
+    let slow = ema(values, slow);
+    let macd = fast
+        .iter()
+        .zip(slow.iter())
+        .map(|(fast, slow)| Some((*fast)? - (*slow)?))
+        .collect::<Vec<Option<f64>>>();
+
+    // the signal line only starts once there are MACD values to average
+    let start = macd.iter().position(|value| value.is_some()).unwrap_or(macd.len());
+    let defined = macd[start..].iter().map(|value| value.unwrap()).collect::<Vec<f64>>();
+    let mut signal_line = vec![None; start];
+    signal_line.extend(ema(&defined, signal));
+
+    let histogram = macd
+        .iter()
+        .zip(signal_line.iter())
+        .map(|(macd, signal)| Some((*macd)? - (*signal)?))
+        .collect();
+
+    Macd {
+        macd,
+        signal: signal_line,
+        histogram,
+    }
+}
+
+// Middle band is the SMA, the others are `std_devs` population standard deviations away
+pub fn bollinger(values: &[f64], period: usize, std_devs: f64) -> Bollinger {
+    let middle = sma(values, period);
+    let width = middle
+        .iter()
+        .enumerate()
+        .map(|(i, mean)| {
+            let mean = (*mean)?;
+            let window = &values[i + 1 - period..=i];
+            let variance = window.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / period as f64;
+            Some(variance.sqrt() * std_devs)
+        })
+        .collect::<Vec<Option<f64>>>();
+
+    Bollinger {
+        upper: middle.iter().zip(width.iter()).map(|(m, w)| Some((*m)? + (*w)?)).collect(),
+        lower: middle.iter().zip(width.iter()).map(|(m, w)| Some((*m)? - (*w)?)).collect(),
+        middle,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the worked examples from StockCharts' ChartSchool articles
+    const EMA_CLOSES: [f64; 20] = [
+        22.27, 22.19, 22.08, 22.17, 22.18, 22.13, 22.23, 22.43, 22.24, 22.29,
+        22.15, 22.39, 22.38, 22.61, 23.36, 24.05, 23.75, 23.83, 23.95, 23.63,
+    ];
+    const RSI_CLOSES: [f64; 20] = [
+        44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08,
+        45.89, 46.03, 45.61, 46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64,
+    ];
+
+    fn assert_close(actual: &[Option<f64>], expected: &[f64], tolerance: f64) {
+        let actual = actual.iter().flatten().cloned().collect::<Vec<f64>>();
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < tolerance, "{} != {}", a, e);
+        }
+    }
+
+    #[test]
+    fn simple_moving_average() {
+        let result = sma(&[1.0, 2.0, 3.0, 4.0, 5.0], 3);
+        assert_eq!(result, vec![None, None, Some(2.0), Some(3.0), Some(4.0)]);
+        assert_eq!(sma(&[1.0, 2.0], 3), vec![None, None]);
+        assert_eq!(sma(&[1.0], 0), vec![None]);
+    }
+
+    #[test]
+    fn exponential_moving_average() {
+        let result = ema(&EMA_CLOSES, 10);
+        assert_eq!(result[8], None);
+        assert_close(
+            &result,
+            &[22.22, 22.21, 22.24, 22.27, 22.33, 22.52, 22.80, 22.97, 23.13, 23.28, 23.34],
+            0.01,
+        );
+    }
+
+    #[test]
+    fn relative_strength_index() {
+        let result = rsi(&RSI_CLOSES, 14);
+        assert_eq!(result[13], None);
+        // StockCharts rounds its averages to two places and lists 70.53, 66.32, ...
+        assert_close(&result, &[70.46, 66.25, 66.48, 69.35, 66.29, 57.92], 0.01);
+
+        let rising = (1..=20).map(|i| i as f64).collect::<Vec<f64>>();
+        assert_eq!(rsi(&rising, 14).last(), Some(&Some(100.0)));
+    }
+
+    #[test]
+    fn macd_of_a_straight_line() {
+        // an SMA seeded EMA of a line with slope 1 lags it by exactly (period - 1) / 2
+        let line = (0..60).map(|i| i as f64).collect::<Vec<f64>>();
+        let result = macd(&line, 12, 26, 9);
+
+        assert_eq!(result.macd.iter().position(|v| v.is_some()), Some(25));
+        assert_eq!(result.signal.iter().position(|v| v.is_some()), Some(33));
+        assert_close(&result.macd, &[7.0; 35], 1e-9);
+        assert_close(&result.histogram, &[0.0; 27], 1e-9);
+    }
+
+    #[test]
+    fn bollinger_bands() {
+        let result = bollinger(&EMA_CLOSES[..10], 10, 2.0);
+
+        assert_close(&result.middle, &[22.221], 1e-9);
+        assert_close(&result.upper, &[22.405], 0.001);
+        assert_close(&result.lower, &[22.037], 0.001);
+
+        let flat = bollinger(&[5.0; 4], 2, 2.0);
+        assert_eq!(flat.upper, flat.lower);
+        assert_eq!(flat.upper[0], None);
+    }
+}
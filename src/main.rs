@@ -4,17 +4,28 @@ mod clients;
 mod config;
 mod session;
 mod stonks_error;
+mod indicators;
 mod network;
+mod price_history;
+mod realized_gains;
+mod spread;
+mod transactions;
 mod store;
+mod vault;
 mod ui;
 mod app;
+mod watch_lists;
+#[cfg(test)]
+mod test_helpers;
 use config::{ClientConfig, UserConfig};
-use store::AuthInMemoryStore;
+use store::EncryptedStore;
+use vault::Vault;
 use crate::clients::{Etrade};
-use crate::session::{Mode, Session};
+use crate::session::{Mode, Session, Verifier};
 use crate::store::{Store};
 use crate::network::{Network, IoEvent};
-use std::sync::mpsc::Receiver;
+use crate::price_history::CsvPriceHistory;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
 use stonks_error::RuntimeError;
 use clap::{App as ClapApp, Arg};
 use crossterm::{
@@ -27,6 +38,7 @@ use crossterm::{
 use std::{
     sync::Arc,
     io::{stdout},
+    time::Duration,
 };
 use log::debug;
 use tokio::sync::Mutex;
@@ -44,7 +56,8 @@ async fn main() -> Result<(), RuntimeError> {
         .author("Scott N. <snewcomer24@gmail.com>")
         .about("Trade easy")
         .after_help(
-          "Your etrade Client ID and Client Secret are stored in $HOME/.config/stonks-terminal/client.yml",
+          "Your etrade Client ID and Client Secret are stored encrypted in $HOME/.config/stonks-terminal/client.yml, \
+          with the key in .stonks_terminal.key next to it. Set STONKS_TERMINAL_PASSPHRASE to derive the key from a passphrase instead.",
         )
         .arg(Arg::with_name("config")
             .short("c")
@@ -56,43 +69,48 @@ async fn main() -> Result<(), RuntimeError> {
             .long("mode")
             .help("Specify either --sandbox or --live. Default is --live.")
             .takes_value(true))
+        .arg(Arg::with_name("refresh")
+            .short("r")
+            .long("refresh")
+            .help("Seconds between quote refreshes, 0 turns them off. Default is 5.")
+            .takes_value(true))
         .get_matches();
 
+    let mut user_config = UserConfig::new();
+    if let Some(refresh) = matches.value_of("refresh") {
+        user_config.quote_refresh_interval = refresh
+            .parse()
+            .map_err(|_| RuntimeError { message: format!("{} is not a number of seconds", refresh) })?;
+    }
+
     match matches.value_of("mode") {
-        Some("sandbox") => run(Mode::Sandbox).await,
-        _ => run(Mode::Live).await,
+        Some("sandbox") => run(Mode::Sandbox, user_config).await,
+        _ => run(Mode::Live, user_config).await,
     }
 }
 
-async fn run(mode: Mode) -> Result<(), RuntimeError> {
+async fn run(mode: Mode, user_config: UserConfig) -> Result<(), RuntimeError> {
+    let config_paths = ClientConfig::config_paths(&mode)?;
+    let store = EncryptedStore::new(Vault::new(&config_paths)?, config_paths.token_cache_path.to_owned());
     let mut client_config = ClientConfig::new();
     // ask user for configuration details
-    let config_paths = client_config.load_config(&mode)?;
-    let user_config = UserConfig::new();
+    client_config.load_config(&config_paths, &store)?;
 
     env_logger::init();
 
     debug!("Request token in flight for {:?} mode", mode);
 
-    let mut session = Session::new(mode, AuthInMemoryStore::new(), config_paths);
+    let price_history = Arc::new(CsvPriceHistory::new(config_paths.history_dir.to_owned()));
+    let mut session = Session::new(mode, store, config_paths);
 
     // SESSION REQUESTS ---
-    if let Some(cached_creds) = session.get_creds_from_cache() {
-        session.hydrate_local_store(client_config.clone());
-
-        if session.expired_access_token(&cached_creds) {
-            // get consumer and access tokens if no access creds or expired at midnight
-            session.full_access_flow(client_config.clone()).await?;
-        } else if session.should_renew_access_token() {
-            session.renew_access_token(client_config.clone(), cached_creds).await?;
-        }
-    } else {
-        // get consumer and access tokens if no saved data
-        session.full_access_flow(client_config.clone()).await?;
-    }
+    // get consumer and access tokens if there are no saved ones or they expired at midnight, renew them if they've
+    // been idle
+    session.maintain_token(client_config.clone()).await?;
 
     // Now we know we have cached creds
-    session.hydrate_local_store(client_config.clone());
+    // stdin is off limits once the UI has the terminal
+    session.verifier = Verifier::Dialog;
     // END SESSION REQUEST ---
 
     let (sync_io_tx, sync_io_rx) = std::sync::mpsc::channel::<IoEvent>();
@@ -100,34 +118,48 @@ async fn run(mode: Mode) -> Result<(), RuntimeError> {
     // network APIs interface
     let etrade = Etrade::new(client_config.clone());
 
-    let etrade_token_expiry = utils::midnight_eastern(1);
-
     // Initialise app state
     let app = Arc::new(Mutex::new(App::new(
         sync_io_tx,
         user_config.clone(),
-        etrade_token_expiry,
     )));
 
     let cloned_app = Arc::clone(&app);
 
-    std::thread::spawn(move || {
-        let mut network = Network::new(etrade, session, client_config, &app);
+    let network_thread = std::thread::spawn(move || {
+        let mut network = Network::new(etrade, session, client_config, price_history, &app);
         start_tokio(sync_io_rx, &mut network);
     });
 
-    start_ui(&cloned_app).await?;
+    let result = start_ui(&cloned_app).await;
 
-    Ok(())
+    // let the network thread write the token cache before the process exits
+    cloned_app.lock().await.dispatch(IoEvent::Shutdown);
+    let _ = network_thread.join();
+
+    result
 }
 
+const QUOTE_REFRESH_POLL: Duration = Duration::from_millis(500);
+
 #[tokio::main]
 async fn start_tokio<T>(rx: Receiver<IoEvent>, network: &mut Network<T>)
 where T: Store
 {
     // the sender from App will respond to user key events and receive here...
-    while let Ok(event) = rx.recv() {
-        network.handle_network_event(event).await;
+    // waking up in between to keep quotes fresh and the access token alive
+    loop {
+        match rx.recv_timeout(QUOTE_REFRESH_POLL) {
+            Ok(IoEvent::Shutdown) => {
+                network.handle_network_event(IoEvent::Shutdown).await;
+                break;
+            }
+            Ok(event) => network.handle_network_event(event).await,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        network.refresh_quotes_if_due().await;
+        network.maintain_token().await;
     }
 }
 
@@ -160,15 +192,6 @@ async fn start_ui(app: &Arc<Mutex<App>>) -> Result<(), RuntimeError> {
 
         terminal.draw(|mut f| ui::draw_main(&mut f, &app))?;
 
-        let mut midnight = utils::midnight_eastern(1);
-        // Handle authentication refresh
-        if midnight < app.etrade_token_expiry {
-            // reset to tomorrow
-            midnight = utils::midnight_eastern(1);
-            app.etrade_token_expiry = midnight;
-            app.dispatch(IoEvent::RefreshAuthentication);
-        }
-
         match events.next()? {
             Event::Input(key) => {
                 if key == Key::Ctrl('c') {
@@ -188,6 +211,7 @@ async fn start_ui(app: &Arc<Mutex<App>>) -> Result<(), RuntimeError> {
         if is_first_render {
           app.dispatch(IoEvent::GetAccountsList);
           app.dispatch(IoEvent::GetPortfolio);
+          app.dispatch(IoEvent::GetCurrentSavedTickers);
           is_first_render = false;
         }
     }
@@ -1,41 +1,81 @@
-use crate::app::{ActiveBlock, App, RouteId, SearchResult, Ticker};
+use crate::app::{merge_positions, ActiveBlock, App, OptionChain, PortfolioPosition, PositionLots, PriceChart, RouteId, TaxLot, SearchResult, Ticker};
 use crate::clients::etrade::{Etrade};
+use crate::clients::etrade_json_structs::DetailFlag;
 use crate::config::ClientConfig;
+use crate::price_history::{ChartRange, PriceHistory};
+use crate::realized_gains::{self, Fill};
 use crate::session::Session;
 use crate::store::Store;
+use crate::transactions::{self, TransactionKind};
+use crate::utils;
+use crate::watch_lists::WatchLists;
 use anyhow::anyhow;
+use chrono::{Duration, NaiveDate, Utc};
+use log::debug;
+use secstr::SecUtf8;
 use std::{
     sync::Arc,
+    time::Instant,
 };
 use tokio::sync::Mutex;
 
+// strikes either side of the money to request for an option chain
+const OPTION_CHAIN_STRIKES: u32 = 20;
+
 #[derive(Debug)]
 pub enum IoEvent {
     RefreshAuthentication,
+    CompleteAuthorization(String),
     GetSearchResults(String),
-    GetDowJones,
-    GetNasdaq,
-    GetSandP,
     GetPortfolio,
+    GetPositionLots,
     GetAccountsList,
     GetAccountBalance,
     GetTicker(String),
+    GetTickerDetail(DetailFlag),
+    GetPriceHistory(ChartRange),
+    GetAnalysis,
     GetNotifications,
     GetNotification(String),
     SubmitPreviewRequest,
-    GetCurrentSavedTickers(Option<u32>),
+    SubmitPlaceOrderRequest,
+    GetOrders,
+    CancelOrder(i64),
+    GetTransactions,
+    GetTransactionDetails(i64),
+    ExportTransactions,
+    GetRealizedGains,
+    ExportRealizedGains,
+    GetOptionChain(String),
+    RefreshOptionChain,
+    GetCurrentSavedTickers,
     CurrentUserSavedTickerDelete(String),
     CurrentUserSavedTickerAdd(String),
+    CreateWatchList(String),
+    DeleteWatchList(String),
+    SaveWatchLists,
+    // the UI has quit, start_tokio stops on it
+    Shutdown,
+}
+
+// What get_transactions is fetching: the account, the range, the page and the filter
+type TransactionsQuery = (String, Option<NaiveDate>, Option<NaiveDate>, Option<String>, Option<TransactionKind>);
+
+fn transactions_query(app: &App) -> Option<TransactionsQuery> {
+    let account_id_key = app.active_account()?.account_id_key.to_owned();
+    let view = &app.transactions;
+    Some((account_id_key, view.start_date, view.end_date, view.marker.clone(), view.kind))
 }
 
 #[derive(Clone)]
 pub struct Network<'a, T> {
     pub etrade: Etrade,
     pub session: Session<T>,
-    large_search_limit: u32,
     small_search_limit: u32,
     pub client_config: ClientConfig,
     pub app: &'a Arc<Mutex<App>>,
+    last_quote_refresh: Option<Instant>,
+    price_history: Arc<dyn PriceHistory>,
 }
 
 impl<'a, T> Network<'a, T>
@@ -44,15 +84,17 @@ where T: Store {
         etrade: Etrade,
         session: Session<T>,
         client_config: ClientConfig,
+        price_history: Arc<dyn PriceHistory>,
         app: &'a Arc<Mutex<App>>,
     ) -> Self {
         Network {
             etrade,
             session,
-            large_search_limit: 20,
             small_search_limit: 4,
             client_config,
             app,
+            last_quote_refresh: None,
+            price_history,
         }
     }
 
@@ -61,36 +103,72 @@ where T: Store {
             IoEvent::RefreshAuthentication => {
                 self.refresh_authentication().await;
             }
+            IoEvent::CompleteAuthorization(verification_code) => {
+                self.complete_authorization(verification_code).await;
+            }
             IoEvent::GetAccountsList => {
                 self.get_accounts_list().await;
             }
             IoEvent::GetAccountBalance => {
                 self.get_accounts_balance().await;
             }
-            IoEvent::GetDowJones => {
-                self.get_ticker("dji".to_string()).await;
-            }
-            IoEvent::GetSandP => {
-                self.get_ticker("gspc".to_string()).await;
-            }
-            IoEvent::GetNasdaq => {
-                self.get_ticker("ndaq".to_string()).await;
-            }
             IoEvent::GetPortfolio => {
                 self.get_portfolio().await;
             }
+            IoEvent::GetPositionLots => {
+                self.get_position_lots().await;
+            }
             IoEvent::GetSearchResults(search_term) => {
                 self.get_search_results(search_term).await;
             }
             IoEvent::SubmitPreviewRequest => {
                 self.preview_order_request().await;
             }
-            IoEvent::GetCurrentSavedTickers(offset) => {
-                self.get_current_user_saved_tickers(offset).await;
+            IoEvent::SubmitPlaceOrderRequest => {
+                self.place_order_request().await;
+            }
+            IoEvent::GetOrders => {
+                self.get_orders().await;
+            }
+            IoEvent::CancelOrder(order_id) => {
+                self.cancel_order(order_id).await;
+            }
+            IoEvent::GetTransactions => {
+                self.get_transactions().await;
+            }
+            IoEvent::GetTransactionDetails(transaction_id) => {
+                self.get_transaction_details(transaction_id).await;
+            }
+            IoEvent::ExportTransactions => {
+                self.export_transactions().await;
+            }
+            IoEvent::GetRealizedGains => {
+                self.get_realized_gains().await;
+            }
+            IoEvent::ExportRealizedGains => {
+                self.export_realized_gains().await;
+            }
+            IoEvent::GetOptionChain(symbol) => {
+                self.get_option_chain(symbol).await;
+            }
+            IoEvent::RefreshOptionChain => {
+                self.refresh_option_chain().await;
+            }
+            IoEvent::GetCurrentSavedTickers => {
+                self.get_current_user_saved_tickers().await;
             }
             IoEvent::GetTicker(ticker_id) => {
                 self.get_ticker(ticker_id).await;
             }
+            IoEvent::GetTickerDetail(detail_flag) => {
+                self.get_ticker_detail(detail_flag).await;
+            }
+            IoEvent::GetPriceHistory(range) => {
+                self.get_price_history(range).await;
+            }
+            IoEvent::GetAnalysis => {
+                self.get_analysis().await;
+            }
             IoEvent::GetNotifications => {
                 self.get_notifications().await;
             }
@@ -103,7 +181,20 @@ where T: Store {
             IoEvent::CurrentUserSavedTickerAdd(show_id) => {
                 self.current_user_saved_ticker_add(show_id).await;
             }
+            IoEvent::CreateWatchList(name) => {
+                self.update_watch_lists(|watch_lists| watch_lists.create(&name)).await;
+            }
+            IoEvent::DeleteWatchList(name) => {
+                self.update_watch_lists(|watch_lists| watch_lists.delete(&name)).await;
+            }
+            IoEvent::SaveWatchLists => {
+                self.update_watch_lists(|_| Ok(())).await;
+            }
+            IoEvent::Shutdown => {
+                self.shutdown();
+            }
         };
+        self.show_pending_authorization().await;
 
         let mut app = self.app.lock().await;
         app.is_loading = false;
@@ -115,15 +206,10 @@ where T: Store {
     }
 
     async fn get_accounts_list(&mut self) {
-        match self.etrade.accounts_list(&self.session).await {
+        match self.etrade.accounts_list(&mut self.session).await {
             Ok(user_accounts) => {
                 let mut app = self.app.lock().await;
-
-                let account_len = user_accounts.accounts.accounts.len();
-                app.user_accounts = Some(user_accounts.accounts.accounts);
-                if account_len > 0 {
-                    app.active_account_index = Some(0);
-                }
+                app.set_user_accounts(user_accounts.accounts.accounts);
 
                 app.dispatch(IoEvent::GetAccountBalance);
             }
@@ -137,7 +223,7 @@ where T: Store {
         let mut app = self.app.lock().await;
         if let Some(ref mut accounts) = app.user_accounts {
             for item in accounts.iter_mut() {
-                match self.etrade.account_balance(&item.account_id_key, &self.session).await {
+                match self.etrade.account_balance(&item.account_id_key, &mut self.session).await {
                     Ok(account_balance) => {
                         item.account_balance = Some(account_balance);
                     }
@@ -150,7 +236,7 @@ where T: Store {
     }
 
     async fn get_search_results(&mut self, search_term: String) {
-        let searched_tickers = self.etrade.search(&self.session, &search_term).await;
+        let searched_tickers = self.etrade.search(&mut self.session, &search_term).await;
 
         match searched_tickers {
             Ok(tickers) => {
@@ -169,60 +255,227 @@ where T: Store {
         }
     }
 
-    async fn get_current_user_saved_tickers(&mut self, offset: Option<u32>) {
-        match self.etrade.current_user_saved_tickers(self.large_search_limit, offset).await {
-            Ok(saved_tickers) => {
+    async fn get_current_user_saved_tickers(&mut self) {
+        match WatchLists::load(&self.session.config_paths.watch_lists_path) {
+            Ok(watch_lists) => {
                 let mut app = self.app.lock().await;
+                app.library.watch_lists = watch_lists;
+                app.library.selected_index = 0;
+            }
+            Err(e) => self.handle_error(anyhow!(e)).await,
+        }
+    }
+
+    pub async fn current_user_saved_ticker_delete(&mut self, ticker_id: String) {
+        self.update_watch_lists(|watch_lists| watch_lists.remove(&ticker_id)).await;
+
+        let mut app = self.app.lock().await;
+        let len = app.library.watch_lists.active_symbols().len();
+        if app.library.selected_index >= len {
+            app.library.selected_index = len.saturating_sub(1);
+        }
+    }
+
+    async fn current_user_saved_ticker_add(&mut self, ticker_id: String) {
+        self.update_watch_lists(|watch_lists| watch_lists.add(&ticker_id)).await;
+    }
+
+    // Applies the change to the watch lists held by the app and writes them back to disk
+    async fn update_watch_lists<F>(&mut self, update: F)
+    where F: FnOnce(&mut WatchLists) -> Result<(), String>
+    {
+        let mut app = self.app.lock().await;
+        if let Err(message) = update(&mut app.library.watch_lists) {
+            app.handle_error(anyhow!(message));
+            return;
+        }
 
-                saved_tickers.iter().for_each(|item| {
-                    app.liked_ticker_ids_set.insert(item.symbol.to_string());
-                });
+        if let Err(e) = app.library.watch_lists.save(&self.session.config_paths.watch_lists_path) {
+            app.handle_error(anyhow!(e));
+        }
+    }
+
+    // Polled from the network thread between events, batch refreshes every visible quote
+    pub async fn refresh_quotes_if_due(&mut self) {
+        let (symbols, interval) = {
+            let app = self.app.lock().await;
+            let interval = utils::quote_refresh_interval(
+                utils::market_hours(),
+                app.user_config.quote_refresh_interval,
+                app.user_config.closed_quote_refresh_interval,
+            );
+            (app.visible_symbols(), interval)
+        };
+
+        let interval = match interval {
+            Some(interval) => interval,
+            None => return,
+        };
+        if symbols.is_empty() || self.last_quote_refresh.is_some_and(|last| last.elapsed() < interval) {
+            return;
+        }
+        self.last_quote_refresh = Some(Instant::now());
 
-                app.library.saved_tickers = saved_tickers;
+        match self.etrade.quotes(&mut self.session, &symbols).await {
+            Ok(quotes) => {
+                let mut app = self.app.lock().await;
+                app.update_quotes(quotes.into_iter().map(|q| q.into()).collect::<Vec<Ticker>>(), Utc::now());
             }
+            // a missed refresh shouldn't take over the screen, the next poll tries again
             Err(e) => {
-                self.handle_error(anyhow!(e)).await;
+                debug!("Quote refresh failed: {}", e);
+                self.show_pending_authorization().await;
             }
         }
     }
 
-    pub async fn current_user_saved_ticker_delete(&mut self, ticker_id: String) {
-        match self
-            .etrade
-            .current_user_saved_tickers_delete(&[ticker_id.to_owned()])
-            .await
-            {
-                Ok(_) => {
-                    self.get_current_user_saved_tickers(None).await;
-                    let mut app = self.app.lock().await;
-                    app.saved_ticker_ids_set.remove(&ticker_id.to_owned());
-                }
-                Err(e) => {
-                    self.handle_error(anyhow!(e)).await;
-                }
-            };
+    // Renews the access token before it goes idle, and brings up the authorization screen once it expires
+    // at midnight. A failure waits for the next request or attempt rather than taking over the screen
+    // Writes what's only in memory before the process exits
+    pub fn shutdown(&mut self) {
+        if let Err(e) = self.session.store.flush() {
+            debug!("Writing the token cache failed: {}", e);
+        }
     }
 
-    async fn current_user_saved_ticker_add(&mut self, ticker_id: String) {
-        match self
-            .etrade
-            .current_user_saved_tickers_add(&[ticker_id.to_owned()]).await
-            {
-                Ok(_) => {
-                    let mut app = self.app.lock().await;
-                    app.saved_ticker_ids_set.insert(ticker_id.to_owned());
-                }
-                Err(e) => self.handle_error(anyhow!(e)).await,
-            }
+    pub async fn maintain_token(&mut self) {
+        if let Err(e) = self.session.maintain_token(self.client_config.clone()).await {
+            debug!("Keeping the access token alive failed: {}", e);
+            self.show_pending_authorization().await;
+        }
     }
 
     async fn get_ticker(&mut self, ticker_id: String) {
-        match self.etrade.ticker(&self.session, &ticker_id).await {
+        match self.etrade.ticker(&mut self.session, &ticker_id, DetailFlag::ALL).await {
             Ok(ticker) => {
                 let mut app = self.app.lock().await;
 
                 app.selected_ticker = Some(ticker.into());
                 app.push_navigation_stack(RouteId::TickerDetail, ActiveBlock::TickerDetail);
+                let range = app.chart_range;
+                drop(app);
+
+                self.get_price_history(range).await;
+            }
+            Err(e) => {
+                self.handle_error(anyhow!(e)).await;
+            }
+        }
+    }
+
+    async fn get_price_history(&mut self, range: ChartRange) {
+        let mut app = self.app.lock().await;
+        let symbol = match &app.selected_ticker {
+            Some(selected_ticker) => selected_ticker.ticker.symbol.to_owned(),
+            None => return,
+        };
+
+        app.chart_range = range;
+        app.price_chart = Some(self.load_price_chart(symbol, range));
+    }
+
+    // A year of daily bars for the selected ticker's indicators
+    async fn get_analysis(&mut self) {
+        let mut app = self.app.lock().await;
+        let symbol = match &app.selected_ticker {
+            Some(selected_ticker) => selected_ticker.ticker.symbol.to_owned(),
+            None => return,
+        };
+
+        app.analysis = Some(self.load_price_chart(symbol, ChartRange::OneYear));
+        app.push_navigation_stack(RouteId::Analysis, ActiveBlock::Analysis);
+    }
+
+    // a missing history shows in the chart panel instead of taking over the screen
+    fn load_price_chart(&self, symbol: String, range: ChartRange) -> PriceChart {
+        match self.price_history.bars(&symbol, range) {
+            Ok(bars) if bars.is_empty() => PriceChart {
+                message: Some(format!("No {} price history for {}", range.label(), symbol)),
+                ..PriceChart::new(symbol, range)
+            },
+            Ok(bars) => PriceChart {
+                bars,
+                ..PriceChart::new(symbol, range)
+            },
+            Err(e) => PriceChart {
+                message: Some(e.message),
+                ..PriceChart::new(symbol, range)
+            },
+        }
+    }
+
+    // Reload the selected ticker with another detail block, staying on the ticker detail screen
+    async fn get_ticker_detail(&mut self, detail_flag: DetailFlag) {
+        let mut app = self.app.lock().await;
+        let symbol = match &app.selected_ticker {
+            Some(selected_ticker) => selected_ticker.ticker.symbol.to_owned(),
+            None => return,
+        };
+
+        match self.etrade.ticker(&mut self.session, &symbol, detail_flag).await {
+            Ok(ticker) => {
+                if let Some(ref mut selected_ticker) = app.selected_ticker {
+                    selected_ticker.ticker.update_detail(&ticker.quote_data.into());
+                    selected_ticker.detail_flag = detail_flag;
+                }
+            }
+            Err(e) => app.handle_error(anyhow!(e)),
+        }
+    }
+
+    async fn get_option_chain(&mut self, symbol: String) {
+        let expiration_dates = match self.etrade.option_expire_dates(&mut self.session, &symbol).await {
+            Ok(expiration_dates) => expiration_dates,
+            Err(e) => {
+                self.handle_error(anyhow!(e)).await;
+                return;
+            }
+        };
+
+        let mut option_chain = OptionChain::new(symbol, expiration_dates);
+        let expiration_date = match option_chain.selected_expiry() {
+            Some(expiration_date) => expiration_date.clone(),
+            None => {
+                self.handle_error(anyhow!("No options listed for {}", option_chain.symbol)).await;
+                return;
+            }
+        };
+
+        match self.etrade.option_chains(&mut self.session, &option_chain.symbol, &expiration_date, OPTION_CHAIN_STRIKES).await {
+            Ok(option_chain_response) => {
+                let mut app = self.app.lock().await;
+
+                option_chain.update(option_chain_response);
+                app.set_option_chain(option_chain);
+                app.push_navigation_stack(RouteId::OptionChain, ActiveBlock::OptionChain);
+            }
+            Err(e) => {
+                self.handle_error(anyhow!(e)).await;
+            }
+        }
+    }
+
+    // Fetch the chain again for the currently selected expiration
+    async fn refresh_option_chain(&mut self) {
+        let (symbol, expiration_date) = {
+            let app = self.app.lock().await;
+            match app.selected_option_chain {
+                Some(ref selected) => match selected.option_chain.selected_expiry() {
+                    Some(expiration_date) => (selected.option_chain.symbol.to_owned(), expiration_date.clone()),
+                    None => return,
+                },
+                None => return,
+            }
+        };
+
+        match self.etrade.option_chains(&mut self.session, &symbol, &expiration_date, OPTION_CHAIN_STRIKES).await {
+            Ok(option_chain_response) => {
+                let mut app = self.app.lock().await;
+
+                if let Some(ref mut selected) = app.selected_option_chain {
+                    selected.option_chain.update(option_chain_response);
+                    selected.selected_index = selected.option_chain.near_the_money_index();
+                }
             }
             Err(e) => {
                 self.handle_error(anyhow!(e)).await;
@@ -231,7 +484,7 @@ where T: Store {
     }
 
     async fn get_notifications(&mut self) {
-        match self.etrade.alerts(&self.session).await {
+        match self.etrade.alerts(&mut self.session).await {
             Ok(alerts) => {
                 let mut app = self.app.lock().await;
 
@@ -246,7 +499,7 @@ where T: Store {
     }
 
     async fn get_notification(&mut self, notification_id: String) {
-        match self.etrade.alert(&notification_id, &self.session).await {
+        match self.etrade.alert(&notification_id, &mut self.session).await {
             Ok(alert) => {
                 let mut app = self.app.lock().await;
 
@@ -261,43 +514,316 @@ where T: Store {
 
     async fn get_portfolio(&mut self) {
         let mut app = self.app.lock().await;
-        if let Some(accounts) = &app.user_accounts {
-            match self.etrade.portfolio(&accounts.first().unwrap().account_id_key, &self.session).await {
-                Ok(portfolio) => {
-                    app.portfolio_tickers = Some(portfolio.account_portfolio.positions.into_iter().map(|t| t.into()).collect::<Vec<Ticker>>());
+        let account_id_keys = app.portfolio_account_keys();
+        if account_id_keys.is_empty() {
+            return;
+        }
+
+        let mut positions = vec![];
+        for account_id_key in account_id_keys.iter() {
+            match self.etrade.portfolio(account_id_key, &mut self.session).await {
+                Ok(account_positions) => {
+                    positions.extend(account_positions.into_iter().map(|position| PortfolioPosition {
+                        account_id_key: account_id_key.to_owned(),
+                        ..position.into()
+                    }));
                 }
                 Err(e) => {
-                    self.handle_error(anyhow!(e)).await;
+                    app.handle_error(anyhow!(e));
+                    return;
                 }
             }
         }
+
+        if account_id_keys.len() > 1 {
+            positions = merge_positions(positions);
+        }
+        app.set_positions(positions);
+    }
+
+    // Tax lots of the position selected in the positions table
+    async fn get_position_lots(&mut self) {
+        let mut app = self.app.lock().await;
+        let (symbol, lots_url, account_id_key) = match app.selected_position() {
+            Some(position) => (position.ticker.symbol.to_owned(), position.lots_details.to_owned(), position.account_id_key.to_owned()),
+            None => return,
+        };
+        if lots_url.is_empty() && app.all_accounts {
+            app.handle_error(anyhow!("{} is held in more than one account, pick one to see its tax lots", symbol));
+            return;
+        }
+        if lots_url.is_empty() {
+            app.handle_error(anyhow!("E*TRADE sent no tax lots for {}", symbol));
+            return;
+        }
+
+        match self.etrade.position_lots(&lots_url, &mut self.session).await {
+            Ok(lots) => {
+                let today = utils::today_eastern();
+                let lots = lots.into_iter().map(|lot| TaxLot::new(lot, today)).collect::<Vec<TaxLot>>();
+                app.position_lots = Some(PositionLots::new(symbol, account_id_key, lots));
+                app.push_navigation_stack(RouteId::TaxLots, ActiveBlock::TaxLots);
+            }
+            Err(e) => {
+                app.handle_error(anyhow!(e));
+            }
+        }
     }
 
+    // against the account the order form was started for
     async fn preview_order_request(&mut self) {
+        let mut app = self.app.lock().await;
+        if let Some(preview_order_form) = &app.preview_order_form {
+            let account_id_key = &preview_order_form.account_id_key;
+            let preview_order_response = match preview_order_form.order_id {
+                Some(order_id) => self.etrade.change_preview_order_request(account_id_key, order_id, &mut self.session, preview_order_form.clone().into()).await,
+                None => self.etrade.preview_order_request(account_id_key, &mut self.session, preview_order_form.clone().into()).await,
+            };
+            match preview_order_response {
+                Ok(preview_order_response) => {
+                    app.preview_order_response = Some(preview_order_response);
+                    app.place_order_response = None;
+                    app.push_navigation_stack(RouteId::ConfirmOrderForm, ActiveBlock::ConfirmOrderForm);
+                }
+                Err(e) => {
+                    app.handle_error(anyhow!(e));
+                }
+            }
+        }
+    }
+
+    async fn place_order_request(&mut self) {
+        let mut app = self.app.lock().await;
+        if let (Some(preview_order_form), Some(preview_order_response)) = (&app.preview_order_form, &app.preview_order_response) {
+            let account_id_key = &preview_order_form.account_id_key;
+            let place_order_request = preview_order_form.place_order_request(preview_order_response.preview_ids.clone());
+            let place_order_response = match preview_order_form.order_id {
+                Some(order_id) => self.etrade.change_place_order_request(account_id_key, order_id, &mut self.session, place_order_request).await,
+                None => self.etrade.place_order_request(account_id_key, &mut self.session, place_order_request).await,
+            };
+            // Enter is ignored until the response is in, so the order only goes out once
+            match place_order_response {
+                Ok(place_order_response) => {
+                    app.placing_order = false;
+                    app.place_order_response = Some(place_order_response);
+                }
+                Err(e) => {
+                    app.placing_order = false;
+                    app.handle_error(anyhow!(e));
+                }
+            }
+        } else {
+            app.placing_order = false;
+        }
+    }
+
+    async fn get_orders(&mut self) {
         let mut app = self.app.lock().await;
         if let Some(active_account_index) = app.active_account_index {
             let account_id_key = &app.user_accounts.as_ref().unwrap()[active_account_index].account_id_key;
-            if let Some(preview_order_form) = &app.preview_order_form {
-                match self.etrade.preview_order_request(account_id_key, &self.session, preview_order_form.clone().into()).await {
-                    Ok(preview_order_response) => {
-                        app.preview_order_form = Some(preview_order_response.into());
-                        app.push_navigation_stack(RouteId::ConfirmOrderForm, ActiveBlock::ConfirmOrderForm);
+            let status = app.orders_status_filter;
+            let symbol = app.orders_symbol_filter.clone();
+            match self.etrade.orders(account_id_key, &mut self.session, status, symbol).await {
+                Ok(orders_response) => {
+                    if app.selected_order_index >= orders_response.orders.len() {
+                        app.selected_order_index = 0;
+                    }
+                    app.orders = Some(orders_response.orders);
+                }
+                Err(e) => {
+                    app.handle_error(anyhow!(e));
+                }
+            }
+        }
+    }
+
+    // The page of transactions app.transactions.marker points at, and the ones after it a type filter needs. The
+    // app is only locked between pages, so the UI keeps drawing
+    async fn get_transactions(&mut self) {
+        let query = match transactions_query(&*self.app.lock().await) {
+            Some(query) => query,
+            None => return,
+        };
+        let (account_id_key, start_date, end_date, mut marker, _) = query.clone();
+
+        let mut first_page = true;
+        loop {
+            let response = self.etrade.transactions(&account_id_key, &mut self.session, start_date, end_date, marker).await;
+            let mut app = self.app.lock().await;
+            // the account or filter changed while the page was on its way, the change fetches its own
+            if transactions_query(&app).as_ref() != Some(&query) {
+                return;
+            }
+            match response {
+                Ok(response) if first_page => app.transactions.set_page(response),
+                Ok(response) => app.transactions.extend_page(response),
+                Err(e) => {
+                    app.handle_error(anyhow!(e));
+                    return;
+                }
+            }
+            if !app.transactions.needs_more() {
+                return;
+            }
+            marker = app.transactions.next_marker.clone();
+            first_page = false;
+        }
+    }
+
+    async fn get_transaction_details(&mut self, transaction_id: i64) {
+        let mut app = self.app.lock().await;
+        let account_id_key = match app.active_account() {
+            Some(account) => account.account_id_key.to_owned(),
+            None => return,
+        };
+
+        match self.etrade.transaction_details(&account_id_key, &mut self.session, transaction_id).await {
+            Ok(details) => {
+                app.transactions.details = Some(details);
+            }
+            Err(e) => {
+                app.handle_error(anyhow!(e));
+            }
+        }
+    }
+
+    async fn export_transactions(&mut self) {
+        let mut app = self.app.lock().await;
+        let now = chrono::Local::now().naive_local();
+        let exported = {
+            let visible = app.transactions.visible();
+            transactions::export(&visible, &self.session.config_paths.exports_dir, now).map(|path| (visible.len(), path))
+        };
+
+        match exported {
+            Ok((count, path)) => {
+                app.transactions.message = Some(format!("Exported {} transactions to {}", count, path.display()));
+            }
+            Err(e) => {
+                app.handle_error(anyhow!(e));
+            }
+        }
+    }
+
+    // Every buy and sell in the range, matched locally so changing the method needs no refetch
+    async fn get_realized_gains(&mut self) {
+        let mut app = self.app.lock().await;
+        let account_id_key = match app.active_account() {
+            Some(account) => account.account_id_key.to_owned(),
+            None => return,
+        };
+        // E*TRADE keeps two years of transactions
+        let end_date = *app.realized_gains.end_date.get_or_insert_with(utils::today_eastern);
+        let start_date = *app.realized_gains.start_date.get_or_insert(end_date - Duration::days(730));
+
+        let selections = match realized_gains::load_lot_selections(&self.session.config_paths.lot_selections_path) {
+            Ok(selections) => selections,
+            Err(e) => {
+                app.handle_error(anyhow!(e));
+                return;
+            }
+        };
+        match self.etrade.all_transactions(&account_id_key, &mut self.session, Some(start_date), Some(end_date)).await {
+            Ok(transactions) => {
+                let (mut fills, mut skipped) = (vec![], vec![]);
+                for transaction in &transactions {
+                    match Fill::from_transaction(transaction) {
+                        Ok(Some(fill)) => fills.push(fill),
+                        Ok(None) => {}
+                        Err(message) => skipped.push(message),
+                    }
+                }
+                app.realized_gains.set_fills(fills, selections, skipped);
+            }
+            Err(e) => {
+                app.handle_error(anyhow!(e));
+            }
+        }
+    }
+
+    async fn export_realized_gains(&mut self) {
+        let mut app = self.app.lock().await;
+        let now = chrono::Local::now().naive_local();
+        let gains = &app.realized_gains.report.gains;
+        let exported = realized_gains::export(gains, &self.session.config_paths.exports_dir, now).map(|path| (gains.len(), path));
+
+        match exported {
+            Ok((count, path)) => {
+                app.realized_gains.message = Some(format!("Exported {} lots to {}", count, path.display()));
+            }
+            Err(e) => {
+                app.handle_error(anyhow!(e));
+            }
+        }
+    }
+
+    async fn cancel_order(&mut self, order_id: i64) {
+        {
+            let mut app = self.app.lock().await;
+            if let Some(active_account_index) = app.active_account_index {
+                let account_id_key = &app.user_accounts.as_ref().unwrap()[active_account_index].account_id_key;
+                match self.etrade.cancel_order(account_id_key, &mut self.session, order_id).await {
+                    Ok(cancel_order_response) => {
+                        let message = cancel_order_response.messages.messages
+                            .first()
+                            .map(|m| m.description.to_owned())
+                            .unwrap_or_else(|| "Cancel requested".to_string());
+                        app.orders_message = Some(format!("#{}: {}", cancel_order_response.order_id, message));
                     }
                     Err(e) => {
-                        app.push_navigation_stack(RouteId::Error, ActiveBlock::Error);
+                        app.handle_error(anyhow!(e));
+                        return;
                     }
                 }
             }
         }
+
+        self.get_orders().await;
     }
 
+    // Starts the OAuth flow over, the link to authorize with shows up once this returns
     async fn refresh_authentication(&mut self) {
-        let mut client_config = ClientConfig::new();
-        // ask user for configuration details
-        if let Ok(_) = client_config.load_config(&self.session.mode) {
-            let _ = self.session.full_access_flow(client_config).await;
+        // a new link replaces one that was never used
+        self.session.pending_authorization = None;
+        if let Err(e) = self.session.full_access_flow(self.client_config.clone()).await {
+            if self.session.pending_authorization.is_none() {
+                let mut app = self.app.lock().await;
+                app.handle_error(anyhow!(e));
+            }
+        }
+    }
+
+    async fn complete_authorization(&mut self, verification_code: String) {
+        let pending = match self.session.pending_authorization.clone() {
+            Some(pending) => pending,
+            None => return,
+        };
+
+        let result = self.session.finish_authorization(self.client_config.clone(), pending, SecUtf8::from(verification_code)).await;
+        let mut app = self.app.lock().await;
+        match result {
+            Ok(_) => {
+                app.close_authorization();
+                app.dispatch(IoEvent::GetAccountsList);
+                app.dispatch(IoEvent::GetPortfolio);
+            }
+            // the request token is good for 5 minutes, so a mistyped code can be entered again
+            Err(e) => {
+                if let Some(authorization) = app.authorization.as_mut() {
+                    authorization.message = Some(format!("{}, check the code or press r for a new link", e));
+                }
+            }
         }
+    }
 
+    // Any request can find out the token is gone, whichever did hands the link to the UI here
+    async fn show_pending_authorization(&mut self) {
+        if let Some(pending) = &self.session.pending_authorization {
+            let mut app = self.app.lock().await;
+            if app.authorization.as_ref().is_none_or(|authorization| authorization.authorize_url != pending.authorize_url) {
+                app.open_authorization(pending.authorize_url.to_owned());
+            }
+        }
     }
 }
 
@@ -0,0 +1,253 @@
+use crate::stonks_error::RuntimeError;
+use chrono::prelude::*;
+use chrono::Duration;
+use std::{fs, path::PathBuf};
+
+// One OHLCV bar, in the exchange's local time
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bar {
+    pub time: NaiveDateTime,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChartRange {
+    OneDay,
+    FiveDays,
+    OneMonth,
+    SixMonths,
+    OneYear,
+}
+
+impl ChartRange {
+    pub const ALL: [ChartRange; 5] = [
+        ChartRange::OneDay,
+        ChartRange::FiveDays,
+        ChartRange::OneMonth,
+        ChartRange::SixMonths,
+        ChartRange::OneYear,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChartRange::OneDay => "1D",
+            ChartRange::FiveDays => "5D",
+            ChartRange::OneMonth => "1M",
+            ChartRange::SixMonths => "6M",
+            ChartRange::OneYear => "1Y",
+        }
+    }
+
+    pub fn is_intraday(&self) -> bool {
+        matches!(self, ChartRange::OneDay | ChartRange::FiveDays)
+    }
+
+    // Bars (sorted oldest first) that fall in the range, counted back from the latest bar rather
+    // than from now so offline data still charts
+    pub fn filter(&self, bars: &[Bar]) -> Vec<Bar> {
+        let latest = match bars.last() {
+            Some(bar) => bar.time,
+            None => return vec![],
+        };
+
+        let start = match self {
+            ChartRange::OneDay | ChartRange::FiveDays => {
+                let days = if *self == ChartRange::OneDay { 1 } else { 5 };
+                let mut dates = bars.iter().map(|bar| bar.time.date()).collect::<Vec<NaiveDate>>();
+                dates.dedup();
+                match dates[dates.len().saturating_sub(days)].and_hms_opt(0, 0, 0) {
+                    Some(start) => start,
+                    None => return vec![],
+                }
+            }
+            ChartRange::OneMonth => latest - Duration::days(30),
+            ChartRange::SixMonths => latest - Duration::days(182),
+            ChartRange::OneYear => latest - Duration::days(365),
+        };
+
+        bars.iter().filter(|bar| bar.time >= start).cloned().collect()
+    }
+}
+
+// Where chart data comes from. E*TRADE's API has no price history, so anything that can produce
+// bars (a CSV export, another market data API) can be plugged in here.
+pub trait PriceHistory: Send + Sync {
+    fn bars(&self, symbol: &str, range: ChartRange) -> Result<Vec<Bar>, RuntimeError>;
+}
+
+// Reads <SYMBOL>.csv daily bars from a directory, and <SYMBOL>-intraday.csv for the 1D and 5D
+// ranges when there is one. Columns are matched by header name, so Yahoo Finance exports work as is.
+pub struct CsvPriceHistory {
+    dir: PathBuf,
+}
+
+impl CsvPriceHistory {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+impl PriceHistory for CsvPriceHistory {
+    fn bars(&self, symbol: &str, range: ChartRange) -> Result<Vec<Bar>, RuntimeError> {
+        let symbol = symbol.to_uppercase();
+        let daily = self.dir.join(format!("{}.csv", symbol));
+        let intraday = self.dir.join(format!("{}-intraday.csv", symbol));
+
+        let path = if range.is_intraday() && intraday.exists() { intraday } else { daily };
+        if !path.exists() {
+            return Err(RuntimeError { message: format!("No price history for {}, add {}", symbol, path.display()) });
+        }
+
+        let bars = parse_csv(&fs::read_to_string(&path)?)?;
+        Ok(range.filter(&bars))
+    }
+}
+
+pub fn parse_csv(contents: &str) -> Result<Vec<Bar>, RuntimeError> {
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+    let header = lines
+        .next()
+        .ok_or(RuntimeError { message: "Price history is empty".to_string() })?
+        .split(',')
+        .map(|column| column.trim().to_lowercase())
+        .collect::<Vec<String>>();
+
+    let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+    let (time, open, high, low, close) = match (
+        column(&["date", "datetime", "time", "timestamp"]),
+        column(&["open"]),
+        column(&["high"]),
+        column(&["low"]),
+        column(&["close"]),
+    ) {
+        (Some(time), Some(open), Some(high), Some(low), Some(close)) => (time, open, high, low, close),
+        _ => return Err(RuntimeError { message: "Price history needs date, open, high, low and close columns".to_string() }),
+    };
+    let volume = column(&["volume"]);
+
+    let mut bars = vec![];
+    for line in lines {
+        let fields = line.split(',').map(|field| field.trim()).collect::<Vec<&str>>();
+        let price = |index: usize| fields.get(index).and_then(|field| field.parse::<f64>().ok());
+
+        // Yahoo writes "null" for days without a price, skip them rather than failing the chart
+        if let (Some(time), Some(open), Some(high), Some(low), Some(close)) =
+            (fields.get(time).and_then(|field| parse_time(field)), price(open), price(high), price(low), price(close))
+        {
+            bars.push(Bar {
+                time,
+                open,
+                high,
+                low,
+                close,
+                volume: volume.and_then(price).map_or(0, |volume| volume as u64),
+            });
+        }
+    }
+    bars.sort_by_key(|bar| bar.time);
+
+    Ok(bars)
+}
+
+fn parse_time(field: &str) -> Option<NaiveDateTime> {
+    let formats = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"];
+    formats
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(field, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(field, "%Y-%m-%d").ok().and_then(|date| date.and_hms_opt(0, 0, 0)))
+        .or_else(|| field.parse::<i64>().ok().and_then(|seconds| NaiveDateTime::from_timestamp_opt(seconds, 0)))
+}
+
+// Sums volume into at most `buckets` columns so a histogram fits the width it is drawn in
+pub fn bucket_volumes(bars: &[Bar], buckets: usize) -> Vec<u64> {
+    if buckets == 0 || bars.is_empty() {
+        return vec![];
+    }
+
+    let size = bars.len().div_ceil(buckets);
+    bars.chunks(size).map(|chunk| chunk.iter().map(|bar| bar.volume).sum()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::temp_dir;
+
+    const DAILY: &str = "Date,Open,High,Low,Close,Adj Close,Volume
+2021-01-04,100.0,102.0,99.0,101.0,101.0,1000
+2021-01-05,101.0,103.0,100.0,102.5,102.5,1200
+2021-01-06,null,null,null,null,null,null
+2021-02-05,102.5,104.0,101.5,103.0,103.0,900
+";
+
+    #[test]
+    fn parses_yahoo_export() {
+        let bars = parse_csv(DAILY).unwrap();
+
+        assert_eq!(bars.len(), 3);
+        assert_eq!(Some(bars[0].time), NaiveDate::from_ymd_opt(2021, 1, 4).and_then(|date| date.and_hms_opt(0, 0, 0)));
+        assert_eq!((bars[1].open, bars[1].high, bars[1].low, bars[1].close), (101.0, 103.0, 100.0, 102.5));
+        assert_eq!(bars[2].volume, 900);
+    }
+
+    #[test]
+    fn parses_unix_times() {
+        assert_eq!(parse_time("1612519200"), NaiveDate::from_ymd_opt(2021, 2, 5).and_then(|date| date.and_hms_opt(10, 0, 0)));
+        // out of chrono's range, skipped rather than a panic
+        assert_eq!(parse_time("9223372036854775807"), None);
+    }
+
+    #[test]
+    fn rejects_missing_columns() {
+        assert!(parse_csv("Date,Close\n2021-01-04,101.0\n").is_err());
+        assert!(parse_csv("").is_err());
+    }
+
+    #[test]
+    fn ranges_count_back_from_latest_bar() {
+        let bars = parse_csv(DAILY).unwrap();
+
+        assert_eq!(ChartRange::OneMonth.filter(&bars).len(), 1);
+        assert_eq!(ChartRange::OneYear.filter(&bars).len(), 3);
+        assert_eq!(ChartRange::FiveDays.filter(&bars).len(), 3);
+        assert_eq!(ChartRange::OneDay.filter(&bars).len(), 1);
+        assert_eq!(ChartRange::OneDay.filter(&[]), vec![]);
+
+        let intraday = parse_csv("Datetime,Open,High,Low,Close,Volume
+2021-02-04 15:55:00,1,1,1,1,10
+2021-02-05 09:30:00,1,1,1,1,10
+2021-02-05 09:35:00,1,1,1,1,10
+").unwrap();
+        assert_eq!(ChartRange::OneDay.filter(&intraday).len(), 2);
+        assert_eq!(ChartRange::FiveDays.filter(&intraday).len(), 3);
+    }
+
+    #[test]
+    fn csv_provider_prefers_intraday_file() {
+        let dir = temp_dir("history");
+        fs::write(dir.join("IBM.csv"), DAILY).unwrap();
+        fs::write(dir.join("IBM-intraday.csv"), "Datetime,Open,High,Low,Close,Volume
+2021-02-05 09:30:00,1,1,1,1,10
+").unwrap();
+
+        let history = CsvPriceHistory::new(dir.to_owned());
+        assert_eq!(history.bars("ibm", ChartRange::OneDay).unwrap().len(), 1);
+        assert_eq!(history.bars("IBM", ChartRange::OneYear).unwrap().len(), 3);
+        assert!(history.bars("MSFT", ChartRange::OneYear).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn buckets_volume_to_width() {
+        let bars = parse_csv(DAILY).unwrap();
+
+        assert_eq!(bucket_volumes(&bars, 10), vec![1000, 1200, 900]);
+        assert_eq!(bucket_volumes(&bars, 2), vec![2200, 900]);
+        assert_eq!(bucket_volumes(&bars, 0), Vec::<u64>::new());
+    }
+}
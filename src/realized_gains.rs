@@ -0,0 +1,564 @@
+use crate::clients::etrade_json_structs::Transaction;
+use crate::stonks_error::RuntimeError;
+use crate::transactions;
+use crate::utils::{self, HoldingTerm};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::{fs, path::{Path, PathBuf}};
+
+// shares left over from floating point subtraction
+const EPSILON: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LotMatching {
+    #[default]
+    Fifo,
+    // the lots picked in lot_selections.yml, FIFO for whatever they don't cover
+    SpecificLots,
+}
+
+impl LotMatching {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LotMatching::Fifo => "FIFO",
+            LotMatching::SpecificLots => "Specific lots",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LotSelection {
+    // transaction id of the buy
+    pub buy: i64,
+    pub quantity: f64,
+}
+
+// Sell transaction id to the buys it closed, saved as yaml next to client.yml
+pub type LotSelections = HashMap<i64, Vec<LotSelection>>;
+
+pub fn load_lot_selections(path: &Path) -> Result<LotSelections, RuntimeError> {
+    if !path.exists() {
+        return Ok(LotSelections::new());
+    }
+
+    Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Side {
+    Buy,
+    Sell,
+    // opens a short position, closed by BuyToCover
+    SellShort,
+    BuyToCover,
+}
+
+impl Side {
+    // E*TRADE's transactionType for equities, then for options
+    fn from_transaction_type(transaction_type: &str) -> Option<Side> {
+        match transaction_type.to_lowercase().as_str() {
+            "bought" | "bought to open" => Some(Side::Buy),
+            "sold" | "sold to close" => Some(Side::Sell),
+            "sold short" | "sold to open" => Some(Side::SellShort),
+            "bought to cover" | "bought to close" => Some(Side::BuyToCover),
+            _ => None,
+        }
+    }
+
+    fn is_buy(&self) -> bool {
+        matches!(self, Side::Buy | Side::BuyToCover)
+    }
+
+    fn opens(&self) -> bool {
+        matches!(self, Side::Buy | Side::SellShort)
+    }
+
+    fn is_short(&self) -> bool {
+        matches!(self, Side::SellShort | Side::BuyToCover)
+    }
+}
+
+// A trade of one security, `amount` being the cash that changed hands after fees. `symbol` is the
+// whole contract for options, so each one is matched on its own
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fill {
+    pub id: i64,
+    pub date: NaiveDate,
+    pub symbol: String,
+    pub security_type: String,
+    pub side: Side,
+    pub quantity: f64,
+    pub amount: f64,
+}
+
+impl Fill {
+    // None for transactions that don't move shares, like dividends and deposits. An error for ones that
+    // do but aren't a trade this can match, like an exercise or a transfer in
+    pub fn from_transaction(transaction: &Transaction) -> Result<Option<Fill>, String> {
+        let brokerage = match transaction.brokerage.as_ref() {
+            Some(brokerage) => brokerage,
+            None => return Ok(None),
+        };
+        let quantity = brokerage.quantity.abs();
+        if quantity < EPSILON || brokerage.product.symbol.is_empty() {
+            return Ok(None);
+        }
+        let symbol = brokerage.product.display();
+        let date = transactions::date(transaction);
+        let side = match Side::from_transaction_type(&transaction.transaction_type) {
+            Some(side) => side,
+            None => return Err(format!("{} {} {} on {} isn't counted", transaction.transaction_type, quantity, symbol, date)),
+        };
+
+        // the amount already has fees and option multipliers in it, fall back to price when it's missing
+        let amount = if transaction.amount != 0.0 {
+            transaction.amount.abs()
+        } else if side.is_buy() {
+            brokerage.price * quantity + brokerage.fee
+        } else {
+            brokerage.price * quantity - brokerage.fee
+        };
+
+        Ok(Some(Fill {
+            id: transaction.transaction_id,
+            date,
+            symbol,
+            security_type: brokerage.product.security_type.to_owned(),
+            side,
+            quantity,
+            amount,
+        }))
+    }
+}
+
+// Part of a sell matched against one buy. For a short sale, the buy to cover matched against the sale,
+// acquired being the date of the sale
+#[derive(Clone, Debug, PartialEq)]
+pub struct RealizedGain {
+    pub symbol: String,
+    pub quantity: f64,
+    pub acquired: NaiveDate,
+    pub sold: NaiveDate,
+    pub proceeds: f64,
+    pub cost_basis: f64,
+    pub term: HoldingTerm,
+    // the trade that opened the position and the one that closed it
+    pub buy_id: i64,
+    pub sell_id: i64,
+}
+
+impl RealizedGain {
+    pub fn gain(&self) -> f64 {
+        self.proceeds - self.cost_basis
+    }
+}
+
+// Shares closed without a trade in the history that opened them, opened before the range fetched
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unmatched {
+    pub symbol: String,
+    pub sold: NaiveDate,
+    pub quantity: f64,
+    pub sell_id: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    pub gains: Vec<RealizedGain>,
+    pub unmatched: Vec<Unmatched>,
+}
+
+struct OpenLot {
+    id: i64,
+    date: NaiveDate,
+    remaining: f64,
+    // paid for a buy, received for a short sale
+    price_per_share: f64,
+}
+
+// Fills have to come oldest first, as all_transactions fetches them
+pub fn realize(fills: &[Fill], matching: LotMatching, selections: &LotSelections) -> Report {
+    let mut fills = fills.to_vec();
+    // stable, so trades on the same day stay in the order they happened
+    fills.sort_by_key(|fill| fill.date);
+
+    let mut report = Report::default();
+    // long and short lots of each security, apart
+    let mut open_lots: HashMap<(String, String, bool), Vec<OpenLot>> = HashMap::new();
+    for fill in fills {
+        let key = (fill.security_type.to_owned(), fill.symbol.to_owned(), fill.side.is_short());
+        let lots = open_lots.entry(key).or_default();
+        if fill.side.opens() {
+            lots.push(OpenLot {
+                id: fill.id,
+                date: fill.date,
+                remaining: fill.quantity,
+                price_per_share: fill.amount / fill.quantity,
+            });
+            continue;
+        }
+
+        let price_per_share = fill.amount / fill.quantity;
+        let mut remaining = fill.quantity;
+        let mut close = |lot: &mut OpenLot, quantity: f64| {
+            let quantity = quantity.min(lot.remaining).min(remaining);
+            if quantity < EPSILON {
+                return;
+            }
+            lot.remaining -= quantity;
+            remaining -= quantity;
+            let (proceeds, cost_basis, term) = if fill.side.is_short() {
+                // a short sale is short-term whenever it was opened, the shares delivered were only just bought
+                (lot.price_per_share * quantity, price_per_share * quantity, HoldingTerm::ShortTerm)
+            } else {
+                (price_per_share * quantity, lot.price_per_share * quantity, utils::holding_term(lot.date, fill.date))
+            };
+            report.gains.push(RealizedGain {
+                symbol: fill.symbol.to_owned(),
+                quantity,
+                acquired: lot.date,
+                sold: fill.date,
+                proceeds,
+                cost_basis,
+                term,
+                buy_id: lot.id,
+                sell_id: fill.id,
+            });
+        };
+
+        if matching == LotMatching::SpecificLots {
+            for selection in selections.get(&fill.id).into_iter().flatten() {
+                if let Some(lot) = lots.iter_mut().find(|lot| lot.id == selection.buy) {
+                    close(lot, selection.quantity);
+                }
+            }
+        }
+        for lot in lots.iter_mut() {
+            close(lot, f64::MAX);
+        }
+        lots.retain(|lot| lot.remaining > EPSILON);
+
+        if remaining > EPSILON {
+            report.unmatched.push(Unmatched {
+                symbol: fill.symbol.to_owned(),
+                sold: fill.date,
+                quantity: remaining,
+                sell_id: fill.id,
+            });
+        }
+    }
+
+    report
+}
+
+// Gains for one symbol in one tax year, or every symbol when `symbol` is None
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GainSummary {
+    pub year: i32,
+    pub symbol: Option<String>,
+    pub proceeds: f64,
+    pub cost_basis: f64,
+    pub short_term: f64,
+    pub long_term: f64,
+}
+
+impl GainSummary {
+    pub fn total(&self) -> f64 {
+        self.short_term + self.long_term
+    }
+
+    fn add(&mut self, gain: &RealizedGain) {
+        self.proceeds += gain.proceeds;
+        self.cost_basis += gain.cost_basis;
+        match gain.term {
+            HoldingTerm::ShortTerm => self.short_term += gain.gain(),
+            HoldingTerm::LongTerm => self.long_term += gain.gain(),
+        }
+    }
+}
+
+// Each year's total followed by its symbols, newest year first
+pub fn summarize(gains: &[RealizedGain]) -> Vec<GainSummary> {
+    let mut years: Vec<GainSummary> = vec![];
+    let mut symbols: Vec<GainSummary> = vec![];
+    for gain in gains {
+        let year = gain.sold.year();
+        match years.iter_mut().find(|summary| summary.year == year) {
+            Some(summary) => summary.add(gain),
+            None => {
+                let mut summary = GainSummary { year, ..Default::default() };
+                summary.add(gain);
+                years.push(summary);
+            }
+        }
+
+        let symbol = Some(gain.symbol.to_owned());
+        match symbols.iter_mut().find(|summary| summary.year == year && summary.symbol == symbol) {
+            Some(summary) => summary.add(gain),
+            None => {
+                let mut summary = GainSummary { year, symbol, ..Default::default() };
+                summary.add(gain);
+                symbols.push(summary);
+            }
+        }
+    }
+
+    years.sort_by_key(|summary| std::cmp::Reverse(summary.year));
+    symbols.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    years
+        .into_iter()
+        .flat_map(|year| {
+            let rows = symbols.iter().filter(|summary| summary.year == year.year).cloned().collect::<Vec<GainSummary>>();
+            std::iter::once(year).chain(rows)
+        })
+        .collect()
+}
+
+// One row per matched lot, in the columns of Form 8949
+pub fn to_csv(gains: &[RealizedGain]) -> String {
+    let mut csv = "Description,Date Acquired,Date Sold,Proceeds,Cost Basis,Gain or Loss,Term,Symbol,Quantity\n".to_string();
+    for gain in gains {
+        let term = match gain.term {
+            HoldingTerm::ShortTerm => "Short-term",
+            HoldingTerm::LongTerm => "Long-term",
+        };
+        let fields = [
+            format!("{} sh {}", gain.quantity, gain.symbol),
+            gain.acquired.format("%m/%d/%Y").to_string(),
+            gain.sold.format("%m/%d/%Y").to_string(),
+            format!("{:.2}", gain.proceeds),
+            format!("{:.2}", gain.cost_basis),
+            format!("{:.2}", gain.gain()),
+            term.to_string(),
+            gain.symbol.to_owned(),
+            gain.quantity.to_string(),
+        ];
+        csv.push_str(&fields.iter().map(|field| utils::csv_field(field)).collect::<Vec<String>>().join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+pub fn export(gains: &[RealizedGain], dir: &Path, now: NaiveDateTime) -> Result<PathBuf, RuntimeError> {
+    Ok(utils::write_export(dir, "realized-gains", &to_csv(gains), now)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::temp_dir;
+
+    fn fill(id: i64, date: (i32, u32, u32), symbol: &str, side: Side, quantity: f64, amount: f64) -> Fill {
+        Fill {
+            id,
+            date: NaiveDate::from_ymd(date.0, date.1, date.2),
+            symbol: symbol.to_string(),
+            security_type: "EQ".to_string(),
+            side,
+            quantity,
+            amount,
+        }
+    }
+
+    // 10 AAPL at $100 in 2019, 10 more at $150 in 2020, 15 sold at $200 in 2020
+    fn aapl_fills() -> Vec<Fill> {
+        vec![
+            fill(3, (2020, 9, 1), "AAPL", Side::Sell, 15.0, 3000.0),
+            fill(1, (2019, 6, 3), "AAPL", Side::Buy, 10.0, 1000.0),
+            fill(2, (2020, 3, 2), "AAPL", Side::Buy, 10.0, 1500.0),
+        ]
+    }
+
+    #[test]
+    fn fifo_closes_the_oldest_lots_first() {
+        let report = realize(&aapl_fills(), LotMatching::Fifo, &LotSelections::new());
+
+        assert_eq!(report.gains.len(), 2);
+        let (first, second) = (&report.gains[0], &report.gains[1]);
+        assert_eq!((first.buy_id, first.quantity, first.cost_basis, first.proceeds), (1, 10.0, 1000.0, 2000.0));
+        assert_eq!(first.term, HoldingTerm::LongTerm);
+        assert_eq!((second.buy_id, second.quantity, second.cost_basis, second.proceeds), (2, 5.0, 750.0, 1000.0));
+        assert_eq!(second.term, HoldingTerm::ShortTerm);
+        assert!(report.unmatched.is_empty());
+    }
+
+    #[test]
+    fn specific_lots_then_fifo_for_the_rest() {
+        let mut selections = LotSelections::new();
+        selections.insert(3, vec![LotSelection { buy: 2, quantity: 10.0 }, LotSelection { buy: 99, quantity: 5.0 }]);
+
+        let report = realize(&aapl_fills(), LotMatching::SpecificLots, &selections);
+        let matched = report.gains.iter().map(|gain| (gain.buy_id, gain.quantity)).collect::<Vec<(i64, f64)>>();
+        // the unknown buy 99 falls back to FIFO
+        assert_eq!(matched, vec![(2, 10.0), (1, 5.0)]);
+        assert_eq!(report.gains[0].gain(), 500.0);
+        assert_eq!(report.gains[1].gain(), 500.0);
+
+        // selections are ignored under FIFO
+        let report = realize(&aapl_fills(), LotMatching::Fifo, &selections);
+        assert_eq!(report.gains[0].buy_id, 1);
+    }
+
+    #[test]
+    fn sells_without_buys_are_unmatched() {
+        let fills = vec![
+            fill(1, (2021, 1, 4), "F", Side::Buy, 100.0, 1000.0),
+            fill(2, (2021, 2, 1), "F", Side::Sell, 150.0, 1800.0),
+            fill(3, (2021, 2, 1), "TSLA", Side::Sell, 1.0, 800.0),
+        ];
+        let report = realize(&fills, LotMatching::Fifo, &LotSelections::new());
+
+        assert_eq!(report.gains.len(), 1);
+        assert!((report.gains[0].gain() - 200.0).abs() < 1e-9);
+        let unmatched = report.unmatched.iter().map(|u| (u.symbol.as_str(), u.quantity)).collect::<Vec<(&str, f64)>>();
+        assert_eq!(unmatched, vec![("F", 50.0), ("TSLA", 1.0)]);
+    }
+
+    #[test]
+    fn same_day_buy_then_sell() {
+        // a day trade, oldest first as all_transactions fetches them
+        let fills = vec![
+            fill(1, (2021, 2, 1), "F", Side::Buy, 100.0, 1000.0),
+            fill(2, (2021, 2, 1), "F", Side::Sell, 100.0, 1100.0),
+        ];
+        let report = realize(&fills, LotMatching::Fifo, &LotSelections::new());
+
+        assert!(report.unmatched.is_empty());
+        assert_eq!(report.gains.len(), 1);
+        assert_eq!((report.gains[0].buy_id, report.gains[0].sell_id, report.gains[0].gain()), (1, 2, 100.0));
+    }
+
+    #[test]
+    fn short_sales_close_on_the_cover() {
+        let fills = vec![
+            fill(1, (2021, 1, 4), "GME", Side::SellShort, 10.0, 3000.0),
+            fill(2, (2021, 1, 6), "GME", Side::Buy, 5.0, 1000.0),
+            fill(3, (2022, 2, 1), "GME", Side::BuyToCover, 10.0, 1000.0),
+        ];
+        let report = realize(&fills, LotMatching::Fifo, &LotSelections::new());
+
+        // the long buy stays open, it isn't what covered the short
+        assert_eq!(report.gains.len(), 1);
+        let gain = &report.gains[0];
+        assert_eq!((gain.buy_id, gain.sell_id, gain.proceeds, gain.cost_basis), (1, 3, 3000.0, 1000.0));
+        assert_eq!(gain.term, HoldingTerm::ShortTerm);
+        assert!(report.unmatched.is_empty());
+    }
+
+    #[test]
+    fn option_contracts_are_matched_on_their_own() {
+        let mut fills = vec![
+            fill(1, (2021, 1, 4), "IBM 2021-06-18 $140.00 CALL", Side::Buy, 1.0, 300.0),
+            fill(2, (2021, 1, 5), "IBM 2021-06-18 $150.00 CALL", Side::Buy, 1.0, 100.0),
+            fill(3, (2021, 2, 1), "IBM 2021-06-18 $150.00 CALL", Side::Sell, 1.0, 250.0),
+        ];
+        for fill in fills.iter_mut() {
+            fill.security_type = "OPTN".to_string();
+        }
+        let report = realize(&fills, LotMatching::Fifo, &LotSelections::new());
+
+        assert_eq!(report.gains.len(), 1);
+        assert_eq!((report.gains[0].buy_id, report.gains[0].gain()), (2, 150.0));
+    }
+
+    #[test]
+    fn summarizes_by_year_and_symbol() {
+        let mut fills = aapl_fills();
+        fills.extend(vec![
+            fill(4, (2021, 1, 4), "F", Side::Buy, 100.0, 1000.0),
+            fill(5, (2021, 2, 1), "F", Side::Sell, 100.0, 900.0),
+            fill(6, (2021, 3, 1), "AAPL", Side::Sell, 5.0, 600.0),
+        ]);
+        let report = realize(&fills, LotMatching::Fifo, &LotSelections::new());
+        let summaries = summarize(&report.gains);
+
+        let rows = summaries.iter().map(|s| (s.year, s.symbol.as_deref(), s.short_term, s.long_term)).collect::<Vec<_>>();
+        assert_eq!(rows, vec![
+            (2021, None, -100.0 - 150.0, 0.0),
+            (2021, Some("AAPL"), -150.0, 0.0),
+            (2021, Some("F"), -100.0, 0.0),
+            (2020, None, 250.0, 1000.0),
+            (2020, Some("AAPL"), 250.0, 1000.0),
+        ]);
+        assert_eq!(summaries[3].total(), 1250.0);
+        assert_eq!((summaries[3].proceeds, summaries[3].cost_basis), (3000.0, 1750.0));
+    }
+
+    #[test]
+    fn fills_from_transactions() {
+        let transaction = |transaction_type: &str, amount: f64| -> Transaction {
+            serde_json::from_str(&format!(r#"{{
+                "transactionId": 7,
+                "transactionDate": 1619841600000,
+                "amount": {},
+                "transactionType": "{}",
+                "brokerage": {{ "product": {{ "symbol": "AAPL" }}, "quantity": -10, "price": 150, "fee": 4.95 }}
+            }}"#, amount, transaction_type)).unwrap()
+        };
+
+        let sold = Fill::from_transaction(&transaction("Sold", 1495.05)).unwrap().unwrap();
+        assert_eq!((sold.side, sold.quantity, sold.amount), (Side::Sell, 10.0, 1495.05));
+        assert_eq!(sold.date, NaiveDate::from_ymd(2021, 5, 1));
+
+        let bought = Fill::from_transaction(&transaction("Bought", 0.0)).unwrap().unwrap();
+        assert_eq!((bought.side, bought.amount), (Side::Buy, 1504.95));
+
+        let short = Fill::from_transaction(&transaction("Sold Short", 1495.05)).unwrap().unwrap();
+        assert_eq!(short.side, Side::SellShort);
+        let cover = Fill::from_transaction(&transaction("Bought To Cover", 0.0)).unwrap().unwrap();
+        assert_eq!((cover.side, cover.amount), (Side::BuyToCover, 1504.95));
+
+        // shares moved by something that isn't a trade are reported instead of dropped
+        assert_eq!(
+            Fill::from_transaction(&transaction("Transfer", 0.0)),
+            Err("Transfer 10 AAPL on 2021-05-01 isn't counted".to_string())
+        );
+
+        let mut dividend = transaction("Qualified Dividend", 10.0);
+        dividend.brokerage.as_mut().unwrap().quantity = 0.0;
+        assert_eq!(Fill::from_transaction(&dividend), Ok(None));
+    }
+
+    #[test]
+    fn fills_from_option_transactions() {
+        let transaction: Transaction = serde_json::from_str(r#"{
+            "transactionId": 8,
+            "transactionDate": 1619841600000,
+            "amount": -310.5,
+            "transactionType": "Bought To Open",
+            "brokerage": {
+                "product": { "symbol": "IBM", "securityType": "OPTN", "callPut": "CALL", "expiryYear": 2021, "expiryMonth": 6, "expiryDay": 18, "strikePrice": 140 },
+                "quantity": 1, "price": 3.05, "fee": 5.5
+            }
+        }"#).unwrap();
+
+        let fill = Fill::from_transaction(&transaction).unwrap().unwrap();
+        assert_eq!((fill.side, fill.amount), (Side::Buy, 310.5));
+        assert_eq!((fill.symbol.as_str(), fill.security_type.as_str()), ("IBM 2021-06-18 $140.00 CALL", "OPTN"));
+    }
+
+    #[test]
+    fn writes_form_8949_csv() {
+        let report = realize(&aapl_fills(), LotMatching::Fifo, &LotSelections::new());
+        let csv = to_csv(&report.gains);
+        let lines = csv.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "10 sh AAPL,06/03/2019,09/01/2020,2000.00,1000.00,1000.00,Long-term,AAPL,10");
+        assert_eq!(lines[2], "5 sh AAPL,03/02/2020,09/01/2020,1000.00,750.00,250.00,Short-term,AAPL,5");
+    }
+
+    #[test]
+    fn loads_lot_selections() {
+        let dir = temp_dir("lot-selections");
+        let path = dir.join("lot_selections.yml");
+        assert_eq!(load_lot_selections(&path).unwrap(), LotSelections::new());
+
+        fs::write(&path, "3:\n  - buy: 2\n    quantity: 10\n").unwrap();
+        let selections = load_lot_selections(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(selections.get(&3), Some(&vec![LotSelection { buy: 2, quantity: 10.0 }]));
+    }
+}
@@ -1,9 +1,9 @@
-use crate::utils;
-use crate::store::{Store};
+use crate::store::{LocalCredsData, Store};
 use crate::stonks_error::RuntimeError;
 use crate::config::{ClientConfig, ConfigPaths, UrlConfig};
 use serde::{Deserialize, Serialize};
-use chrono::prelude::*;
+use secstr::SecUtf8;
+use chrono::{prelude::*, Duration};
 use http::header::{AUTHORIZATION};
 use hyper::{
     client::{connect::dns::GaiResolver, HttpConnector},
@@ -12,30 +12,31 @@ use hyper::{
 use hyper_tls::HttpsConnector;
 use log::debug;
 use std::{
-    fs,
-    io::{stdin, Read, Write}
+    fmt,
+    io::{stdin, Write},
+    sync::Arc,
 };
 // use log::debug;
 
 type HttpClient = Client<HttpsConnector<HttpConnector<GaiResolver>>, hyper::Body>;
 
 // general response struct from oauth apis
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// zeroed when dropped, and Debug prints ***SECRET*** instead of the token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Credentials {
-    pub key: String,
-    pub secret: String,
+    pub key: SecUtf8,
+    pub secret: SecUtf8,
 }
 
 impl Credentials {
-    // pub fn new(key: SecUtf8, secret: SecUtf8) -> Credentials {
-    pub fn new(key: String, secret: String) -> Credentials {
+    pub fn new(key: SecUtf8, secret: SecUtf8) -> Credentials {
         Credentials { key, secret }
     }
 }
 
 impl Into<oauth::Credentials> for Credentials {
     fn into(self) -> oauth::Credentials {
-        oauth::Credentials::new(self.key, self.secret)
+        oauth::Credentials::new(self.key.into_unsecure(), self.secret.into_unsecure())
     }
 }
 
@@ -46,20 +47,120 @@ where
 {
   fn from(input: oauth::Credentials<T>) -> Self {
     Credentials {
-      key: input.identifier.into(),
-      secret: input.secret.into(),
+      key: SecUtf8::from(input.identifier),
+      secret: SecUtf8::from(input.secret),
     }
   }
 }
 
-// serde serialization format for writing and retrieving from file
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct LocalCredsData {
-    pub access_creds: Credentials, // we store this for all requests
-    pub request_token_creds: Credentials, // we store this if user quits application and opens up and we just need to renew_access_token
-    pub verification_code: String, // we store this if user quits application and opens up and we just need to renew_access_token
-    pub expires_at: DateTime<Utc>,
-    pub last_request_timestamp: DateTime<Utc>,
+// How the verification code from E*TRADE's authorize page is collected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verifier {
+    // prompted for on the terminal, before the UI takes over the screen
+    Stdin,
+    // typed into the authorization screen
+    Dialog,
+}
+
+// A request token waiting on the verification code the user gets from `authorize_url`
+#[derive(Debug, Clone)]
+pub struct PendingAuthorization {
+    pub request_token_creds: Credentials,
+    pub authorize_url: String,
+}
+
+fn authorization_required() -> RuntimeError {
+    RuntimeError { message: "E*TRADE needs you to authorize stonks-terminal again".to_string() }
+}
+
+// Where the session gets the time, so tests can move it along
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+#[derive(Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// A clock tests set and move forward by hand
+#[cfg(test)]
+#[derive(Debug)]
+pub struct TestClock(std::sync::Mutex<DateTime<Utc>>);
+
+#[cfg(test)]
+impl TestClock {
+    pub fn at(now: DateTime<Utc>) -> Arc<TestClock> {
+        Arc::new(TestClock(std::sync::Mutex::new(now)))
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.0.lock().unwrap();
+        *now = *now + duration;
+    }
+}
+
+#[cfg(test)]
+impl Clock for TestClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap()
+    }
+}
+
+// E*TRADE deactivates an access token after two hours without a request, which a renewal undoes, and expires it
+// at midnight Eastern, after which only a new authorization will do
+// https://apisb.etrade.com/docs/api/authorization/renew_access_token.html
+const IDLE_TIMEOUT_MINUTES: i64 = 120;
+// renews this far ahead of the cutoff, so no request finds the token inactive
+const RENEW_MARGIN_MINUTES: i64 = 15;
+// between attempts when a renewal or authorization fails
+const RETRY_MINUTES: i64 = 1;
+
+// What the access token needs before the next request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenAction {
+    Keep,
+    Renew,
+    Reauthorize,
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenLifecycle {
+    clock: Arc<dyn Clock>,
+    // after a failed attempt, the next one waits until then
+    retry_at: Option<DateTime<Utc>>,
+}
+
+impl TokenLifecycle {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            retry_at: None,
+        }
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    pub fn action(&self, tokens: Option<&LocalCredsData>) -> TokenAction {
+        let now = self.now();
+        let renew_after = Duration::minutes(IDLE_TIMEOUT_MINUTES - RENEW_MARGIN_MINUTES);
+        match tokens {
+            Some(tokens) if now >= tokens.expires_at => TokenAction::Reauthorize,
+            Some(tokens) if now >= tokens.last_request_timestamp + renew_after => TokenAction::Renew,
+            Some(_) => TokenAction::Keep,
+            None => TokenAction::Reauthorize,
+        }
+    }
+
+    fn waiting_to_retry(&self) -> bool {
+        self.retry_at.is_some_and(|retry_at| self.now() < retry_at)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -75,6 +176,9 @@ pub struct Session<T> {
     client: HttpClient,
     pub store: T,
     pub config_paths: ConfigPaths,
+    pub verifier: Verifier,
+    pub pending_authorization: Option<PendingAuthorization>,
+    pub token_lifecycle: TokenLifecycle,
 }
 
 impl<T> Session<T>
@@ -89,59 +193,80 @@ where T: Store
             client,
             store,
             config_paths,
+            verifier: Verifier::Stdin,
+            pending_authorization: None,
+            token_lifecycle: TokenLifecycle::new(Arc::new(SystemClock)),
         }
     }
 
-    pub fn get_creds_from_cache(&mut self) -> Option<LocalCredsData> {
-        if let Ok(mut file) = fs::File::open(&self.config_paths.token_cache_path) {
-            let mut local_data_str = String::new();
-            if file.read_to_string(&mut local_data_str).is_ok() {
-                if let Ok(local_data) = serde_json::from_str::<LocalCredsData>(&local_data_str) {
-                    return Some(local_data);
-                }
-            }
-        }
-
-        None
+    // A request went out with the access token, which puts off going idle
+    pub fn record_activity(&mut self) -> Result<(), RuntimeError> {
+        let now = self.token_lifecycle.now();
+        self.store.touch(now)
     }
 
-    pub fn expired_access_token(&mut self, local_data: &LocalCredsData) -> bool {
-        return utils::now_eastern() > local_data.expires_at;
-    }
-
-    pub fn should_renew_access_token(&mut self) -> bool {
-        if let Some(local_data) = self.get_creds_from_cache() {
-            let now = utils::now_plus_hours(-2);
-            return now > local_data.last_request_timestamp;
+    // Renews the access token before it goes idle, and runs the full flow once it has expired at midnight or
+    // there's none. Does nothing while an authorization waits on its code, or for a minute after a failure
+    pub async fn maintain_token(&mut self, client_config: ClientConfig) -> Result<(), RuntimeError> {
+        if self.pending_authorization.is_some() || self.token_lifecycle.waiting_to_retry() {
+            return Ok(());
         }
 
-        true
-    }
+        let result = match (self.token_lifecycle.action(self.store.tokens()), self.store.tokens().cloned()) {
+            (TokenAction::Keep, _) => return Ok(()),
+            (TokenAction::Renew, Some(cached_creds)) => {
+                debug!("Renewing the access token before it goes idle");
+                self.renew_access_token(client_config, cached_creds).await
+            }
+            _ => {
+                debug!("Access token expired, authorizing again");
+                self.full_access_flow(client_config).await
+            }
+        };
 
-    pub fn hydrate_local_store(&mut self, client_config: ClientConfig) {
-        // rehydrate local store
-        if let Some(local_data) = self.get_creds_from_cache() {
-            self.store.put(client_config.consumer_key.to_owned(), local_data.access_creds.clone());
-            self.store.put(client_config.consumer_key.to_owned() + &"request_token".to_string(), local_data.request_token_creds.clone());
-            self.store.set_verification_code(local_data.verification_code.to_owned());
-        }
+        // an authorization waiting on its code hasn't failed
+        self.token_lifecycle.retry_at = match result {
+            Err(_) if self.pending_authorization.is_none() => Some(self.token_lifecycle.now() + Duration::minutes(RETRY_MINUTES)),
+            _ => None,
+        };
+
+        result
     }
 
     pub async fn full_access_flow(&mut self, client_config: ClientConfig) -> Result<(), RuntimeError> {
-        let creds = Credentials::new(client_config.consumer_key.to_string(), client_config.consumer_secret.to_string());
-        let request_token_creds = self.request_token(&creds).await;
-
-        // 2. obtain verification code
-        // lives for 5 minutes
-        // https://apisb.etrade.com/docs/api/authorization/authorize.html
-        if request_token_creds.is_err() {
-            return Err(RuntimeError { message: "request_token failed".to_string() });
+        let pending = self.start_authorization(&client_config).await?;
+
+        match self.verifier {
+            Verifier::Stdin => {
+                let verification_code = self.verify_code(&pending.authorize_url)?;
+                self.finish_authorization(client_config, pending, verification_code).await
+            }
+            // the network thread holds the app while this runs, so the code can't be waited on here.
+            // The authorization screen sends it to finish_authorization once it's typed in
+            Verifier::Dialog => {
+                self.pending_authorization = Some(pending);
+                Err(authorization_required())
+            }
         }
+    }
 
-        let request_token_creds = request_token_creds.unwrap();
+    // 1. request token, then 2. the page where the user accepts and gets a verification code
+    // lives for 5 minutes
+    // https://apisb.etrade.com/docs/api/authorization/authorize.html
+    pub async fn start_authorization(&self, client_config: &ClientConfig) -> Result<PendingAuthorization, RuntimeError> {
+        let creds = client_config.consumer_creds();
+        let request_token_creds = self.request_token(&creds)
+            .await
+            .map_err(|_| RuntimeError { message: "request_token failed".to_string() })?;
+
+        Ok(PendingAuthorization {
+            authorize_url: self.urls.authorize_url(creds.key.unsecure(), request_token_creds.key.unsecure()),
+            request_token_creds,
+        })
+    }
 
-        let verification_code = self.verification_code(&creds, &request_token_creds)?;
-        self.store.set_verification_code(verification_code.to_owned());
+    pub async fn finish_authorization(&mut self, client_config: ClientConfig, pending: PendingAuthorization, verification_code: SecUtf8) -> Result<(), RuntimeError> {
+        let creds = client_config.consumer_creds();
 
         // 3. make request for authorization token
         // expires at midnight Eastern Time
@@ -151,15 +276,29 @@ where T: Store
             Mode::Sandbox => self.urls.sandbox_access_token_url,
             Mode::Live => self.urls.access_token_url,
         };
-        let oauth_access_creds = self.access_token(uri, &creds, &request_token_creds, &verification_code).await;
-        let oauth_access_creds = oauth_access_creds.unwrap();
+        let oauth_access_creds = self.access_token(uri, &creds, &pending.request_token_creds, &verification_code).await?;
 
         // finished oauth process
-        self.save_creds_to_file(&request_token_creds, oauth_access_creds)?;
+        self.store.save_tokens(pending.request_token_creds, oauth_access_creds, verification_code, self.token_lifecycle.now())?;
+        self.pending_authorization = None;
 
         Ok(())
     }
 
+    // After E*TRADE rejects the access token: renew it, which starts over with the full flow when renewal
+    // fails too. The store hands the new token to the next requests
+    pub async fn reauthenticate(&mut self, client_config: ClientConfig) -> Result<(), RuntimeError> {
+        // keep the link on screen valid rather than asking for a new one with every request
+        if self.pending_authorization.is_some() {
+            return Err(authorization_required());
+        }
+
+        match self.store.tokens().cloned() {
+            Some(cached_creds) => self.renew_access_token(client_config, cached_creds).await,
+            None => self.full_access_flow(client_config).await,
+        }
+    }
+
     // only valid for 5 minutes
     // https://apisb.etrade.com/docs/api/authorization/request_token.html
     pub async fn request_token(&self, consumer: &Credentials) -> Result<Credentials, RuntimeError> {
@@ -171,27 +310,21 @@ where T: Store
             .callback("oob")
             .get(&uri, &());
 
-        let body = self.send_request_for_auth(uri, authorization_header).await;
+        let body = self.send_request_for_auth(uri, authorization_header).await?;
         let creds: oauth_credentials::Credentials<Box<str>> = serde_urlencoded::from_bytes(&body)?;
         let request_token_creds = creds.into();
 
         Ok(request_token_creds)
     }
 
-    pub fn verification_code(&self, consumer: &Credentials, request_token: &Credentials) -> Result<String, RuntimeError> {
-        let url = self.urls.authorize_url(&consumer.key, &request_token.key);
-        let verification_code = self.verify_code(url)?;
-        Ok(verification_code)
-    }
-
     // https://apisb.etrade.com/docs/api/authorization/authorize.html
-    pub async fn access_token(&self, uri: &str, consumer: &Credentials, request_token_creds: &Credentials, verification_code: &String) -> Result<Credentials, RuntimeError> {
+    pub async fn access_token(&self, uri: &str, consumer: &Credentials, request_token_creds: &Credentials, verification_code: &SecUtf8) -> Result<Credentials, RuntimeError> {
         let authorization_header = oauth::Builder::<_, _>::new(consumer.clone().into(), oauth::HmacSha1)
             .token(Some(request_token_creds.clone().into()))
-            .verifier(Some(verification_code.as_ref()))
+            .verifier(Some(verification_code.unsecure()))
             .get(&uri, &());
 
-        let body = self.send_request_for_auth(uri, authorization_header).await;
+        let body = self.send_request_for_auth(uri, authorization_header).await?;
         let creds: oauth_credentials::Credentials<Box<str>> = serde_urlencoded::from_bytes(&body)?;
         let oauth_access_creds = creds.into();
 
@@ -214,16 +347,16 @@ where T: Store
         // let resp = req.send().await?;
     }
 
-    pub async fn send_request_for_auth(&self, uri: &str, authorization: String) -> Vec<u8> {
-        let resp = self.send_request(uri, authorization).await.unwrap();
+    pub async fn send_request_for_auth(&self, uri: &str, authorization: String) -> Result<Vec<u8>, RuntimeError> {
+        let resp = self.send_request(uri, authorization).await?;
 
         if resp.status().as_u16() / 100 == 2 {
             let bd = resp.into_body();
-            hyper::body::to_bytes(bd).await.unwrap().to_vec()
+            Ok(hyper::body::to_bytes(bd).await?.to_vec())
         } else {
-            // soft fail
-            println!("error {:?}", resp);
-            vec![]
+            // soft fail, the empty body doesn't parse as credentials
+            debug!("error {:?}", resp);
+            Ok(vec![])
         }
     }
 
@@ -240,53 +373,106 @@ where T: Store
         resp
     }
 
+    pub async fn send_put_request(&self, uri: &str, authorization: String, body: String) -> Result<Response<Body>, hyper::Error> {
+        let req = Request::builder()
+            .method(Method::PUT)
+            .uri(uri)
+            .header(AUTHORIZATION, authorization)
+            .body(Body::from(body));
+
+        self.client.request(req.unwrap()).await
+    }
+
+    // Signed with the access token itself. E*TRADE answers 200 with a message and the same token goes on
+    // working, anything else and the full flow runs instead
+    // https://apisb.etrade.com/docs/api/authorization/renew_access_token.html
     pub async fn renew_access_token(&mut self, client_config: ClientConfig, local_data: LocalCredsData) -> Result<(), RuntimeError> {
-        let creds = Credentials::new(client_config.consumer_key.to_string(), client_config.consumer_secret.to_string());
+        let creds = client_config.consumer_creds();
 
         let uri = match self.mode {
             Mode::Sandbox => self.urls.sandbox_renew_token_url,
             Mode::Live => self.urls.renew_token_url,
         };
-        let oauth_access_creds = self.access_token(uri, &creds, &local_data.request_token_creds, &local_data.verification_code).await;
-        if oauth_access_creds.is_ok() {
-            let oauth_access_creds = oauth_access_creds.unwrap();
+        let authorization_header = oauth::Builder::<_, _>::new(creds.into(), oauth::HmacSha1)
+            .token(Some(local_data.access_creds.into()))
+            .get(&uri, &());
 
-            self.save_creds_to_file(&local_data.request_token_creds, oauth_access_creds)?;
-        } else {
-            self.full_access_flow(client_config).await?;
+        match self.send_request(uri, authorization_header).await {
+            Ok(resp) if resp.status().is_success() => {
+                self.store.touch(self.token_lifecycle.now())?;
+                self.store.flush()?;
+            }
+            resp => {
+                debug!("renew_token failed {:?}", resp);
+                self.full_access_flow(client_config).await?;
+            }
         }
 
         Ok(())
     }
 
-    fn verify_code(&self, url: String) -> Result<String, RuntimeError> {
+    fn verify_code(&self, url: &str) -> Result<SecUtf8, RuntimeError> {
         let msg = format!("Please visit and accept the license. \n{}\ninput verification code:\n", url,);
         std::io::stderr().write_all(msg.as_bytes())?;
 
         let mut key = String::new();
         stdin().read_line(&mut key)?;
 
-        let result = key.trim().to_owned();
+        let result = SecUtf8::from(key.trim());
         Ok(result)
     }
+}
 
-    fn save_creds_to_file(&self, request_token_creds: &Credentials, oauth_access_creds: Credentials) -> Result<(), RuntimeError> {
-        // write access creds to file
-        let mut file = fs::OpenOptions::new().write(true).create(true).open(&self.config_paths.token_cache_path)?;
-        // shrink file
-        file.set_len(0)?;
-        let data = LocalCredsData {
-            request_token_creds: request_token_creds.clone(),
-            access_creds: oauth_access_creds.clone(),
-            verification_code: self.store.get_verification_code(),
-            expires_at: utils::midnight_eastern(1),
-            last_request_timestamp: utils::now_eastern(),
-        };
-        let access_creds = serde_json::to_string::<LocalCredsData>(&data)?;
-        file.write_all(access_creds.as_bytes())?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(issued: DateTime<Utc>) -> LocalCredsData {
+        LocalCredsData {
+            access_creds: Credentials::new(SecUtf8::from("access-token"), SecUtf8::from("access-secret")),
+            request_token_creds: Credentials::new(SecUtf8::from("request-token"), SecUtf8::from("request-secret")),
+            verification_code: SecUtf8::from("4XK2P"),
+            expires_at: crate::utils::next_midnight_eastern(issued),
+            last_request_timestamp: issued,
+        }
+    }
 
-        debug!("OAuth saved to in memory store: oauth access key {}", &oauth_access_creds.key);
+    #[test]
+    fn renews_ahead_of_the_idle_cutoff() {
+        // 9am EST
+        let issued = Utc.ymd(2021, 1, 4).and_hms(14, 0, 0);
+        let clock = TestClock::at(issued);
+        let lifecycle = TokenLifecycle::new(clock.clone());
+        let mut tokens = tokens(issued);
+        assert_eq!(lifecycle.action(Some(&tokens)), TokenAction::Keep);
+
+        clock.advance(Duration::minutes(104));
+        assert_eq!(lifecycle.action(Some(&tokens)), TokenAction::Keep);
+        clock.advance(Duration::minutes(1));
+        assert_eq!(lifecycle.action(Some(&tokens)), TokenAction::Renew);
+
+        // a request in the meantime starts the two hours over
+        tokens.last_request_timestamp = issued + Duration::minutes(60);
+        assert_eq!(lifecycle.action(Some(&tokens)), TokenAction::Keep);
+        clock.advance(Duration::minutes(60));
+        assert_eq!(lifecycle.action(Some(&tokens)), TokenAction::Renew);
+    }
 
-        Ok(())
+    #[test]
+    fn authorizes_again_from_midnight_eastern() {
+        // 11:30pm EDT, the night the clocks fall back. Midnight comes before they do, at 04:00 UTC
+        let issued = Utc.ymd(2021, 11, 7).and_hms(3, 30, 0);
+        let clock = TestClock::at(issued);
+        let lifecycle = TokenLifecycle::new(clock.clone());
+        let tokens = tokens(issued);
+        assert_eq!(lifecycle.action(None), TokenAction::Reauthorize);
+
+        clock.advance(Duration::minutes(29));
+        assert_eq!(lifecycle.action(Some(&tokens)), TokenAction::Keep);
+        // expired beats idle
+        clock.advance(Duration::minutes(1));
+        assert_eq!(lifecycle.action(Some(&tokens)), TokenAction::Reauthorize);
+        clock.advance(Duration::hours(3));
+        assert_eq!(lifecycle.action(Some(&tokens)), TokenAction::Reauthorize);
     }
 }
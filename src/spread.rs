@@ -0,0 +1,266 @@
+use crate::app::{OptionContract, OPTION_MULTIPLIER};
+use crate::clients::etrade_json_structs::{CallPut, OrderAction, OrderType};
+
+// One leg of a multi-leg option order, ratio is contracts per spread. The contract carries the expiry
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionLeg {
+    // underlying
+    pub symbol: String,
+    pub contract: OptionContract,
+    pub order_action: OrderAction,
+    pub ratio: u32,
+}
+
+impl OptionLeg {
+    pub fn new(symbol: String, contract: OptionContract) -> Self {
+        Self {
+            symbol,
+            contract,
+            order_action: OrderAction::BUY_OPEN,
+            ratio: 1,
+        }
+    }
+
+    pub fn is_buy(&self) -> bool {
+        matches!(self.order_action, OrderAction::BUY_OPEN | OrderAction::BUY_CLOSE)
+    }
+
+    fn sign(&self) -> f64 {
+        if self.is_buy() { 1.0 } else { -1.0 }
+    }
+
+    // Per share price of the leg at the natural: buys pay the ask, sells receive the bid
+    pub fn natural_price(&self) -> f64 {
+        let price = if self.is_buy() { self.contract.ask } else { self.contract.bid };
+        self.sign() * price * self.ratio as f64
+    }
+
+    // Per share value of the leg at expiration
+    fn value_at(&self, underlying: f64) -> f64 {
+        let intrinsic = match self.contract.call_put {
+            CallPut::CALL => (underlying - self.contract.strike_price).max(0.0),
+            CallPut::PUT => (self.contract.strike_price - underlying).max(0.0),
+        };
+        self.sign() * intrinsic * self.ratio as f64
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpreadSummary {
+    // per spread, positive is a debit and negative a credit
+    pub net_price: f64,
+    // None when unlimited
+    pub max_profit: Option<f64>,
+    pub max_loss: Option<f64>,
+    pub breakevens: Vec<f64>,
+}
+
+// Per share net of all legs at the natural, positive is a debit
+pub fn net_price(legs: &[OptionLeg]) -> f64 {
+    legs.iter().map(|leg| leg.natural_price()).sum()
+}
+
+// Profit per share at expiration for a spread opened at net_price
+fn profit_at(legs: &[OptionLeg], net_price: f64, underlying: f64) -> f64 {
+    legs.iter().map(|leg| leg.value_at(underlying)).sum::<f64>() - net_price
+}
+
+// Whether every leg expires on the same date
+pub fn same_expiry(legs: &[OptionLeg]) -> bool {
+    legs.windows(2).all(|pair| pair[0].contract.expiration_date == pair[1].contract.expiration_date)
+}
+
+// Max profit/loss and breakevens at expiration. The payoff is piecewise linear with kinks at the
+// strikes, so it only has to be checked at zero, at each strike and past the highest strike.
+// None for calendars and diagonals, the later legs still have time value when the first ones expire.
+pub fn summary(legs: &[OptionLeg], net_price: f64) -> Option<SpreadSummary> {
+    if !same_expiry(legs) {
+        return None;
+    }
+
+    let mut points = vec![0.0];
+    points.extend(legs.iter().map(|leg| leg.contract.strike_price));
+    points.sort_by(f64::total_cmp);
+    points.dedup();
+
+    let profits = points.iter().map(|p| profit_at(legs, net_price, *p)).collect::<Vec<f64>>();
+    // past the highest strike only the calls move
+    let slope: f64 = legs
+        .iter()
+        .filter(|leg| leg.contract.call_put == CallPut::CALL)
+        .map(|leg| leg.sign() * leg.ratio as f64)
+        .sum();
+
+    let highest = profits.iter().cloned().fold(f64::MIN, f64::max);
+    let lowest = profits.iter().cloned().fold(f64::MAX, f64::min);
+    let max_profit = if slope > 0.0 { None } else { Some(highest * OPTION_MULTIPLIER) };
+    let max_loss = if slope < 0.0 { None } else { Some(-lowest * OPTION_MULTIPLIER) };
+
+    let mut breakevens = vec![];
+    for i in 0..points.len() {
+        if profits[i] == 0.0 && points[i] > 0.0 {
+            breakevens.push(points[i]);
+        }
+        if i + 1 < points.len() && profits[i] * profits[i + 1] < 0.0 {
+            let (x0, x1, y0, y1) = (points[i], points[i + 1], profits[i], profits[i + 1]);
+            breakevens.push(x0 - y0 * (x1 - x0) / (y1 - y0));
+        }
+    }
+    let (last_point, last_profit) = (points[points.len() - 1], profits[profits.len() - 1]);
+    if slope != 0.0 && last_profit * slope < 0.0 {
+        breakevens.push(last_point - last_profit / slope);
+    }
+
+    Some(SpreadSummary {
+        net_price,
+        max_profit,
+        max_loss: max_loss.map(|loss| loss.max(0.0)),
+        breakevens,
+    })
+}
+
+// Which E*TRADE order type the legs make up, legs with different expiries only make a plain spread
+pub fn order_type(legs: &[OptionLeg]) -> OrderType {
+    if !same_expiry(legs) {
+        return OrderType::SPREADS;
+    }
+
+    let calls = legs.iter().filter(|leg| leg.contract.call_put == CallPut::CALL).count();
+    let puts = legs.len() - calls;
+
+    match (legs.len(), calls, puts) {
+        (3, _, _) if calls == 3 || puts == 3 => OrderType::BUTTERFLY,
+        (4, 4, 0) | (4, 0, 4) => OrderType::CONDOR,
+        (4, 2, 2) => {
+            // iron butterflies share the short strike between the put and call sides
+            let short_strikes = legs
+                .iter()
+                .filter(|leg| !leg.is_buy())
+                .map(|leg| leg.contract.strike_price)
+                .collect::<Vec<f64>>();
+            match short_strikes.as_slice() {
+                [a, b] if a == b => OrderType::IRON_BUTTERFLY,
+                _ => OrderType::IRON_CONDOR,
+            }
+        }
+        _ => OrderType::SPREADS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::etrade_json_structs::ExpirationDate;
+
+    fn leg(call_put: CallPut, strike_price: f64, order_action: OrderAction, bid: f64, ask: f64) -> OptionLeg {
+        OptionLeg {
+            symbol: "IBM".to_string(),
+            contract: OptionContract {
+                call_put,
+                strike_price,
+                expiration_date: ExpirationDate { year: 2021, month: 6, day: 18, expiry_type: "MONTHLY".to_string() },
+                display_symbol: "".to_string(),
+                bid,
+                ask,
+            },
+            order_action,
+            ratio: 1,
+        }
+    }
+
+    #[test]
+    fn bull_call_spread() {
+        let legs = vec![
+            leg(CallPut::CALL, 100.0, OrderAction::BUY_OPEN, 4.8, 5.0),
+            leg(CallPut::CALL, 110.0, OrderAction::SELL_OPEN, 2.0, 2.2),
+        ];
+
+        let net = net_price(&legs);
+        assert!((net - 3.0).abs() < 1e-9);
+
+        let summary = summary(&legs, net).unwrap();
+        assert!((summary.max_profit.unwrap() - 700.0).abs() < 1e-6);
+        assert!((summary.max_loss.unwrap() - 300.0).abs() < 1e-6);
+        assert_eq!(summary.breakevens.len(), 1);
+        assert!((summary.breakevens[0] - 103.0).abs() < 1e-9);
+        assert_eq!(order_type(&legs), OrderType::SPREADS);
+    }
+
+    #[test]
+    fn iron_condor() {
+        let legs = vec![
+            leg(CallPut::PUT, 90.0, OrderAction::BUY_OPEN, 0.9, 1.0),
+            leg(CallPut::PUT, 95.0, OrderAction::SELL_OPEN, 2.0, 2.1),
+            leg(CallPut::CALL, 105.0, OrderAction::SELL_OPEN, 2.0, 2.1),
+            leg(CallPut::CALL, 110.0, OrderAction::BUY_OPEN, 0.9, 1.0),
+        ];
+
+        let net = net_price(&legs);
+        assert!((net + 2.0).abs() < 1e-9);
+
+        let summary = summary(&legs, net).unwrap();
+        assert!((summary.max_profit.unwrap() - 200.0).abs() < 1e-6);
+        assert!((summary.max_loss.unwrap() - 300.0).abs() < 1e-6);
+        assert_eq!(summary.breakevens.len(), 2);
+        assert!((summary.breakevens[0] - 93.0).abs() < 1e-9);
+        assert!((summary.breakevens[1] - 107.0).abs() < 1e-9);
+        assert_eq!(order_type(&legs), OrderType::IRON_CONDOR);
+    }
+
+    #[test]
+    fn long_straddle_has_unlimited_profit() {
+        let legs = vec![
+            leg(CallPut::PUT, 100.0, OrderAction::BUY_OPEN, 2.9, 3.0),
+            leg(CallPut::CALL, 100.0, OrderAction::BUY_OPEN, 2.9, 3.0),
+        ];
+
+        let summary = summary(&legs, net_price(&legs)).unwrap();
+        assert_eq!(summary.max_profit, None);
+        assert!((summary.max_loss.unwrap() - 600.0).abs() < 1e-6);
+        assert_eq!(summary.breakevens.len(), 2);
+        assert!((summary.breakevens[0] - 94.0).abs() < 1e-9);
+        assert!((summary.breakevens[1] - 106.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn naked_short_call_has_unlimited_loss() {
+        let legs = vec![leg(CallPut::CALL, 100.0, OrderAction::SELL_OPEN, 2.0, 2.1)];
+
+        let summary = summary(&legs, net_price(&legs)).unwrap();
+        assert!((summary.max_profit.unwrap() - 200.0).abs() < 1e-6);
+        assert_eq!(summary.max_loss, None);
+        assert!((summary.breakevens[0] - 102.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn butterfly_order_types() {
+        let mut legs = vec![
+            leg(CallPut::CALL, 95.0, OrderAction::BUY_OPEN, 6.0, 6.1),
+            leg(CallPut::CALL, 100.0, OrderAction::SELL_OPEN, 3.0, 3.1),
+            leg(CallPut::CALL, 105.0, OrderAction::BUY_OPEN, 1.0, 1.1),
+        ];
+        legs[1].ratio = 2;
+        assert_eq!(order_type(&legs), OrderType::BUTTERFLY);
+
+        let iron_butterfly = vec![
+            leg(CallPut::PUT, 95.0, OrderAction::BUY_OPEN, 1.0, 1.1),
+            leg(CallPut::PUT, 100.0, OrderAction::SELL_OPEN, 3.0, 3.1),
+            leg(CallPut::CALL, 100.0, OrderAction::SELL_OPEN, 3.0, 3.1),
+            leg(CallPut::CALL, 105.0, OrderAction::BUY_OPEN, 1.0, 1.1),
+        ];
+        assert_eq!(order_type(&iron_butterfly), OrderType::IRON_BUTTERFLY);
+    }
+
+    #[test]
+    fn calendar_spread_has_no_single_expiration() {
+        let mut legs = vec![
+            leg(CallPut::CALL, 100.0, OrderAction::SELL_OPEN, 2.0, 2.1),
+            leg(CallPut::CALL, 100.0, OrderAction::BUY_OPEN, 3.9, 4.0),
+        ];
+        legs[1].contract.expiration_date.month = 7;
+
+        assert!(!same_expiry(&legs));
+        assert_eq!(summary(&legs, net_price(&legs)), None);
+        assert_eq!(order_type(&legs), OrderType::SPREADS);
+    }
+}
@@ -1,48 +1,199 @@
-use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use chrono::Duration;
+use chrono::prelude::*;
+use log::debug;
+use secstr::SecUtf8;
+use serde::{Deserialize, Serialize};
 use crate::session::Credentials;
+use crate::stonks_error::RuntimeError;
+use crate::utils;
+use crate::vault::Vault;
+
+// serde serialization format for writing and retrieving from file
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LocalCredsData {
+    pub access_creds: Credentials, // we store this for all requests
+    pub request_token_creds: Credentials, // we store this if user quits application and opens up and we just need to renew_access_token
+    pub verification_code: SecUtf8, // we store this if user quits application and opens up and we just need to renew_access_token
+    pub expires_at: DateTime<Utc>,
+    pub last_request_timestamp: DateTime<Utc>,
+}
 
 pub trait Store {
-    fn get(&self, key: String) -> Option<&Credentials>;
-    fn put(&mut self, key: String, secret: Credentials);
-    fn delete(&mut self, key: String);
-    fn get_verification_code(&self) -> String;
-    fn set_verification_code(&mut self, verification_code: String);
+    // tokens from the last authorization or renewal, None until there's been one
+    fn tokens(&self) -> Option<&LocalCredsData>;
+    // replaces the tokens, issued at `now` and expiring at the next midnight Eastern, and writes them to the cache
+    fn save_tokens(&mut self, request_token_creds: Credentials, access_creds: Credentials, verification_code: SecUtf8, now: DateTime<Utc>) -> Result<(), RuntimeError>;
+    // a request made with the tokens at `at`, which keeps them from going idle. Written to the cache so the next
+    // run knows whether they still are, at most once a minute
+    fn touch(&mut self, at: DateTime<Utc>) -> Result<(), RuntimeError>;
+    // writes a request time touch held back
+    fn flush(&mut self) -> Result<(), RuntimeError>;
+    // other files holding secrets, like client.yml. None when the file isn't there yet
+    fn read_file(&self, path: &Path) -> Result<Option<SecUtf8>, RuntimeError>;
+    fn write_file(&self, path: &Path, contents: &SecUtf8) -> Result<(), RuntimeError>;
 }
 
-// in memory store
+// the quote poll touches the tokens every few seconds, a minute off is nothing against the two hours they stay active
+const TOUCH_WRITE_MINUTES: i64 = 1;
+
+// tokens in memory, written through to an encrypted token cache
 //
-pub struct AuthInMemoryStore {
-    data: HashMap<String, Credentials>,
-    pub verification_code: String,
+pub struct EncryptedStore {
+    tokens: Option<LocalCredsData>,
+    token_cache_path: PathBuf,
+    vault: Vault,
+    // the last request time in the cache
+    written_request_timestamp: Option<DateTime<Utc>>,
 }
 
-impl AuthInMemoryStore {
-    pub fn new() -> Self {
+impl EncryptedStore {
+    // picks up the tokens cached by the last run
+    pub fn new(vault: Vault, token_cache_path: PathBuf) -> Self {
+        // one that can't be read is as good as none, the next authorization replaces it
+        let tokens = match vault.read(&token_cache_path) {
+            Ok(Some(cache)) => serde_json::from_str::<LocalCredsData>(cache.unsecure()).ok(),
+            Ok(None) => None,
+            Err(e) => {
+                debug!("{}", e.message);
+                None
+            }
+        };
+
         Self {
-            data: HashMap::new(),
-            verification_code: "".to_string(),
+            written_request_timestamp: tokens.as_ref().map(|tokens| tokens.last_request_timestamp),
+            tokens,
+            token_cache_path,
+            vault,
         }
     }
+
+    fn write_tokens(&mut self, tokens: &LocalCredsData) -> Result<(), RuntimeError> {
+        let cache = SecUtf8::from(serde_json::to_string::<LocalCredsData>(tokens)?);
+        self.write_file(&self.token_cache_path, &cache)?;
+        self.written_request_timestamp = Some(tokens.last_request_timestamp);
+        Ok(())
+    }
 }
 
-impl Store for AuthInMemoryStore {
-    fn get(&self, key: String) -> Option<&Credentials> {
-        self.data.get(&key)
+impl Store for EncryptedStore {
+    fn tokens(&self) -> Option<&LocalCredsData> {
+        self.tokens.as_ref()
+    }
+
+    fn save_tokens(&mut self, request_token_creds: Credentials, access_creds: Credentials, verification_code: SecUtf8, now: DateTime<Utc>) -> Result<(), RuntimeError> {
+        let tokens = LocalCredsData {
+            access_creds,
+            request_token_creds,
+            verification_code,
+            expires_at: utils::next_midnight_eastern(now),
+            last_request_timestamp: now,
+        };
+        self.write_tokens(&tokens)?;
+        self.tokens = Some(tokens);
+
+        debug!("OAuth access token saved to {}", self.token_cache_path.display());
+
+        Ok(())
+    }
+
+    fn touch(&mut self, at: DateTime<Utc>) -> Result<(), RuntimeError> {
+        if let Some(tokens) = self.tokens.as_mut() {
+            tokens.last_request_timestamp = at;
+        }
+        match self.written_request_timestamp {
+            Some(written) if at - written < Duration::minutes(TOUCH_WRITE_MINUTES) => Ok(()),
+            _ => self.flush(),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), RuntimeError> {
+        match self.tokens.clone() {
+            Some(tokens) if self.written_request_timestamp != Some(tokens.last_request_timestamp) => self.write_tokens(&tokens),
+            _ => Ok(()),
+        }
+    }
+
+    fn read_file(&self, path: &Path) -> Result<Option<SecUtf8>, RuntimeError> {
+        self.vault.read(path)
+    }
+
+    fn write_file(&self, path: &Path, contents: &SecUtf8) -> Result<(), RuntimeError> {
+        self.vault.write(path, contents.unsecure().as_bytes())
     }
+}
 
-    fn put(&mut self, key: String, creds: Credentials) {
-        self.data.insert(key, creds);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{store, temp_dir};
+    use std::fs;
+
+    fn creds(key: &str, secret: &str) -> Credentials {
+        Credentials::new(SecUtf8::from(key), SecUtf8::from(secret))
     }
 
-    fn delete(&mut self, key: String) {
-        self.data.remove(&key);
+    #[test]
+    fn round_trips_the_token_cache() {
+        let dir = temp_dir("tokens");
+        let issued = Utc.ymd(2021, 7, 1).and_hms(14, 0, 0);
+        let mut first_run = store(&dir);
+        assert_eq!(first_run.tokens(), None);
+
+        first_run.save_tokens(creds("request-token", "request-secret"), creds("access-token", "access-secret"), SecUtf8::from("4XK2P"), issued).unwrap();
+        let saved = first_run.tokens().cloned().unwrap();
+        assert_eq!(saved.access_creds.key.unsecure(), "access-token");
+        assert_eq!(saved.verification_code.unsecure(), "4XK2P");
+        assert_eq!(saved.last_request_timestamp, issued);
+        // midnight EDT, the night after
+        assert_eq!(saved.expires_at, Utc.ymd(2021, 7, 2).and_hms(4, 0, 0));
+
+        // the next run starts from the same tokens
+        assert_eq!(store(&dir).tokens(), Some(&saved));
+
+        // requests keep them active, the next run included, a new authorization replaces them
+        first_run.touch(issued + Duration::hours(1)).unwrap();
+        assert_eq!(first_run.tokens().unwrap().last_request_timestamp, issued + Duration::hours(1));
+        assert_eq!(store(&dir).tokens(), first_run.tokens());
+        first_run.save_tokens(creds("request-token", "request-secret"), creds("renewed-token", "renewed-secret"), SecUtf8::from("4XK2P"), issued + Duration::hours(2)).unwrap();
+        assert_eq!(store(&dir).tokens().unwrap().access_creds.key.unsecure(), "renewed-token");
+
+        fs::remove_dir_all(dir).unwrap();
     }
 
-    fn get_verification_code(&self) -> String {
-        self.verification_code.to_owned()
+    #[test]
+    fn writes_request_times_a_minute_apart() {
+        let dir = temp_dir("touch");
+        let issued = Utc.ymd(2021, 7, 1).and_hms(14, 0, 0);
+        let mut first_run = store(&dir);
+        first_run.save_tokens(creds("request-token", "request-secret"), creds("access-token", "access-secret"), SecUtf8::from("4XK2P"), issued).unwrap();
+        let last_written = |dir: &Path| store(dir).tokens().unwrap().last_request_timestamp;
+
+        // the quote poll, every 5 seconds
+        first_run.touch(issued + Duration::seconds(5)).unwrap();
+        first_run.touch(issued + Duration::seconds(55)).unwrap();
+        assert_eq!(first_run.tokens().unwrap().last_request_timestamp, issued + Duration::seconds(55));
+        assert_eq!(last_written(&dir), issued);
+        first_run.touch(issued + Duration::seconds(60)).unwrap();
+        assert_eq!(last_written(&dir), issued + Duration::seconds(60));
+
+        // and what's held back goes out on shutdown
+        first_run.touch(issued + Duration::seconds(65)).unwrap();
+        first_run.flush().unwrap();
+        assert_eq!(last_written(&dir), issued + Duration::seconds(65));
+
+        fs::remove_dir_all(dir).unwrap();
     }
 
-    fn set_verification_code(&mut self, verification_code: String) {
-        self.verification_code = verification_code;
+    #[test]
+    fn starts_empty_when_the_cache_is_unreadable() {
+        let dir = temp_dir("unreadable");
+        store(&dir).save_tokens(creds("request-token", "request-secret"), creds("access-token", "access-secret"), SecUtf8::from("4XK2P"), Utc::now()).unwrap();
+
+        // sealed with a key that's since been lost
+        fs::remove_file(dir.join("key")).unwrap();
+        assert_eq!(store(&dir).tokens(), None);
+
+        fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,87 @@
+// Fixtures shared by the tests
+use crate::app::{ActiveBlock, App, PreviewOrder, RouteId, SelectedTicker, Ticker};
+use crate::clients::etrade_json_structs::{DetailFlag, MarketSession, OrderAction, OrderTerm, OrderType, PriceType};
+use crate::clients::etrade_xml_structs::Account;
+use crate::config::ConfigPaths;
+use crate::store::EncryptedStore;
+use crate::vault::Vault;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// An empty directory of the test's own, which it removes when it's done
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("stonks-terminal-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Every config file in `dir`
+pub fn config_paths(dir: &Path) -> ConfigPaths {
+    ConfigPaths {
+        config_file_path: dir.join("client.yml"),
+        token_cache_path: dir.join("token_cache.json"),
+        watch_lists_path: dir.join("watch_lists.yml"),
+        lot_selections_path: dir.join("lot_selections.yml"),
+        history_dir: dir.join("history"),
+        exports_dir: dir.join("exports"),
+        key_path: dir.join("key"),
+        salt_path: dir.join("salt"),
+    }
+}
+
+// The token cache in `dir`, as a run starting there would pick it up
+pub fn store(dir: &Path) -> EncryptedStore {
+    let config_paths = config_paths(dir);
+    EncryptedStore::new(Vault::from_key_file(&config_paths.key_path).unwrap(), config_paths.token_cache_path)
+}
+
+// An open brokerage account, keyed "<id>-key"
+pub fn account(id: &str) -> Account {
+    Account {
+        account_id: id.to_string(),
+        account_id_key: format!("{}-key", id),
+        account_mode: "CASH".to_string(),
+        account_desc: "Brokerage".to_string(),
+        account_name: "".to_string(),
+        account_type: "INDIVIDUAL".to_string(),
+        account_status: "ACTIVE".to_string(),
+        institution_type: "BROKERAGE".to_string(),
+        account_balance: None,
+    }
+}
+
+// An equity order for 10 FB, on the order form, with FB quoted at 100.00/100.50
+pub fn order_form_app(price_type: PriceType, order_action: OrderAction) -> App {
+    let mut app = App::default();
+    app.preview_order_form = Some(PreviewOrder {
+        account_id: "1".to_string(),
+        account_id_key: "1-key".to_string(),
+        client_order_id: "abc".to_string(),
+        quantity: "10".to_string(),
+        symbol: "FB".to_string(),
+        order_type: OrderType::EQ,
+        order_action,
+        price_type,
+        order_term: OrderTerm::GOOD_FOR_DAY,
+        market_session: MarketSession::REGULAR,
+        limit_price: "".to_string(),
+        stop_price: "".to_string(),
+        order_id: None,
+        option: None,
+        legs: vec![],
+        lots: vec![],
+    });
+    app.selected_ticker = Some(SelectedTicker {
+        ticker: Ticker {
+            symbol: "FB".to_string(),
+            bid: 100.00,
+            ask: 100.50,
+            ..Default::default()
+        },
+        selected_index: 0,
+        detail_flag: DetailFlag::ALL,
+    });
+    app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::OrderForm);
+    app
+}
@@ -0,0 +1,194 @@
+use crate::clients::etrade_json_structs::Transaction;
+use crate::stonks_error::RuntimeError;
+use crate::utils;
+use chrono::prelude::*;
+use chrono::Duration;
+use std::path::{Path, PathBuf};
+
+// Groups of E*TRADE's transactionType strings, which are free text like "Bought" or "Qualified Dividend"
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransactionKind {
+    Trades,
+    Dividends,
+    Interest,
+    Transfers,
+    Fees,
+}
+
+impl TransactionKind {
+    pub const ALL: [TransactionKind; 5] = [
+        TransactionKind::Trades,
+        TransactionKind::Dividends,
+        TransactionKind::Interest,
+        TransactionKind::Transfers,
+        TransactionKind::Fees,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TransactionKind::Trades => "Trades",
+            TransactionKind::Dividends => "Dividends",
+            TransactionKind::Interest => "Interest",
+            TransactionKind::Transfers => "Transfers",
+            TransactionKind::Fees => "Fees",
+        }
+    }
+
+    pub fn matches(&self, transaction_type: &str) -> bool {
+        let words: &[&str] = match self {
+            TransactionKind::Trades => &["bought", "sold", "short", "cover", "exercise", "assign", "expire"],
+            TransactionKind::Dividends => &["dividend", "reinvest", "capital gain"],
+            TransactionKind::Interest => &["interest"],
+            TransactionKind::Transfers => &["transfer", "deposit", "withdrawal", "contribution", "distribution", "journal", "wire"],
+            TransactionKind::Fees => &["fee", "commission"],
+        };
+        let transaction_type = transaction_type.to_lowercase();
+        words.iter().any(|word| transaction_type.contains(word))
+    }
+}
+
+// "2021-01-01 2021-03-31", "2021-01-01" through today, or "90d" for the last 90 days
+pub fn parse_date_range(input: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+    let input = input.trim();
+    if let Some(days) = input.strip_suffix('d').and_then(|days| days.parse::<i64>().ok()) {
+        return Ok((today - Duration::days(days), today));
+    }
+
+    let parse = |date: &str| {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("{} is not a YYYY-MM-DD date", date))
+    };
+    let dates = input.split_whitespace().collect::<Vec<&str>>();
+    let (start, end) = match dates.as_slice() {
+        [start] => (parse(start)?, today),
+        [start, end] => (parse(start)?, parse(end)?),
+        _ => return Err("Enter a start date and an optional end date, or a number of days like 90d".to_string()),
+    };
+    if start > end {
+        return Err(format!("{} is after {}", start, end));
+    }
+
+    Ok((start, end))
+}
+
+pub fn date(transaction: &Transaction) -> NaiveDate {
+    utils::date_eastern(transaction.transaction_date)
+}
+
+pub fn to_csv(transactions: &[&Transaction]) -> String {
+    let mut csv = "Date,Type,Symbol,Quantity,Price,Fee,Amount,Description,Transaction ID\n".to_string();
+    for transaction in transactions {
+        let brokerage = transaction.brokerage.clone().unwrap_or_default();
+        let fields = [
+            date(transaction).format("%Y-%m-%d").to_string(),
+            transaction.transaction_type.to_owned(),
+            brokerage.product.symbol,
+            brokerage.quantity.to_string(),
+            brokerage.price.to_string(),
+            brokerage.fee.to_string(),
+            transaction.amount.to_string(),
+            transaction.description.to_owned(),
+            transaction.transaction_id.to_string(),
+        ];
+        csv.push_str(&fields.iter().map(|field| utils::csv_field(field)).collect::<Vec<String>>().join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+pub fn export(transactions: &[&Transaction], dir: &Path, now: NaiveDateTime) -> Result<PathBuf, RuntimeError> {
+    Ok(utils::write_export(dir, "transactions", &to_csv(transactions), now)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::etrade_json_structs::TransactionListResponseJSON;
+    use crate::test_helpers::temp_dir;
+    use std::fs;
+
+    const TRANSACTIONS: &str = r#"{
+        "TransactionListResponse": {
+            "marker": "MTYxMzY4MjAwMDAwMA",
+            "moreTransactions": true,
+            "Transaction": [
+                {
+                    "transactionId": 21050100001,
+                    "accountId": "83405188",
+                    "transactionDate": 1619841600000,
+                    "amount": -1502.5,
+                    "description": "BOUGHT 10 AAPL @ 150.25",
+                    "transactionType": "Bought",
+                    "brokerage": {
+                        "product": { "symbol": "AAPL", "securityType": "EQ" },
+                        "quantity": 10,
+                        "price": 150.25,
+                        "fee": 0
+                    }
+                },
+                {
+                    "transactionId": 21042000002,
+                    "transactionDate": 1618891200000,
+                    "amount": 12.4,
+                    "description": "ORDINARY DIVIDEND, F",
+                    "transactionType": "Qualified Dividend",
+                    "brokerage": { "product": {}, "quantity": 0, "price": 0, "fee": 0 }
+                }
+            ]
+        }
+    }"#;
+
+    fn transactions() -> Vec<Transaction> {
+        let response: TransactionListResponseJSON = serde_json::from_str(TRANSACTIONS).unwrap();
+        response.transaction_list_response.transactions
+    }
+
+    #[test]
+    fn groups_transaction_types() {
+        assert!(TransactionKind::Trades.matches("Bought"));
+        assert!(TransactionKind::Trades.matches("Sold Short"));
+        assert!(TransactionKind::Dividends.matches("Qualified Dividend"));
+        assert!(TransactionKind::Interest.matches("Margin Interest"));
+        assert!(TransactionKind::Transfers.matches("Online Transfer"));
+        assert!(TransactionKind::Fees.matches("Service Fee"));
+        assert!(!TransactionKind::Trades.matches("Qualified Dividend"));
+    }
+
+    #[test]
+    fn parses_date_ranges() {
+        let today = NaiveDate::from_ymd(2021, 5, 1);
+
+        assert_eq!(
+            parse_date_range("2021-01-01 2021-03-31", today),
+            Ok((NaiveDate::from_ymd(2021, 1, 1), NaiveDate::from_ymd(2021, 3, 31)))
+        );
+        assert_eq!(parse_date_range(" 2021-04-01 ", today), Ok((NaiveDate::from_ymd(2021, 4, 1), today)));
+        assert_eq!(parse_date_range("30d", today), Ok((NaiveDate::from_ymd(2021, 4, 1), today)));
+        assert!(parse_date_range("2021-03-31 2021-01-01", today).is_err());
+        assert!(parse_date_range("last week", today).is_err());
+        assert!(parse_date_range("", today).is_err());
+    }
+
+    #[test]
+    fn writes_csv() {
+        let transactions = transactions();
+        let csv = to_csv(&transactions.iter().collect::<Vec<&Transaction>>());
+        let lines = csv.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "2021-05-01,Bought,AAPL,10,150.25,0,-1502.5,BOUGHT 10 AAPL @ 150.25,21050100001");
+        assert_eq!(lines[2], "2021-04-20,Qualified Dividend,,0,0,0,12.4,\"ORDINARY DIVIDEND, F\",21042000002");
+    }
+
+    #[test]
+    fn exports_to_a_timestamped_file() {
+        let dir = temp_dir("exports");
+        let transactions = transactions();
+        let now = NaiveDate::from_ymd(2021, 5, 2).and_hms(9, 30, 0);
+
+        let path = export(&transactions.iter().collect::<Vec<&Transaction>>(), &dir, now).unwrap();
+        assert_eq!(path, dir.join("transactions-20210502-093000.csv"));
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,194 @@
+use crate::app::{ActiveBlock, App, Indicator};
+use crate::indicators;
+use super::util::get_color;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Span, Spans},
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Wrap},
+    Frame,
+};
+
+// A named line on one of the analysis charts
+struct Line {
+    name: String,
+    points: Vec<(f64, f64)>,
+    color: Color,
+}
+
+impl Line {
+    fn new(name: String, series: &[Option<f64>], color: Color) -> Self {
+        // the latest value goes in the legend
+        let name = match series.last().copied().flatten() {
+            Some(value) => format!("{} {:.2}", name, value),
+            None => name,
+        };
+
+        Self {
+            name,
+            points: points(series),
+            color,
+        }
+    }
+
+    // a flat reference line across the chart, RSI's 30/70 or MACD's zero
+    fn level(value: f64, len: usize, color: Color) -> Self {
+        Self {
+            name: format!("{}", value),
+            points: vec![(0.0, value), ((len.max(2) - 1) as f64, value)],
+            color,
+        }
+    }
+}
+
+fn points(series: &[Option<f64>]) -> Vec<(f64, f64)> {
+    series
+        .iter()
+        .enumerate()
+        .filter_map(|(i, value)| value.map(|value| (i as f64, value)))
+        .collect()
+}
+
+fn bounds(lines: &[Line]) -> [f64; 2] {
+    let values = lines.iter().flat_map(|line| line.points.iter().map(|(_, y)| *y));
+    let (low, high) = values.fold((f64::MAX, f64::MIN), |(low, high), y| (low.min(y), high.max(y)));
+    if low > high {
+        return [0.0, 1.0];
+    }
+    [low, high]
+}
+
+pub fn draw_analysis<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let analysis = match &app.analysis {
+        Some(analysis) => analysis,
+        None => return,
+    };
+    let theme = app.user_config.theme;
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::Analysis,
+        current_route.hovered_block == ActiveBlock::Analysis,
+    );
+
+    let mut title = vec![Span::styled(
+        format!("Analysis {}  ", analysis.symbol),
+        get_color(highlight_state, theme),
+    )];
+    for indicator in Indicator::ALL.iter() {
+        let style = if app.indicators.contains(indicator) {
+            Style::default().fg(theme.active).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.inactive)
+        };
+        title.push(Span::styled(format!(" {}: {} ", indicator.key(), indicator.label()), style));
+    }
+    let block = Block::default()
+        .title(Spans::from(title))
+        .borders(Borders::ALL)
+        .border_style(get_color(highlight_state, theme));
+
+    if analysis.bars.is_empty() {
+        let message = analysis.message.to_owned().unwrap_or_default();
+        f.render_widget(Paragraph::new(message).block(block).wrap(Wrap { trim: true }), layout_chunk);
+        return;
+    }
+
+    let inner = block.inner(layout_chunk);
+    f.render_widget(block, layout_chunk);
+
+    let closes = analysis.bars.iter().map(|bar| bar.close).collect::<Vec<f64>>();
+    let periods = app.user_config.indicator_periods;
+    let len = closes.len();
+
+    let mut price_lines = vec![Line::new("Close".to_string(), &closes.iter().map(|c| Some(*c)).collect::<Vec<Option<f64>>>(), theme.text)];
+    if app.indicators.contains(&Indicator::Sma) {
+        price_lines.push(Line::new(format!("SMA({})", periods.sma), &indicators::sma(&closes, periods.sma), theme.hint));
+    }
+    if app.indicators.contains(&Indicator::Ema) {
+        price_lines.push(Line::new(format!("EMA({})", periods.ema), &indicators::ema(&closes, periods.ema), theme.hovered));
+    }
+    if app.indicators.contains(&Indicator::Bollinger) {
+        let bands = indicators::bollinger(&closes, periods.bollinger, periods.bollinger_std_devs);
+        let name = format!("BB({}, {})", periods.bollinger, periods.bollinger_std_devs);
+        price_lines.push(Line::new(format!("{} upper", name), &bands.upper, theme.active));
+        price_lines.push(Line::new(format!("{} lower", name), &bands.lower, theme.active));
+    }
+
+    // oscillators get their own pane under the price, RSI always on a 0-100 scale
+    let mut panes = vec![(price_lines, None)];
+    if app.indicators.contains(&Indicator::Rsi) {
+        let lines = vec![
+            Line::new(format!("RSI({})", periods.rsi), &indicators::rsi(&closes, periods.rsi), theme.hint),
+            Line::level(70.0, len, theme.loss),
+            Line::level(30.0, len, theme.gain),
+        ];
+        panes.push((lines, Some([0.0, 100.0])));
+    }
+    if app.indicators.contains(&Indicator::Macd) {
+        let macd = indicators::macd(&closes, periods.macd_fast, periods.macd_slow, periods.macd_signal);
+        let lines = vec![
+            Line::new(format!("MACD({}, {})", periods.macd_fast, periods.macd_slow), &macd.macd, theme.active),
+            Line::new(format!("Signal({})", periods.macd_signal), &macd.signal, theme.hovered),
+            Line::level(0.0, len, theme.inactive),
+        ];
+        panes.push((lines, None));
+    }
+
+    let oscillators = panes.len() as u16 - 1;
+    let mut constraints = vec![Constraint::Percentage(100 - oscillators * 25)];
+    constraints.extend((0..oscillators).map(|_| Constraint::Percentage(25)));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(inner);
+
+    let (first, last) = (&analysis.bars[0], &analysis.bars[len - 1]);
+    for (i, (lines, fixed_bounds)) in panes.iter().enumerate() {
+        let datasets = lines
+            .iter()
+            .map(|line| {
+                Dataset::default()
+                    .name(line.name.as_str())
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(line.color))
+                    .data(&line.points)
+            })
+            .collect::<Vec<Dataset>>();
+
+        let y_bounds = fixed_bounds.unwrap_or_else(|| bounds(lines));
+        // only the bottom pane carries dates
+        let x_labels = if i == panes.len() - 1 {
+            vec![
+                Span::raw(first.time.format("%Y-%m-%d").to_string()),
+                Span::raw(last.time.format("%Y-%m-%d").to_string()),
+            ]
+        } else {
+            vec![]
+        };
+
+        let chart = Chart::new(datasets)
+            .x_axis(
+                Axis::default()
+                    .style(Style::default().fg(theme.inactive))
+                    .bounds([0.0, (len.max(2) - 1) as f64])
+                    .labels(x_labels),
+            )
+            .y_axis(
+                Axis::default()
+                    .style(Style::default().fg(theme.inactive))
+                    .bounds(y_bounds)
+                    .labels(vec![
+                        Span::raw(format!("{:.2}", y_bounds[0])),
+                        Span::raw(format!("{:.2}", y_bounds[1])),
+                    ]),
+            )
+            .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+        f.render_widget(chart, chunks[i]);
+    }
+}
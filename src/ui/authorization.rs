@@ -0,0 +1,53 @@
+use crate::app::{ActiveBlock, App};
+use super::util::get_color;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+pub fn draw_authorization<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let theme = app.user_config.theme;
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::Authorization,
+        current_route.hovered_block == ActiveBlock::Authorization,
+    );
+    let authorization = match &app.authorization {
+        Some(authorization) => authorization,
+        None => return,
+    };
+
+    let mut text = vec![
+        Spans::from(Span::raw("E*TRADE signed this session out. Visit the link below, accept, and enter the verification code it shows.")),
+        Spans::from(Span::raw("")),
+        // left whole so it can be selected and copied from the terminal
+        Spans::from(Span::styled(authorization.authorize_url.to_owned(), Style::default().fg(theme.text).add_modifier(Modifier::BOLD))),
+        Spans::from(Span::raw("")),
+        Spans::from(Span::styled(
+            "Enter: type the code  o: open in browser  r: new link",
+            Style::default().fg(theme.hint),
+        )),
+    ];
+    if let Some(message) = &authorization.message {
+        text.push(Spans::from(Span::raw("")));
+        text.push(Spans::from(Span::styled(message.to_owned(), Style::default().fg(theme.hint))));
+    }
+
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled("Authorize E*TRADE", get_color(highlight_state, theme)))
+            .border_style(get_color(highlight_state, theme)),
+            )
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, layout_chunk);
+}
@@ -0,0 +1,124 @@
+use crate::app::{ActiveBlock, App};
+use crate::clients::etrade_xml_structs::AccountBalance;
+use super::util::get_color;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+
+// E*TRADE sends amounts as strings, shown as dollars when they parse
+fn dollars(value: Option<&str>) -> String {
+    match value.map(|value| (value, value.parse::<f64>())) {
+        Some((_, Ok(amount))) => format!("${:.2}", amount),
+        Some((value, Err(_))) => value.to_string(),
+        None => "--".to_string(),
+    }
+}
+
+// (label, value) rows under section headings, the margin section only for margin accounts
+fn balance_rows(balance: &AccountBalance, margin_account: bool) -> Vec<(&'static str, String)> {
+    let computed = match &balance.computed {
+        Some(computed) => computed,
+        None => return vec![("E*TRADE sent no computed balance for this account", String::new())],
+    };
+    let real_time = &computed.real_time_values;
+
+    let mut rows = vec![
+        ("Cash", String::new()),
+        ("  Available for investment", dollars(Some(&computed.cash_available_for_investment))),
+        ("  Available for withdrawal", dollars(Some(&computed.cash_available_for_withdrawal))),
+        ("  Cash balance", dollars(Some(&computed.cash_balance))),
+        ("  Net cash", dollars(Some(&computed.net_cash))),
+        ("  Funds for open orders", dollars(Some(&balance.cash.funds_for_open_orders_cash))),
+        ("  Money market", dollars(Some(&balance.cash.money_mkt_balance))),
+        ("Buying power", String::new()),
+        ("  Cash buying power", dollars(computed.cash_buying_power.as_deref())),
+        ("  Margin buying power", dollars(computed.margin_buying_power.as_deref())),
+        ("Market value", String::new()),
+        ("  Net market value long", dollars(Some(&real_time.net_mv_long))),
+        ("  Net market value short", dollars(real_time.net_mv_short.as_deref())),
+        ("  Net market value", dollars(Some(&real_time.net_mv))),
+        ("  Total account value", dollars(Some(&real_time.total_account_value))),
+    ];
+
+    if margin_account {
+        let margin = balance.margin.as_ref();
+        rows.extend(vec![
+            ("Margin", String::new()),
+            ("  Day trading buying power", dollars(computed.dt_margin_buying_power.as_deref())),
+            ("  Margin balance", dollars(computed.margin_balance.as_deref())),
+            ("  RegT equity", dollars(computed.regt_equity.as_deref())),
+            (
+                "  RegT equity %",
+                computed.regt_equity_percent.as_ref().map_or("--".to_string(), |percent| format!("{}%", percent)),
+            ),
+            (
+                "  Day trading cash order reserve",
+                dollars(margin.and_then(|margin| margin.dt_cash_open_order_reserve.as_deref())),
+            ),
+            (
+                "  Day trading margin order reserve",
+                dollars(margin.and_then(|margin| margin.dt_margin_open_order_reserve.as_deref())),
+            ),
+        ]);
+    }
+
+    rows
+}
+
+pub fn draw_balance<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let theme = app.user_config.theme;
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::Balance,
+        current_route.hovered_block == ActiveBlock::Balance,
+    );
+
+    let account = app.active_account();
+    let title = match account {
+        Some(account) => format!("Balance {} {} {}  (r: refresh)", account.account_desc, account.account_id, account.account_mode),
+        None => "Balance  (r: refresh)".to_string(),
+    };
+    let block = Block::default()
+        .title(Span::styled(title, get_color(highlight_state, theme)))
+        .borders(Borders::ALL)
+        .border_style(get_color(highlight_state, theme));
+
+    let (account, balance) = match account.map(|account| (account, account.account_balance.as_ref())) {
+        Some((account, Some(balance))) => (account, balance),
+        Some((_, None)) => {
+            let text = Paragraph::new("Loading balance...").block(block).wrap(Wrap { trim: true });
+            f.render_widget(text, layout_chunk);
+            return;
+        }
+        None => {
+            let text = Paragraph::new("No active account").block(block).wrap(Wrap { trim: true });
+            f.render_widget(text, layout_chunk);
+            return;
+        }
+    };
+
+    let rows = balance_rows(balance, account.account_mode == "MARGIN")
+        .into_iter()
+        .map(|(label, value)| {
+            // section headings are the rows without a value
+            let label_style = if value.is_empty() {
+                Style::default().fg(theme.active).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.text)
+            };
+            Row::new(vec![Cell::from(label).style(label_style), Cell::from(value)]).style(Style::default().fg(theme.text))
+        })
+        .collect::<Vec<Row>>();
+
+    let widths = [Constraint::Percentage(50), Constraint::Percentage(50)];
+    let table = Table::new(rows).block(block).widths(&widths);
+    f.render_widget(table, layout_chunk);
+}
@@ -5,61 +5,35 @@ use super::{
 };
 
 pub fn handler(key: Key, app: &mut App) {
+    let choices = app.account_choices();
     match key {
         k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
-        k if common_key_events::down_event(k) => {
-            match &app.user_accounts {
-                Some(p) => {
-                    if let Some(selected_account_index) = app.selected_account_index {
-                        let next_index =
-                            common_key_events::on_down_press_handler(&p, Some(selected_account_index));
-                        app.selected_account_index = Some(next_index);
-                    }
-                }
-                None => {}
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) && !choices.is_empty() => {
+            // the first press picks the first account
+            let next_index = match app.selected_account_index {
+                Some(_) => common_key_events::on_down_press_handler(&choices, app.selected_account_index),
+                None => 0,
             };
+            app.selected_account_index = Some(next_index);
         }
-        k if common_key_events::up_event(k) => {
-            match &app.user_accounts {
-                Some(p) => {
-                    let next_index =
-                        common_key_events::on_up_press_handler(&p, app.selected_account_index);
-                    app.selected_account_index = Some(next_index);
-                }
-                None => {}
-            };
+        k if common_key_events::up_event(k) && !choices.is_empty() => {
+            let next_index = common_key_events::on_up_press_handler(&choices, app.selected_account_index);
+            app.selected_account_index = Some(next_index);
         }
-        k if common_key_events::high_event(k) => {
-            match &app.user_accounts {
-                Some(_p) => {
-                    let next_index = common_key_events::on_high_press_handler();
-                    app.selected_account_index = Some(next_index);
-                }
-                None => {}
-            };
+        k if common_key_events::high_event(k) && !choices.is_empty() => {
+            app.selected_account_index = Some(common_key_events::on_high_press_handler());
         }
-        k if common_key_events::middle_event(k) => {
-            match &app.user_accounts {
-                Some(p) => {
-                    let next_index = common_key_events::on_middle_press_handler(&p);
-                    app.selected_account_index = Some(next_index);
-                }
-                None => {}
-            };
+        k if common_key_events::middle_event(k) && !choices.is_empty() => {
+            app.selected_account_index = Some(common_key_events::on_middle_press_handler(&choices));
         }
-        k if common_key_events::low_event(k) => {
-            match &app.user_accounts {
-                Some(p) => {
-                    let next_index = common_key_events::on_low_press_handler(&p);
-                    app.selected_account_index = Some(next_index);
-                }
-                None => {}
-            };
+        k if common_key_events::low_event(k) && !choices.is_empty() => {
+            app.selected_account_index = Some(common_key_events::on_low_press_handler(&choices));
         }
         Key::Enter => {
-            if let Some(selected_account_index) = &app.selected_account_index {
-                app.active_account_index = Some(*selected_account_index);
-            };
+            if let Some(choice) = app.selected_account_index.and_then(|index| choices.get(index)) {
+                app.switch_account(*choice);
+            }
         }
         _ => {}
     }
@@ -67,6 +41,147 @@ pub fn handler(key: Key, app: &mut App) {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::app::{merge_positions, PortfolioPosition, Ticker};
+    use crate::clients::etrade_xml_structs::Account;
+    use crate::test_helpers;
+
+    fn account(id: &str, institution_type: &str) -> Account {
+        Account {
+            institution_type: institution_type.to_string(),
+            ..test_helpers::account(id)
+        }
+    }
+
+    fn position(symbol: &str, quantity: f64, total_cost: f64, market_value: f64, days_gain: f64) -> PortfolioPosition {
+        PortfolioPosition {
+            ticker: Ticker {
+                symbol: symbol.to_string(),
+                security_type: "EQ".to_string(),
+                ..Default::default()
+            },
+            position_type: "LONG".to_string(),
+            quantity,
+            cost_per_share: total_cost / quantity,
+            total_cost,
+            market_value,
+            total_gain: market_value - total_cost,
+            days_gain,
+            lots_details: format!("https://api.etrade.com/v1/accounts/{}/lots", symbol),
+            ..Default::default()
+        }
+    }
+
     #[test]
-    fn test() {}
+    fn keeps_the_active_account_through_a_new_list() {
+        let mut app = App::default();
+        app.set_user_accounts(vec![account("1", "BROKERAGE"), account("2", "BROKERAGE")]);
+        assert_eq!((app.active_account_index, app.selected_account_index), (Some(0), Some(0)));
+
+        app.active_account_index = Some(1);
+        app.selected_account_index = Some(1);
+        // as after authorizing again at midnight, in another order
+        app.set_user_accounts(vec![account("3", "BROKERAGE"), account("2", "BROKERAGE"), account("1", "BROKERAGE")]);
+        assert_eq!(app.active_account().unwrap().account_id, "2");
+        assert_eq!(app.selected_account_index, Some(1));
+
+        // closed since, the first one stands in
+        app.set_user_accounts(vec![account("1", "BROKERAGE")]);
+        assert_eq!((app.active_account_index, app.selected_account_index), (Some(0), Some(0)));
+    }
+
+    #[test]
+    fn enter_switches_the_active_account() {
+        let mut app = App::default();
+        app.user_accounts = Some(vec![account("1", "BROKERAGE"), account("2", "BROKERAGE"), account("3", "BANK")]);
+        app.active_account_index = Some(0);
+        app.positions = Some(vec![position("AAPL", 1.0, 100.0, 150.0, 0.0)]);
+
+        // three accounts plus All accounts
+        assert_eq!(app.account_choices(), vec![Some(0), Some(1), Some(2), None]);
+        assert_eq!(app.portfolio_account_keys(), vec!["1-key"]);
+
+        handler(Key::Down, &mut app);
+        handler(Key::Down, &mut app);
+        handler(Key::Enter, &mut app);
+        assert_eq!(app.active_account_index, Some(1));
+        assert!(app.is_active_account(Some(1)));
+        assert_eq!(app.portfolio_account_keys(), vec!["2-key"]);
+        // stale positions are dropped until the new account's arrive
+        assert!(app.positions.is_none());
+
+        handler(Key::Char('L'), &mut app);
+        handler(Key::Enter, &mut app);
+        assert!(app.all_accounts);
+        assert!(app.is_active_account(None));
+        assert!(!app.is_active_account(Some(1)));
+        assert_eq!(app.portfolio_account_keys(), vec!["1-key", "2-key"]);
+        assert_eq!(app.portfolio_label(), "All accounts");
+
+        handler(Key::Char('H'), &mut app);
+        handler(Key::Enter, &mut app);
+        assert!(!app.all_accounts);
+        assert_eq!(app.portfolio_label(), "Brokerage 1");
+    }
+
+    #[test]
+    fn all_accounts_needs_more_than_one_brokerage_account() {
+        let mut app = App::default();
+        assert!(app.account_choices().is_empty());
+        handler(Key::Down, &mut app);
+        assert_eq!(app.selected_account_index, None);
+
+        app.user_accounts = Some(vec![account("1", "BROKERAGE"), account("2", "BANK")]);
+        assert_eq!(app.account_choices(), vec![Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn merges_positions_across_accounts() {
+        let merged = merge_positions(vec![
+            position("AAPL", 10.0, 1000.0, 1500.0, 10.0),
+            position("F", 100.0, 1200.0, 1000.0, -10.0),
+            position("AAPL", 30.0, 5000.0, 4500.0, 30.0),
+        ]);
+
+        assert_eq!(merged.len(), 2);
+        let aapl = &merged[0];
+        assert_eq!((aapl.quantity, aapl.total_cost, aapl.market_value), (40.0, 6000.0, 6000.0));
+        assert_eq!(aapl.cost_per_share, 150.0);
+        assert_eq!((aapl.total_gain, aapl.total_gain_pct), (0.0, 0.0));
+        assert!((aapl.days_gain_pct - 40.0 / 5960.0 * 100.0).abs() < 1e-9);
+        assert!((aapl.pct_of_portfolio - 6000.0 / 7000.0 * 100.0).abs() < 1e-9);
+        // lots can only be sold from one account
+        assert!(aapl.lots_details.is_empty());
+
+        let f = &merged[1];
+        assert_eq!((f.quantity, f.cost_per_share), (100.0, 12.0));
+        assert!(!f.lots_details.is_empty());
+    }
+
+    #[test]
+    fn keeps_option_contracts_apart() {
+        let option = |description: &str| PortfolioPosition {
+            ticker: Ticker {
+                symbol: "IBM".to_string(),
+                security_type: "OPTN".to_string(),
+                description: description.to_string(),
+                ..Default::default()
+            },
+            position_type: "LONG".to_string(),
+            quantity: 1.0,
+            account_id_key: "1-key".to_string(),
+            ..Default::default()
+        };
+        let merged = merge_positions(vec![
+            option("IBM Jan 21 '22 $140 Call"),
+            option("IBM Jan 21 '22 $150 Call"),
+            option("IBM Jan 21 '22 $140 Call"),
+        ]);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].quantity, 2.0);
+        // held in more than one account, a lot sell wouldn't know where to go
+        assert_eq!(merged[0].account_id_key, "");
+        assert_eq!(merged[1].account_id_key, "1-key");
+    }
 }
@@ -0,0 +1,37 @@
+use super::{
+    super::super::app::{App, Indicator},
+    super::key::Key,
+    common_key_events,
+};
+
+pub fn handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        Key::Char(c) => {
+            if let Some(indicator) = Indicator::ALL.iter().find(|indicator| indicator.key() == c) {
+                app.toggle_indicator(*indicator);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggles_indicators_by_key() {
+        let mut app = App::default();
+        assert_eq!(app.indicators, vec![Indicator::Sma, Indicator::Rsi]);
+
+        handler(Key::Char('s'), &mut app);
+        handler(Key::Char('m'), &mut app);
+        handler(Key::Char('b'), &mut app);
+        assert_eq!(app.indicators, vec![Indicator::Rsi, Indicator::Macd, Indicator::Bollinger]);
+
+        handler(Key::Char('x'), &mut app);
+        assert_eq!(app.indicators.len(), 3);
+    }
+}
@@ -0,0 +1,78 @@
+use super::{
+    super::super::app::{ActiveBlock, App},
+    super::key::Key,
+    common_key_events,
+};
+use crate::network::IoEvent;
+use crate::utils;
+
+pub fn handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        // the code goes in the input box, same as a search
+        Key::Enter => {
+            app.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
+        }
+        Key::Char('o') => {
+            if let Some(authorization) = app.authorization.as_mut() {
+                if utils::open_in_browser(&authorization.authorize_url).is_err() {
+                    authorization.message = Some("Couldn't open a browser, copy the link instead".to_string());
+                }
+            }
+        }
+        Key::Char('r') => {
+            app.dispatch(IoEvent::RefreshAuthentication);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::RouteId;
+    use anyhow::anyhow;
+
+    const AUTHORIZE_URL: &str = "https://us.etrade.com/e/t/etws/authorize?key=consumer-key&token=request-token";
+
+    #[test]
+    fn replaces_the_error_left_by_the_failed_request() {
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::Orders, ActiveBlock::Orders);
+        app.handle_error(anyhow!("E*TRADE needs you to authorize stonks-terminal again"));
+
+        app.open_authorization(AUTHORIZE_URL.to_string());
+        assert_eq!(app.get_current_route().id, RouteId::Authorization);
+        // a second failure with the same link doesn't stack another screen
+        app.open_authorization(AUTHORIZE_URL.to_string());
+        app.pop_navigation_stack();
+        assert_eq!(app.get_current_route().id, RouteId::Orders);
+
+        app.open_authorization(AUTHORIZE_URL.to_string());
+        app.close_authorization();
+        assert_eq!(app.get_current_route().id, RouteId::Orders);
+        assert_eq!(app.authorization, None);
+    }
+
+    #[test]
+    fn takes_the_verification_code_from_the_input_box() {
+        let mut app = App::default();
+        app.open_authorization(AUTHORIZE_URL.to_string());
+
+        handler(Key::Enter, &mut app);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Input);
+        crate::ui::handlers::input_handler(Key::Enter, &mut app);
+        assert_eq!(
+            app.authorization.as_ref().and_then(|a| a.message.clone()),
+            Some("Enter the verification code E*TRADE showed you".to_string())
+        );
+
+        handler(Key::Enter, &mut app);
+        app.input = " 4XK2P ".chars().collect();
+        crate::ui::handlers::input_handler(Key::Enter, &mut app);
+        assert_eq!(app.authorization.as_ref().and_then(|a| a.message.clone()), Some("Checking the code...".to_string()));
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Authorization);
+        assert!(app.input.is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+use super::{
+    super::super::app::App,
+    super::key::Key,
+    common_key_events,
+};
+use crate::network::IoEvent;
+
+pub fn handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        Key::Char('r') => {
+            app.dispatch(IoEvent::GetAccountBalance);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ActiveBlock, RouteId};
+    use crate::clients::etrade_xml_structs::{Account, AccountBalance};
+    use crate::test_helpers::account;
+
+    const MARGIN_BALANCE: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<BalanceResponse>
+  <accountId>83405188</accountId>
+  <accountType>MARGIN</accountType>
+  <Cash>
+    <fundsForOpenOrdersCash>0</fundsForOpenOrdersCash>
+    <moneyMktBalance>0</moneyMktBalance>
+  </Cash>
+  <Margin>
+    <dtCashOpenOrderReserve>0</dtCashOpenOrderReserve>
+    <dtMarginOpenOrderReserve>125.5</dtMarginOpenOrderReserve>
+  </Margin>
+  <Computed>
+    <cashAvailableForInvestment>1500.25</cashAvailableForInvestment>
+    <cashAvailableForWithdrawal>1400</cashAvailableForWithdrawal>
+    <netCash>1500.25</netCash>
+    <cashBalance>1500.25</cashBalance>
+    <cashBuyingPower>1500.25</cashBuyingPower>
+    <marginBuyingPower>3000.5</marginBuyingPower>
+    <dtMarginBuyingPower>6001</dtMarginBuyingPower>
+    <marginBalance>0</marginBalance>
+    <regtEquity>9000</regtEquity>
+    <regtEquityPercent>100</regtEquityPercent>
+    <RealTimeValues>
+      <totalAccountValue>10500.25</totalAccountValue>
+      <netMv>9000</netMv>
+      <netMvLong>9500</netMvLong>
+      <netMvShort>-500</netMvShort>
+    </RealTimeValues>
+  </Computed>
+</BalanceResponse>"#;
+
+    const CASH_BALANCE: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<BalanceResponse>
+  <accountId>83405189</accountId>
+  <Cash>
+    <fundsForOpenOrdersCash>0</fundsForOpenOrdersCash>
+    <moneyMktBalance>0</moneyMktBalance>
+  </Cash>
+  <Computed>
+    <cashAvailableForInvestment>200</cashAvailableForInvestment>
+    <cashAvailableForWithdrawal>200</cashAvailableForWithdrawal>
+    <netCash>200</netCash>
+    <cashBalance>200</cashBalance>
+    <RealTimeValues>
+      <totalAccountValue>1200</totalAccountValue>
+      <netMv>1000</netMv>
+      <netMvLong>1000</netMvLong>
+    </RealTimeValues>
+  </Computed>
+</BalanceResponse>"#;
+
+    #[test]
+    fn parses_margin_fields() {
+        let balance: AccountBalance = serde_xml_rs::from_reader(MARGIN_BALANCE.as_bytes()).unwrap();
+        let computed = balance.computed.as_ref().unwrap();
+
+        assert_eq!(computed.margin_buying_power.as_deref(), Some("3000.5"));
+        assert_eq!(computed.dt_margin_buying_power.as_deref(), Some("6001"));
+        assert_eq!(computed.regt_equity_percent.as_deref(), Some("100"));
+        assert_eq!(computed.real_time_values.net_mv_short.as_deref(), Some("-500"));
+        assert_eq!(balance.margin.unwrap().dt_margin_open_order_reserve.as_deref(), Some("125.5"));
+
+        // cash accounts leave them out
+        let balance: AccountBalance = serde_xml_rs::from_reader(CASH_BALANCE.as_bytes()).unwrap();
+        let computed = balance.computed.as_ref().unwrap();
+        assert_eq!(computed.cash_available_for_investment, "200");
+        assert_eq!(computed.margin_buying_power, None);
+        assert_eq!(balance.margin, None);
+    }
+
+    #[test]
+    fn balance_is_for_the_active_account() {
+        let mut app = App::default();
+        assert!(app.active_account().is_none());
+
+        let account = |id: &str, balance: &str| Account {
+            account_mode: "MARGIN".to_string(),
+            account_balance: serde_xml_rs::from_reader(balance.as_bytes()).ok(),
+            ..account(id)
+        };
+        app.user_accounts = Some(vec![account("83405188", MARGIN_BALANCE), account("83405189", CASH_BALANCE)]);
+        app.active_account_index = Some(1);
+
+        let balance = app.active_account().and_then(|account| account.account_balance.as_ref()).unwrap();
+        assert_eq!(balance.account_id, "83405189");
+
+        app.push_navigation_stack(RouteId::Balance, ActiveBlock::Balance);
+        handler(Key::Char('r'), &mut app);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Balance);
+    }
+}
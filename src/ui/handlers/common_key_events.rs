@@ -1,4 +1,4 @@
-use crate::app::{ActiveBlock, App, OrderFormState, RouteId};
+use crate::app::{ActiveBlock, App, RouteId};
 use super::super::key::Key;
 
 pub fn down_event(key: Key) -> bool {
@@ -94,14 +94,43 @@ pub fn handle_right_event(app: &mut App) {
                 app.set_current_route_state(None, Some(ActiveBlock::TickerDetail));
             }
             RouteId::OrderForm => {
-                if let OrderFormState::Quantity = app.order_form_state {
+                if app.order_form_state.is_input() {
                     app.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
                 } else {
                     app.set_current_route_state(None, Some(ActiveBlock::OrderForm));
                 }
             }
+            RouteId::Orders => {
+                app.set_current_route_state(None, Some(ActiveBlock::Orders));
+            }
+            RouteId::OptionChain => {
+                app.set_current_route_state(None, Some(ActiveBlock::OptionChain));
+            }
+            RouteId::Spread => {
+                app.set_current_route_state(None, Some(ActiveBlock::Spread));
+            }
             RouteId::Error => {}
-            RouteId::Analysis => {}
+            RouteId::Analysis => {
+                app.set_current_route_state(None, Some(ActiveBlock::Analysis));
+            }
+            RouteId::Portfolio => {
+                app.set_current_route_state(None, Some(ActiveBlock::Positions));
+            }
+            RouteId::TaxLots => {
+                app.set_current_route_state(None, Some(ActiveBlock::TaxLots));
+            }
+            RouteId::Balance => {
+                app.set_current_route_state(None, Some(ActiveBlock::Balance));
+            }
+            RouteId::Transactions => {
+                app.set_current_route_state(None, Some(ActiveBlock::Transactions));
+            }
+            RouteId::RealizedGains => {
+                app.set_current_route_state(None, Some(ActiveBlock::RealizedGains));
+            }
+            RouteId::Authorization => {
+                app.set_current_route_state(None, Some(ActiveBlock::Authorization));
+            }
             _ => {}
         },
         _ => {}
@@ -0,0 +1,83 @@
+use super::super::super::app::App;
+use crate::ui::key::Key;
+use crate::network::IoEvent;
+
+pub fn handler(key: Key, app: &mut App) {
+    if key == Key::Enter {
+        place_order(app);
+    }
+}
+
+fn place_order(app: &mut App) {
+    if app.place_order_response.is_some() {
+        // order already placed, Enter dismisses the confirmation
+        app.finish_order();
+    } else if app.preview_order_response.is_some() && !app.placing_order {
+        app.placing_order = true;
+        app.dispatch(IoEvent::SubmitPlaceOrderRequest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ActiveBlock, RouteId};
+    use crate::config::UserConfig;
+    use std::sync::mpsc;
+
+    #[test]
+    fn dismiss_after_order_placed() {
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::TickerDetail, ActiveBlock::TickerDetail);
+        app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::OrderForm);
+        app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::OrderForm);
+        app.push_navigation_stack(RouteId::ConfirmOrderForm, ActiveBlock::ConfirmOrderForm);
+        app.place_order_response = Some(serde_json::from_str(r#"{
+            "orderType": "EQ",
+            "accountId": "835649790",
+            "MessageList": {
+                "Message": [{ "description": "Your order was successfully entered", "code": 1026, "type": "WARNING" }]
+            },
+            "Order": [{
+                "orderTerm": "GOOD_FOR_DAY",
+                "priceType": "MARKET",
+                "stopPrice": 0,
+                "marketSession": "REGULAR",
+                "estimatedCommission": 0,
+                "Instrument": [{
+                    "orderAction": "BUY",
+                    "quantityType": "QUANTITY",
+                    "quantity": 10,
+                    "Product": { "symbol": "FB", "securityType": "EQ" }
+                }]
+            }],
+            "OrderIds": [{ "orderId": 12 }],
+            "orderId": 12
+        }"#).unwrap());
+
+        handler(Key::Enter, &mut app);
+
+        assert!(app.place_order_response.is_none());
+        assert_eq!(app.get_current_route().id, RouteId::TickerDetail);
+    }
+
+    #[test]
+    fn places_the_order_once() {
+        let (io_tx, io_rx) = mpsc::channel();
+        let mut app = App::new(io_tx, UserConfig::new());
+        app.push_navigation_stack(RouteId::ConfirmOrderForm, ActiveBlock::ConfirmOrderForm);
+        app.preview_order_response = Some(serde_json::from_str(r#"{
+            "orderType": "EQ",
+            "accountId": "835649790",
+            "previewTime": 0,
+            "Order": [],
+            "PreviewIds": [{ "previewId": 1 }]
+        }"#).unwrap());
+
+        handler(Key::Enter, &mut app);
+        // a second Enter while the request is out is dropped
+        handler(Key::Enter, &mut app);
+        assert!(app.placing_order);
+        assert_eq!(io_rx.try_iter().count(), 1);
+    }
+}
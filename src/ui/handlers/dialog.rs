@@ -2,6 +2,7 @@ use super::{
     super::super::app::{ActiveBlock, App, DialogContext},
     super::key::Key,
 };
+use crate::network::IoEvent;
 
 pub fn handler(key: Key, app: &mut App) {
     match key {
@@ -11,6 +12,7 @@ pub fn handler(key: Key, app: &mut App) {
                     if let ActiveBlock::Dialog(d) = route.active_block {
                         match d {
                             DialogContext::TickerDetail => handle_ticker_detail(app),
+                            DialogContext::CancelOrder => handle_cancel_order(app),
                         }
                     }
                 }
@@ -19,6 +21,12 @@ pub fn handler(key: Key, app: &mut App) {
         Key::Char('q') => {
             app.pop_navigation_stack();
         }
+        Key::Char('y') => {
+            app.confirm = true;
+        }
+        Key::Char('n') => {
+            app.confirm = false;
+        }
         Key::Right => app.confirm = !app.confirm,
         Key::Left => app.confirm = !app.confirm,
         _ => {}
@@ -29,3 +37,11 @@ fn handle_ticker_detail(app: &mut App) {
     // transition to order page
     todo!();
 }
+
+fn handle_cancel_order(app: &mut App) {
+    app.dialog = None;
+    app.confirm = false;
+    if let Some(order_id) = app.selected_order().map(|order| order.order_id) {
+        app.dispatch(IoEvent::CancelOrder(order_id));
+    }
+}
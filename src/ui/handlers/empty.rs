@@ -27,7 +27,10 @@ pub fn handler(key: Key, app: &mut App) {
     k if common_key_events::left_event(k) => match app.get_current_route().hovered_block {
       ActiveBlock::Home
       | ActiveBlock::TickerDetail
-      | ActiveBlock::OrderForm => {
+      | ActiveBlock::OrderForm
+      | ActiveBlock::Orders
+      | ActiveBlock::OptionChain
+      | ActiveBlock::Spread => {
         app.set_current_route_state(None, Some(ActiveBlock::WatchList));
       }
       _ => {}
@@ -1,5 +1,5 @@
 use super::{
-    super::super::app::{App},
+    super::super::app::{ActiveBlock, App, RouteId},
     super::key::Key,
 };
 use crate::network::IoEvent;
@@ -13,9 +13,24 @@ pub fn handler(key: Key, app: &mut App) {
             // app.push_navigation_stack(RouteId::Notifications, ActiveBlock::Notifications);
             // app.push_navigation_stack(RouteId::Notifications, ActiveBlock::Notifications);
         }
-        // k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
-        // k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
-        _ => {}
+        Key::Char('O') => {
+            app.dispatch(IoEvent::GetOrders);
+            app.push_navigation_stack(RouteId::Orders, ActiveBlock::Orders);
+        }
+        Key::Char('B') => {
+            app.dispatch(IoEvent::GetAccountBalance);
+            app.push_navigation_stack(RouteId::Balance, ActiveBlock::Balance);
+        }
+        Key::Char('T') => {
+            app.dispatch(IoEvent::GetTransactions);
+            app.push_navigation_stack(RouteId::Transactions, ActiveBlock::Transactions);
+        }
+        Key::Char('G') => {
+            app.dispatch(IoEvent::GetRealizedGains);
+            app.push_navigation_stack(RouteId::RealizedGains, ActiveBlock::RealizedGains);
+        }
+        // the home screen is the account list
+        _ => super::account_list::handler(key, app),
     }
 }
 
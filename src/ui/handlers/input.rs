@@ -1,8 +1,9 @@
 extern crate unicode_width;
 
-use super::super::super::app::{ActiveBlock, App, OrderFormState, RouteId};
+use super::super::super::app::{ActiveBlock, App, OrderFormState, RouteId, Ticker};
 use crate::ui::key::Key;
 use crate::network::IoEvent;
+use crate::{transactions, utils};
 use std::convert::TryInto;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
@@ -74,7 +75,11 @@ pub fn handler(key: Key, app: &mut App) {
                 // handler - app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::OrderForm);
                 app.pop_navigation_stack();
                 app.pop_navigation_stack();
+            } else if app.order_form_state.is_input() {
+                clear_input(app);
+                app.previous_order_form_state();
             } else {
+                app.library.naming_watch_list = false;
                 app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::WatchList));
             }
         }
@@ -105,6 +110,80 @@ pub fn handler(key: Key, app: &mut App) {
 }
 
 fn process_input(app: &mut App, input: String) {
+    // The orders blotter reuses the input box as its symbol filter, empty input clears it
+    if app.get_current_route().id == RouteId::Orders {
+        app.orders_symbol_filter = match input.trim() {
+            "" => None,
+            symbol => Some(symbol.to_uppercase()),
+        };
+        app.selected_order_index = 0;
+        clear_input(app);
+        app.set_current_route_state(Some(ActiveBlock::Orders), Some(ActiveBlock::Orders));
+        app.dispatch(IoEvent::GetOrders);
+        return;
+    }
+
+    // and the transactions screen as its date range
+    if app.get_current_route().id == RouteId::Transactions {
+        match transactions::parse_date_range(&input, utils::today_eastern()) {
+            Ok((start_date, end_date)) => {
+                app.transactions.set_date_range(start_date, end_date);
+                app.transactions.message = None;
+                app.dispatch(IoEvent::GetTransactions);
+            }
+            Err(e) => {
+                app.transactions.message = Some(e);
+            }
+        }
+        clear_input(app);
+        app.set_current_route_state(Some(ActiveBlock::Transactions), Some(ActiveBlock::Transactions));
+        return;
+    }
+
+    // and the authorization screen as the verification code from E*TRADE
+    if app.get_current_route().id == RouteId::Authorization {
+        let verification_code = input.trim().to_string();
+        if let Some(authorization) = app.authorization.as_mut() {
+            if verification_code.is_empty() {
+                authorization.message = Some("Enter the verification code E*TRADE showed you".to_string());
+            } else {
+                authorization.message = Some("Checking the code...".to_string());
+                app.dispatch(IoEvent::CompleteAuthorization(verification_code));
+            }
+        }
+        clear_input(app);
+        app.set_current_route_state(Some(ActiveBlock::Authorization), Some(ActiveBlock::Authorization));
+        return;
+    }
+
+    // and so does the realized gains report
+    if app.get_current_route().id == RouteId::RealizedGains {
+        match transactions::parse_date_range(&input, utils::today_eastern()) {
+            Ok((start_date, end_date)) => {
+                app.realized_gains.set_date_range(start_date, end_date);
+                app.realized_gains.message = None;
+                app.dispatch(IoEvent::GetRealizedGains);
+            }
+            Err(e) => {
+                app.realized_gains.message = Some(e);
+            }
+        }
+        clear_input(app);
+        app.set_current_route_state(Some(ActiveBlock::RealizedGains), Some(ActiveBlock::RealizedGains));
+        return;
+    }
+
+    if app.library.naming_watch_list {
+        app.library.naming_watch_list = false;
+        clear_input(app);
+        app.set_current_route_state(Some(ActiveBlock::WatchList), Some(ActiveBlock::WatchList));
+        if !input.trim().is_empty() {
+            app.library.selected_index = 0;
+            app.dispatch(IoEvent::CreateWatchList(input));
+        }
+        return;
+    }
+
     // Don't do anything if there is no input
     if input.is_empty() {
         return;
@@ -115,12 +194,22 @@ fn process_input(app: &mut App, input: String) {
         RouteId::OrderForm => {
             match app.order_form_state {
                 OrderFormState::Quantity => {
+                    let unit = app.preview_order_form.as_ref().map_or("shares", |order_form| order_form.quantity_unit());
+                    if input.parse::<u32>().map_or(true, |quantity| quantity == 0) {
+                        app.order_form_error = Some(format!("{} is not a valid number of {}", input, unit));
+                        return;
+                    }
+                    let lots_quantity = app.preview_order_form.as_ref().and_then(|order_form| order_form.lots_quantity());
+                    if let Some(lots_quantity) = lots_quantity.filter(|lots_quantity| input.parse::<f64>().unwrap_or_default() > *lots_quantity) {
+                        app.order_form_error = Some(format!("The selected lots only have {} {}", lots_quantity, unit));
+                        return;
+                    }
                     app.add_next_order_field("quantity", input);
-                    app.order_form_state = OrderFormState::Submit;
-                    app.input = vec![];
-                    app.input_idx = 0;
-                    app.input_cursor_position = 0;
-                    app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::OrderForm);
+                    clear_input(app);
+                    app.next_order_form_state();
+                }
+                OrderFormState::LimitPrice | OrderFormState::StopPrice => {
+                    process_order_price(app, input);
                 }
                 _ => {}
             }
@@ -136,6 +225,52 @@ fn process_input(app: &mut App, input: String) {
     }
 }
 
+fn process_order_price(app: &mut App, input: String) {
+    let price = match input.parse::<f64>() {
+        Ok(price) => price,
+        Err(_) => {
+            app.order_form_error = Some(format!("{} is not a valid price", input));
+            return;
+        }
+    };
+
+    // a change started from the blotter may not have a quote for its symbol loaded
+    let check = match (&app.preview_order_form, &app.selected_ticker) {
+        (Some(order_form), _) if order_form.is_option() => {
+            order_form.check_price(&app.order_form_state, price, &order_form.option_quote().unwrap_or_default())
+        }
+        (Some(order_form), Some(selected_ticker)) if selected_ticker.ticker.symbol == order_form.symbol => {
+            order_form.check_price(&app.order_form_state, price, &selected_ticker.ticker)
+        }
+        (Some(order_form), _) => order_form.check_price(&app.order_form_state, price, &Ticker::default()),
+        _ => return,
+    };
+
+    match check {
+        Ok(warning) => {
+            let field = match app.order_form_state {
+                OrderFormState::LimitPrice => "limit_price",
+                _ => "stop_price",
+            };
+            app.add_next_order_field(field, input);
+            if warning.is_some() {
+                app.order_form_warning = warning;
+            }
+            clear_input(app);
+            app.next_order_form_state();
+        }
+        Err(e) => {
+            app.order_form_error = Some(e);
+        }
+    }
+}
+
+fn clear_input(app: &mut App) {
+    app.input = vec![];
+    app.input_idx = 0;
+    app.input_cursor_position = 0;
+}
+
 fn compute_character_width(character: char) -> u16 {
     UnicodeWidthChar::width(character)
         .unwrap()
@@ -146,6 +281,9 @@ fn compute_character_width(character: char) -> u16 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::OptionContract;
+    use crate::clients::etrade_json_structs::{CallPut, ExpirationDate, OrderAction, PriceType};
+    use crate::test_helpers::order_form_app;
 
     fn str_to_vec_char(s: &str) -> Vec<char> {
         String::from(s).chars().collect()
@@ -158,6 +296,21 @@ mod tests {
         assert_eq!(1, compute_character_width('ç'));
     }
 
+    #[test]
+    fn test_input_handler_names_new_watch_list() {
+        let mut app = App::default();
+        app.library.naming_watch_list = true;
+        app.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
+        app.input = str_to_vec_char("Tech");
+
+        handler(Key::Enter, &mut app);
+
+        assert!(!app.library.naming_watch_list);
+        assert_eq!(app.input, str_to_vec_char(""));
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::WatchList);
+        assert_eq!(app.get_current_route().id, RouteId::Home);
+    }
+
     #[test]
     fn test_input_handler_clear_input_on_ctrl_l() {
         let mut app = App::default();
@@ -243,10 +396,10 @@ mod tests {
     }
 
     #[test]
-    fn test_input_handler_esc_back_to_playlist() {
+    fn test_input_handler_esc_back_to_watch_list() {
         let mut app = App::default();
 
-        app.set_current_route_state(Some(ActiveBlock::MyPlaylists), None);
+        app.set_current_route_state(Some(ActiveBlock::WatchList), None);
         handler(Key::Esc, &mut app);
 
         let current_route = app.get_current_route();
@@ -373,37 +526,97 @@ mod tests {
         assert_eq!(app.input_cursor_position, 4);
     }
 
-    mod test_uri_parsing {
-        use super::*;
+    #[test]
+    fn test_input_handler_rejects_quantity_in_contracts() {
+        let mut app = order_form_app(PriceType::LIMIT, OrderAction::BUY_OPEN);
+        app.preview_order_form.as_mut().unwrap().option = Some(OptionContract {
+            call_put: CallPut::CALL,
+            strike_price: 140.0,
+            expiration_date: ExpirationDate { year: 2018, month: 8, day: 17, expiry_type: "MONTHLY".to_string() },
+            display_symbol: "FB Aug 17 '18 $140 Call".to_string(),
+            bid: 3.1,
+            ask: 3.3,
+        });
+        app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::Input);
+        app.order_form_state = OrderFormState::Quantity;
+        app.input = str_to_vec_char("1.5");
+
+        handler(Key::Enter, &mut app);
+
+        assert_eq!(app.order_form_state, OrderFormState::Quantity);
+        assert_eq!(app.order_form_error, Some("1.5 is not a valid number of contracts".to_string()));
+    }
 
-        const URI_BASE: &str = "etrade:";
-        const URL_BASE: &str = "https://open.etrade.com/";
+    #[test]
+    fn test_input_handler_limit_price_advances_to_order_term() {
+        let mut app = order_form_app(PriceType::LIMIT, OrderAction::BUY);
+        app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::Input);
+        app.order_form_state = OrderFormState::LimitPrice;
+        app.input = str_to_vec_char("99.5");
 
-        fn check_uri_parse(expected_id: &str, parsed: (String, bool)) {
-            assert_eq!(parsed.1, true);
-            assert_eq!(parsed.0, expected_id);
-        }
+        handler(Key::Enter, &mut app);
+
+        assert_eq!(app.order_form_state, OrderFormState::OrderTerm);
+        assert_eq!(app.preview_order_form.as_ref().unwrap().limit_price, "99.5");
+        assert_eq!(app.order_form_warning, None);
+        assert_eq!(app.input, str_to_vec_char(""));
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::OrderForm);
+    }
 
-        fn run_test_for_id_and_resource_type(id: &str, resource_type: &str) {
-            check_uri_parse(
-                id,
-                etrade_resource_id(
-                    URI_BASE,
-                    &format!("etrade:{}:{}", resource_type, id),
-                    ":",
-                    resource_type,
-                    ),
-                    );
-            check_uri_parse(
-                id,
-                etrade_resource_id(
-                    URL_BASE,
-                    &format!("https://open.etrade.com/{}/{}", resource_type, id),
-                    "/",
-                    resource_type,
-                    ),
-                    )
+    #[test]
+    fn test_input_handler_marketable_limit_warns() {
+        let mut app = order_form_app(PriceType::LIMIT, OrderAction::BUY);
+        app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::Input);
+        app.order_form_state = OrderFormState::LimitPrice;
+        app.input = str_to_vec_char("101");
+
+        handler(Key::Enter, &mut app);
+
+        assert_eq!(app.order_form_state, OrderFormState::OrderTerm);
+        assert!(app.order_form_warning.is_some());
+    }
+
+    #[test]
+    fn test_input_handler_rejects_prices_that_are_not_numbers() {
+        for input in ["NaN", "inf", "-inf", "0"] {
+            let mut app = order_form_app(PriceType::LIMIT, OrderAction::SELL);
+            app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::Input);
+            app.order_form_state = OrderFormState::LimitPrice;
+            app.input = str_to_vec_char(input);
+
+            handler(Key::Enter, &mut app);
+
+            assert_eq!(app.order_form_state, OrderFormState::LimitPrice);
+            assert_eq!(app.order_form_error, Some("Price must be greater than 0".to_string()));
+            assert_eq!(app.preview_order_form.as_ref().unwrap().limit_price, "");
         }
     }
-}
 
+    #[test]
+    fn test_input_handler_rejects_buy_stop_below_ask() {
+        let mut app = order_form_app(PriceType::STOP, OrderAction::BUY);
+        app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::Input);
+        app.order_form_state = OrderFormState::StopPrice;
+        app.input = str_to_vec_char("99");
+
+        handler(Key::Enter, &mut app);
+
+        assert_eq!(app.order_form_state, OrderFormState::StopPrice);
+        assert_eq!(app.order_form_error, Some("Buy stop must be above the ask of $100.50".to_string()));
+        assert_eq!(app.preview_order_form.as_ref().unwrap().stop_price, "");
+    }
+
+    #[test]
+    fn test_input_handler_rejects_sell_stop_limit_above_stop() {
+        let mut app = order_form_app(PriceType::STOP_LIMIT, OrderAction::SELL);
+        app.preview_order_form.as_mut().unwrap().limit_price = "96".to_string();
+        app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::Input);
+        app.order_form_state = OrderFormState::StopPrice;
+        app.input = str_to_vec_char("95");
+
+        handler(Key::Enter, &mut app);
+
+        assert_eq!(app.order_form_state, OrderFormState::StopPrice);
+        assert!(app.order_form_error.is_some());
+    }
+}
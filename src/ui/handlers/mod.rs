@@ -1,17 +1,28 @@
+mod analysis;
+mod authorization;
+mod balance;
 mod common_key_events;
+mod confirm_order_form;
 mod dialog;
 mod empty;
 mod home;
 mod input;
 mod notification;
+mod option_chain;
 mod portfolio;
+mod positions;
+mod realized_gains;
+mod tax_lots;
 mod order_form;
+mod orders;
 mod watch_list;
 mod account_list;
 mod search_results;
+mod spread;
 mod ticker_detail;
+mod transactions;
 
-use crate::app::{ActiveBlock, App, RouteId, OrderFormState};
+use crate::app::{ActiveBlock, App, RouteId};
 use super::key::Key;
 
 pub use input::handler as input_handler;
@@ -63,12 +74,45 @@ fn handle_block_events(key: Key, app: &mut App) {
         ActiveBlock::Portfolio => {
             portfolio::handler(key, app);
         }
+        ActiveBlock::Positions => {
+            positions::handler(key, app);
+        }
+        ActiveBlock::TaxLots => {
+            tax_lots::handler(key, app);
+        }
         ActiveBlock::AccountList => {
             account_list::handler(key, app);
         }
         ActiveBlock::OrderForm => {
             order_form::handler(key, app);
         }
+        ActiveBlock::ConfirmOrderForm => {
+            confirm_order_form::handler(key, app);
+        }
+        ActiveBlock::Orders => {
+            orders::handler(key, app);
+        }
+        ActiveBlock::OptionChain => {
+            option_chain::handler(key, app);
+        }
+        ActiveBlock::Spread => {
+            spread::handler(key, app);
+        }
+        ActiveBlock::Analysis => {
+            analysis::handler(key, app);
+        }
+        ActiveBlock::Balance => {
+            balance::handler(key, app);
+        }
+        ActiveBlock::Transactions => {
+            transactions::handler(key, app);
+        }
+        ActiveBlock::RealizedGains => {
+            realized_gains::handler(key, app);
+        }
+        ActiveBlock::Authorization => {
+            authorization::handler(key, app);
+        }
         ActiveBlock::Notifications => {
             notification::handler(key, app);
         }
@@ -89,7 +133,7 @@ fn handle_escape(app: &mut App) {
             app.search_results.selected_ticker_index = None;
             app.pop_navigation_stack();
         }
-        ActiveBlock::Notifications | ActiveBlock::NotificationDetail => {
+        ActiveBlock::Notifications | ActiveBlock::NotificationDetail | ActiveBlock::Orders | ActiveBlock::OptionChain | ActiveBlock::Spread | ActiveBlock::Analysis | ActiveBlock::Positions | ActiveBlock::TaxLots | ActiveBlock::Balance | ActiveBlock::Transactions | ActiveBlock::RealizedGains => {
             app.pop_navigation_stack();
         }
         ActiveBlock::TickerDetail => {
@@ -103,14 +147,16 @@ fn handle_escape(app: &mut App) {
                 }
         }
         ActiveBlock::OrderForm => {
-            match app.order_form_state {
-                OrderFormState::Submit => {
-                    app.order_form_state = OrderFormState::Quantity;
-                    app.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
-                }
-                _ => ()
+            app.previous_order_form_state();
+        }
+        ActiveBlock::ConfirmOrderForm => {
+            if app.place_order_response.is_some() {
+                app.finish_order();
+            } else {
+                // back to the order form to adjust before previewing again
+                app.preview_order_response = None;
+                app.pop_navigation_stack();
             }
-            app.pop_navigation_stack();
         }
         ActiveBlock::Dialog(_) => {
             app.pop_navigation_stack();
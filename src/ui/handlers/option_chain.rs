@@ -0,0 +1,198 @@
+use super::{
+    super::super::app::{ActiveBlock, App, RouteId},
+    super::key::Key,
+    common_key_events,
+};
+use crate::clients::etrade_json_structs::OrderAction;
+use crate::network::IoEvent;
+
+pub fn handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            if let Some(ref mut selected) = app.selected_option_chain {
+                selected.selected_index = common_key_events::on_down_press_handler(
+                    &selected.option_chain.option_pairs,
+                    Some(selected.selected_index),
+                );
+            }
+        }
+        k if common_key_events::up_event(k) => {
+            if let Some(ref mut selected) = app.selected_option_chain {
+                selected.selected_index = common_key_events::on_up_press_handler(
+                    &selected.option_chain.option_pairs,
+                    Some(selected.selected_index),
+                );
+            }
+        }
+        k if common_key_events::high_event(k) => {
+            if let Some(ref mut selected) = app.selected_option_chain {
+                selected.selected_index = common_key_events::on_high_press_handler();
+            }
+        }
+        k if common_key_events::middle_event(k) => {
+            if let Some(ref mut selected) = app.selected_option_chain {
+                selected.selected_index = common_key_events::on_middle_press_handler(&selected.option_chain.option_pairs);
+            }
+        }
+        k if common_key_events::low_event(k) => {
+            if let Some(ref mut selected) = app.selected_option_chain {
+                selected.selected_index = match selected.option_chain.option_pairs.len() {
+                    0 => 0,
+                    _ => common_key_events::on_low_press_handler(&selected.option_chain.option_pairs),
+                };
+            }
+        }
+        Key::Char(']') | Key::Char('[') => {
+            let forward = key == Key::Char(']');
+            if app.cycle_option_expiry(forward) {
+                app.dispatch(IoEvent::RefreshOptionChain);
+            }
+        }
+        Key::Char('r') => {
+            app.dispatch(IoEvent::RefreshOptionChain);
+        }
+        Key::Char('t') => {
+            app.toggle_option_side();
+        }
+        Key::Char('B') => {
+            app.new_option_order(OrderAction::BUY_OPEN);
+        }
+        Key::Char('S') => {
+            app.new_option_order(OrderAction::SELL_CLOSE);
+        }
+        Key::Char('a') => {
+            app.add_spread_leg();
+        }
+        Key::Char('v') => {
+            app.push_navigation_stack(RouteId::Spread, ActiveBlock::Spread);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{OptionChain, OrderFormState};
+    use crate::clients::etrade_json_structs::{CallPut, ExpirationDate, OptionChainResponseJSON, OrderType, PreviewOrderRequest};
+    use crate::test_helpers::account;
+
+    fn app_with_option_chain() -> App {
+        let response: OptionChainResponseJSON = serde_json::from_str(r#"{
+            "OptionChainResponse": {
+                "OptionPair": [
+                    {
+                        "Call": { "optionType": "CALL", "strikePrice": 120, "symbol": "IBM", "bid": 21.1, "ask": 21.4, "inTheMoney": "y",
+                                  "OptionGreeks": { "delta": 0.91, "gamma": 0.01, "theta": -0.02, "vega": 0.05, "rho": 0.03, "iv": 0.25 } },
+                        "Put": { "optionType": "PUT", "strikePrice": 120, "symbol": "IBM", "bid": 0.4, "ask": 0.5, "inTheMoney": "n" }
+                    },
+                    {
+                        "Call": { "optionType": "CALL", "strikePrice": 140, "symbol": "IBM", "bid": 3.1, "ask": 3.3, "inTheMoney": "n" },
+                        "Put": { "optionType": "PUT", "strikePrice": 140, "symbol": "IBM", "bid": 2.2, "ask": 2.4, "inTheMoney": "y" }
+                    }
+                ],
+                "timeStamp": 1529430420,
+                "quoteType": "DELAYED",
+                "nearPrice": 138.5,
+                "SelectedED": { "month": 8, "year": 2018, "day": 17 }
+            }
+        }"#).unwrap();
+
+        let expiration_dates = vec![
+            ExpirationDate { year: 2018, month: 8, day: 17, expiry_type: "MONTHLY".to_string() },
+            ExpirationDate { year: 2018, month: 9, day: 21, expiry_type: "MONTHLY".to_string() },
+        ];
+        let mut option_chain = OptionChain::new("IBM".to_string(), expiration_dates);
+        option_chain.update(response.option_chain_response);
+
+        let mut app = App::default();
+        app.set_option_chain(option_chain);
+        app.push_navigation_stack(RouteId::OptionChain, ActiveBlock::OptionChain);
+        app
+    }
+
+    #[test]
+    fn opens_near_the_money() {
+        let app = app_with_option_chain();
+        let selected = app.selected_option_chain.as_ref().unwrap();
+
+        assert_eq!(selected.selected_index, 1);
+        assert_eq!(selected.option_chain.option_pairs[0].strike_price(), 120.0);
+        assert!(selected.option_chain.option_pairs[0].call.as_ref().unwrap().is_in_the_money());
+    }
+
+    #[test]
+    fn moves_between_strikes() {
+        let mut app = app_with_option_chain();
+
+        handler(Key::Down, &mut app);
+        assert_eq!(app.selected_option_chain.as_ref().unwrap().selected_index, 0);
+        handler(Key::Up, &mut app);
+        assert_eq!(app.selected_option_chain.as_ref().unwrap().selected_index, 1);
+    }
+
+    #[test]
+    fn buy_put_from_option_chain() {
+        let mut app = app_with_option_chain();
+        app.user_accounts = Some(vec![account("84345")]);
+        app.active_account_index = Some(0);
+
+        handler(Key::Char('t'), &mut app);
+        handler(Key::Char('B'), &mut app);
+
+        assert_eq!(app.order_form_state, OrderFormState::OptionAction);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::OrderForm);
+        let order_form = app.preview_order_form.clone().unwrap();
+        assert_eq!(order_form.order_type, OrderType::OPTN);
+        assert_eq!(order_form.order_action, OrderAction::BUY_OPEN);
+        assert_eq!(order_form.symbol, "IBM");
+
+        let request: PreviewOrderRequest = order_form.into();
+        let product = &request.preview_order_request.order[0].instrument[0].product;
+        assert_eq!(product.security_type, "OPTN");
+        assert_eq!(product.call_put, Some("PUT".to_string()));
+        assert_eq!(product.strike_price, Some(140.0));
+        assert_eq!((product.expiry_year, product.expiry_month, product.expiry_day), (Some(2018), Some(8), Some(17)));
+    }
+
+    #[test]
+    fn add_spread_legs() {
+        let mut app = app_with_option_chain();
+
+        handler(Key::Char('a'), &mut app);
+        handler(Key::Char('t'), &mut app);
+        handler(Key::Char('a'), &mut app);
+
+        assert_eq!(app.spread_legs.len(), 2);
+        assert_eq!(app.spread_legs[0].contract.call_put, CallPut::CALL);
+        assert_eq!(app.spread_legs[1].contract.call_put, CallPut::PUT);
+        assert_eq!(app.selected_spread_leg_index, 1);
+
+        assert_eq!(app.spread_legs[1].symbol, "IBM");
+
+        handler(Key::Char('v'), &mut app);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Spread);
+
+        // another expiration of the same chain keeps them, another underlying starts over
+        app.set_option_chain(OptionChain::new("IBM".to_string(), vec![]));
+        assert_eq!(app.spread_legs.len(), 2);
+        app.set_option_chain(OptionChain::new("AAPL".to_string(), vec![]));
+        assert!(app.spread_legs.is_empty());
+        assert_eq!(app.selected_spread_leg_index, 0);
+    }
+
+    #[test]
+    fn cycles_expiration_dates() {
+        let mut app = app_with_option_chain();
+
+        handler(Key::Char(']'), &mut app);
+        let option_chain = &app.selected_option_chain.as_ref().unwrap().option_chain;
+        assert_eq!(option_chain.selected_expiry().unwrap().to_string(), "2018-09-21");
+
+        handler(Key::Char(']'), &mut app);
+        let option_chain = &app.selected_option_chain.as_ref().unwrap().option_chain;
+        assert_eq!(option_chain.selected_expiry().unwrap().to_string(), "2018-08-17");
+    }
+}
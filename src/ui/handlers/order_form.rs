@@ -1,6 +1,7 @@
 extern crate unicode_width;
 
 use super::super::super::app::{App, OrderFormState};
+use super::common_key_events;
 use crate::ui::key::Key;
 use crate::network::IoEvent;
 
@@ -9,12 +10,126 @@ pub fn handler(key: Key, app: &mut App) {
         Key::Enter => {
             process_order(app);
         }
+        k if common_key_events::down_event(k) && app.order_form_state.is_select() => {
+            app.cycle_order_form_option(true);
+        }
+        k if common_key_events::up_event(k) && app.order_form_state.is_select() => {
+            app.cycle_order_form_option(false);
+        }
         _ => {}
     }
 }
 
 fn process_order(app: &mut App) {
-    if let OrderFormState::Submit = app.order_form_state {
-        app.dispatch(IoEvent::SubmitPreviewRequest);
+    match app.order_form_state {
+        OrderFormState::OptionAction | OrderFormState::PriceType | OrderFormState::OrderTerm => app.next_order_form_state(),
+        OrderFormState::MarketSession => {
+            let check = app.preview_order_form.as_ref().map(|order_form| order_form.check_session());
+            match check {
+                Some(Err(e)) => app.order_form_error = Some(e),
+                Some(Ok(_)) => app.next_order_form_state(),
+                None => {}
+            }
+        }
+        OrderFormState::Submit => app.dispatch(IoEvent::SubmitPreviewRequest),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ActiveBlock, OptionContract};
+    use crate::clients::etrade_json_structs::{CallPut, ExpirationDate, MarketSession, OrderAction, OrderTerm, OrderType, PriceType};
+    use crate::test_helpers::order_form_app;
+
+    #[test]
+    fn cycle_order_term() {
+        let mut app = order_form_app(PriceType::MARKET, OrderAction::BUY);
+        app.order_form_state = OrderFormState::OrderTerm;
+
+        handler(Key::Char('j'), &mut app);
+        assert_eq!(app.preview_order_form.as_ref().unwrap().order_term, OrderTerm::GOOD_UNTIL_CANCEL);
+
+        handler(Key::Char('k'), &mut app);
+        handler(Key::Char('k'), &mut app);
+        assert_eq!(app.preview_order_form.as_ref().unwrap().order_term, OrderTerm::FILL_OR_KILL);
+
+        handler(Key::Enter, &mut app);
+        assert_eq!(app.order_form_state, OrderFormState::MarketSession);
+    }
+
+    #[test]
+    fn extended_session_requires_limit() {
+        let mut app = order_form_app(PriceType::MARKET, OrderAction::BUY);
+        app.order_form_state = OrderFormState::MarketSession;
+
+        handler(Key::Char('j'), &mut app);
+        assert_eq!(app.preview_order_form.as_ref().unwrap().market_session, MarketSession::EXTENDED);
+
+        handler(Key::Enter, &mut app);
+        assert_eq!(app.order_form_state, OrderFormState::MarketSession);
+        assert!(app.order_form_error.is_some());
+
+        app.preview_order_form.as_mut().unwrap().price_type = PriceType::LIMIT;
+        handler(Key::Enter, &mut app);
+        assert_eq!(app.order_form_state, OrderFormState::Submit);
+        assert!(app.order_form_error.is_none());
+    }
+
+    fn option_order_app() -> App {
+        let mut app = order_form_app(PriceType::MARKET, OrderAction::BUY);
+        let order_form = app.preview_order_form.as_mut().unwrap();
+        order_form.order_type = OrderType::OPTN;
+        order_form.order_action = OrderAction::BUY_OPEN;
+        order_form.quantity = "2".to_string();
+        order_form.option = Some(OptionContract {
+            call_put: CallPut::CALL,
+            strike_price: 140.0,
+            expiration_date: ExpirationDate { year: 2018, month: 8, day: 17, expiry_type: "MONTHLY".to_string() },
+            display_symbol: "FB Aug 17 '18 $140 Call".to_string(),
+            bid: 3.1,
+            ask: 3.3,
+        });
+        app.order_form_state = OrderFormState::OptionAction;
+        app
+    }
+
+    #[test]
+    fn cycle_option_action() {
+        let mut app = option_order_app();
+
+        handler(Key::Char('j'), &mut app);
+        assert_eq!(app.preview_order_form.as_ref().unwrap().order_action, OrderAction::SELL_CLOSE);
+
+        handler(Key::Enter, &mut app);
+        assert_eq!(app.order_form_state, OrderFormState::Quantity);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Input);
+    }
+
+    #[test]
+    fn option_premium_uses_multiplier() {
+        let mut app = option_order_app();
+        let order_form = app.preview_order_form.as_mut().unwrap();
+
+        // market buy is priced at the ask
+        assert_eq!(order_form.estimated_premium(), Some(2.0 * 100.0 * 3.3));
+
+        order_form.price_type = PriceType::LIMIT;
+        order_form.limit_price = "3.2".to_string();
+        assert_eq!(order_form.estimated_premium(), Some(2.0 * 100.0 * 3.2));
+    }
+
+    #[test]
+    fn option_orders_only_trade_regular_session() {
+        let mut app = option_order_app();
+        let order_form = app.preview_order_form.as_mut().unwrap();
+        order_form.price_type = PriceType::LIMIT;
+        order_form.market_session = MarketSession::EXTENDED;
+        app.order_form_state = OrderFormState::MarketSession;
+
+        handler(Key::Enter, &mut app);
+        assert_eq!(app.order_form_state, OrderFormState::MarketSession);
+        assert!(app.order_form_error.is_some());
     }
 }
@@ -0,0 +1,171 @@
+use super::{
+    super::super::app::{ActiveBlock, App, DialogContext, RouteId},
+    super::key::Key,
+    common_key_events,
+};
+use crate::network::IoEvent;
+
+pub fn handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            if let Some(orders) = &app.orders {
+                let next_index =
+                    common_key_events::on_down_press_handler(orders, Some(app.selected_order_index));
+                app.selected_order_index = next_index;
+            }
+        }
+        k if common_key_events::up_event(k) => {
+            if let Some(orders) = &app.orders {
+                let next_index =
+                    common_key_events::on_up_press_handler(orders, Some(app.selected_order_index));
+                app.selected_order_index = next_index;
+            }
+        }
+        k if common_key_events::high_event(k) => {
+            app.selected_order_index = common_key_events::on_high_press_handler();
+        }
+        k if common_key_events::middle_event(k) => {
+            if let Some(orders) = &app.orders {
+                app.selected_order_index = common_key_events::on_middle_press_handler(orders);
+            }
+        }
+        k if common_key_events::low_event(k) => {
+            if let Some(orders) = &app.orders {
+                if !orders.is_empty() {
+                    app.selected_order_index = common_key_events::on_low_press_handler(orders);
+                }
+            }
+        }
+        Key::Char('s') => {
+            app.cycle_orders_status_filter();
+            app.dispatch(IoEvent::GetOrders);
+        }
+        Key::Char('r') => {
+            app.dispatch(IoEvent::GetOrders);
+        }
+        Key::Char('e') => {
+            if let Some(order_id) = app.selected_order().map(|order| order.order_id) {
+                if let Err(e) = app.change_order(order_id) {
+                    app.orders_message = Some(e);
+                }
+            }
+        }
+        Key::Char('c') => {
+            let cancellable = app.selected_order().and_then(|order| {
+                match order.order_detail.first() {
+                    Some(detail) if detail.status == "OPEN" || detail.status == "PARTIAL" => Some(order.order_id),
+                    _ => None,
+                }
+            });
+
+            match cancellable {
+                Some(order_id) => {
+                    app.dialog = Some(format!("Cancel order #{}?", order_id));
+                    app.confirm = false;
+                    app.push_navigation_stack(RouteId::Orders, ActiveBlock::Dialog(DialogContext::CancelOrder));
+                }
+                None => {
+                    app.orders_message = Some("Only open orders can be cancelled".to_string());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::etrade_json_structs::StatusType;
+    use crate::test_helpers::account;
+
+    fn app_with_orders(status: &str) -> App {
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::Orders, ActiveBlock::Orders);
+        app.orders = Some(serde_json::from_str(&format!(r#"[{{
+            "orderId": 481,
+            "orderType": "EQ",
+            "OrderDetail": [{{
+                "placedTime": 1613770519226,
+                "status": "{}",
+                "orderTerm": "GOOD_FOR_DAY",
+                "priceType": "LIMIT",
+                "limitPrice": 99.5,
+                "marketSession": "REGULAR",
+                "Instrument": [{{
+                    "Product": {{ "symbol": "FB", "securityType": "EQ" }},
+                    "orderAction": "BUY",
+                    "quantityType": "QUANTITY",
+                    "orderedQuantity": 10,
+                    "filledQuantity": 0
+                }}]
+            }}]
+        }}]"#, status)).unwrap());
+        app
+    }
+
+    #[test]
+    fn cancel_open_order_asks_for_confirmation() {
+        let mut app = app_with_orders("OPEN");
+
+        handler(Key::Char('c'), &mut app);
+
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Dialog(DialogContext::CancelOrder));
+        assert_eq!(app.dialog, Some("Cancel order #481?".to_string()));
+    }
+
+    #[test]
+    fn cancel_executed_order_is_refused() {
+        let mut app = app_with_orders("EXECUTED");
+
+        handler(Key::Char('c'), &mut app);
+
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Orders);
+        assert!(app.orders_message.is_some());
+    }
+
+    #[test]
+    fn change_open_order_prefills_form() {
+        let mut app = app_with_orders("OPEN");
+        app.user_accounts = Some(vec![account("84345")]);
+        app.active_account_index = Some(0);
+
+        handler(Key::Char('e'), &mut app);
+
+        let order_form = app.preview_order_form.as_ref().unwrap();
+        assert_eq!(order_form.order_id, Some(481));
+        assert_eq!(order_form.quantity, "10");
+        assert_eq!(order_form.limit_price, "99.5");
+        assert_eq!(app.input, "10".chars().collect::<Vec<char>>());
+        assert_eq!(app.get_current_route().id, RouteId::OrderForm);
+        assert!(app.order_form_changes().is_empty());
+
+        app.add_next_order_field("limit_price", "98".to_string());
+        assert_eq!(app.order_form_changes(), vec![("Limit price", "99.5".to_string(), "98".to_string())]);
+    }
+
+    #[test]
+    fn change_without_account_is_refused() {
+        let mut app = app_with_orders("OPEN");
+
+        handler(Key::Char('e'), &mut app);
+
+        assert!(app.preview_order_form.is_none());
+        assert!(app.orders_message.is_some());
+    }
+
+    #[test]
+    fn status_filter_cycles_through_all() {
+        let mut app = App::default();
+        app.orders_status_filter = None;
+
+        for _ in 0..StatusType::FILTERS.len() {
+            app.cycle_orders_status_filter();
+            assert!(app.orders_status_filter.is_some());
+        }
+        app.cycle_orders_status_filter();
+        assert_eq!(app.orders_status_filter, None);
+    }
+}
@@ -1,5 +1,5 @@
 use super::{
-    super::super::app::{App},
+    super::super::app::{ActiveBlock, App, RouteId},
     super::key::Key,
     common_key_events,
 };
@@ -9,7 +9,7 @@ pub fn handler(key: Key, app: &mut App) {
     match key {
         k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
         k if common_key_events::down_event(k) => {
-            match &app.portfolio_tickers {
+            match &app.positions {
                 Some(p) => {
                     if let Some(selected_watch_list_index) = app.selected_watch_list_index {
                         let next_index =
@@ -21,7 +21,7 @@ pub fn handler(key: Key, app: &mut App) {
             };
         }
         k if common_key_events::up_event(k) => {
-            match &app.portfolio_tickers {
+            match &app.positions {
                 Some(p) => {
                     let next_index =
                         common_key_events::on_up_press_handler(&p, app.selected_watch_list_index);
@@ -31,7 +31,7 @@ pub fn handler(key: Key, app: &mut App) {
             };
         }
         k if common_key_events::high_event(k) => {
-            match &app.portfolio_tickers {
+            match &app.positions {
                 Some(_p) => {
                     let next_index = common_key_events::on_high_press_handler();
                     app.selected_watch_list_index = Some(next_index);
@@ -40,7 +40,7 @@ pub fn handler(key: Key, app: &mut App) {
             };
         }
         k if common_key_events::middle_event(k) => {
-            match &app.portfolio_tickers {
+            match &app.positions {
                 Some(p) => {
                     let next_index = common_key_events::on_middle_press_handler(&p);
                     app.selected_watch_list_index = Some(next_index);
@@ -49,7 +49,7 @@ pub fn handler(key: Key, app: &mut App) {
             };
         }
         k if common_key_events::low_event(k) => {
-            match &app.portfolio_tickers {
+            match &app.positions {
                 Some(p) => {
                     let next_index = common_key_events::on_low_press_handler(&p);
                     app.selected_watch_list_index = Some(next_index);
@@ -58,16 +58,21 @@ pub fn handler(key: Key, app: &mut App) {
             };
         }
         Key::Enter => {
-            if let (Some(tickers), Some(selected_watch_list_index)) =
-                (&app.portfolio_tickers, &app.selected_watch_list_index)
+            if let (Some(positions), Some(selected_watch_list_index)) =
+                (&app.positions, &app.selected_watch_list_index)
                 {
                     app.active_ticker_index = Some(selected_watch_list_index.to_owned());
-                    if let Some(selected_ticker) = tickers.get(selected_watch_list_index.to_owned()) {
-                        let ticker_id = selected_ticker.symbol.to_owned();
+                    if let Some(position) = positions.get(selected_watch_list_index.to_owned()) {
+                        let ticker_id = position.ticker.symbol.to_owned();
                         app.dispatch(IoEvent::GetTicker(ticker_id));
                     }
                 };
         }
+        // the full positions table
+        Key::Char('P') => {
+            app.push_navigation_stack(RouteId::Portfolio, ActiveBlock::Positions);
+            app.dispatch(IoEvent::GetPortfolio);
+        }
         _ => {}
     }
 }
@@ -0,0 +1,162 @@
+use super::{
+    super::super::app::App,
+    super::key::Key,
+    common_key_events,
+};
+use crate::network::IoEvent;
+
+pub fn handler(key: Key, app: &mut App) {
+    let positions = app.positions.clone().unwrap_or_default();
+    match key {
+        k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            app.selected_position_index =
+                common_key_events::on_down_press_handler(&positions, Some(app.selected_position_index));
+        }
+        k if common_key_events::up_event(k) => {
+            app.selected_position_index =
+                common_key_events::on_up_press_handler(&positions, Some(app.selected_position_index));
+        }
+        k if common_key_events::high_event(k) => {
+            app.selected_position_index = common_key_events::on_high_press_handler();
+        }
+        k if common_key_events::middle_event(k) && !positions.is_empty() => {
+            app.selected_position_index = common_key_events::on_middle_press_handler(&positions);
+        }
+        k if common_key_events::low_event(k) && !positions.is_empty() => {
+            app.selected_position_index = common_key_events::on_low_press_handler(&positions);
+        }
+        Key::Char('s') => {
+            let sort = app.next_positions_sort();
+            app.sort_positions_by(sort);
+        }
+        Key::Char('S') => {
+            app.sort_positions_by(app.positions_sort);
+        }
+        Key::Char('r') => {
+            app.dispatch(IoEvent::GetPortfolio);
+        }
+        Key::Char('t') if app.selected_position().is_some() => {
+            app.dispatch(IoEvent::GetPositionLots);
+        }
+        Key::Enter => {
+            if let Some(position) = app.selected_position() {
+                let symbol = position.ticker.symbol.to_owned();
+                app.dispatch(IoEvent::GetTicker(symbol));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{PortfolioPosition, PortfolioTotals, PositionSort};
+    use crate::clients::etrade_xml_structs::PortfolioXML;
+
+    const PORTFOLIO: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<PortfolioResponse>
+  <AccountPortfolio>
+    <accountId>83405188</accountId>
+    <Position>
+      <positionId>1</positionId>
+      <Product><symbol>AAPL</symbol><securityType>EQ</securityType></Product>
+      <symbolDescription>APPLE INC COM</symbolDescription>
+      <positionType>LONG</positionType>
+      <quantity>10</quantity>
+      <pricePaid>120</pricePaid>
+      <costPerShare>120</costPerShare>
+      <totalCost>1200</totalCost>
+      <marketValue>1500</marketValue>
+      <totalGain>300</totalGain>
+      <totalGainPct>25</totalGainPct>
+      <daysGain>-20</daysGain>
+      <daysGainPct>-1.31</daysGainPct>
+      <pctOfPortfolio>60</pctOfPortfolio>
+      <Quick><lastTrade>150</lastTrade><change>-2</change><changePct>-1.31</changePct></Quick>
+      <lotsDetails>https://api.etrade.com/v1/accounts/abc/portfolio/1</lotsDetails>
+    </Position>
+    <Position>
+      <positionId>2</positionId>
+      <Product><symbol>F</symbol><securityType>EQ</securityType></Product>
+      <symbolDescription>FORD MOTOR CO</symbolDescription>
+      <positionType>LONG</positionType>
+      <quantity>100</quantity>
+      <costPerShare>12</costPerShare>
+      <totalCost>1200</totalCost>
+      <marketValue>1000</marketValue>
+      <totalGain>-200</totalGain>
+      <totalGainPct>-16.67</totalGainPct>
+      <daysGain>10</daysGain>
+      <pctOfPortfolio>40</pctOfPortfolio>
+    </Position>
+    <totalPages>1</totalPages>
+  </AccountPortfolio>
+</PortfolioResponse>"#;
+
+    fn app_with_positions() -> App {
+        let portfolio: PortfolioXML = serde_xml_rs::from_reader(PORTFOLIO.as_bytes()).unwrap();
+        let mut app = App::default();
+        app.set_positions(portfolio.account_portfolio.positions.into_iter().map(|p| p.into()).collect());
+        app
+    }
+
+    fn symbols(app: &App) -> Vec<String> {
+        app.positions.iter().flatten().map(|p| p.ticker.symbol.to_owned()).collect()
+    }
+
+    #[test]
+    fn parses_positions_and_totals() {
+        let app = app_with_positions();
+        let positions = app.positions.as_ref().unwrap();
+
+        let aapl = &positions[0];
+        assert_eq!((aapl.quantity, aapl.cost_per_share, aapl.market_value), (10.0, 120.0, 1500.0));
+        assert_eq!(aapl.ticker.price(), 150.0);
+        // positions without a Quick block still parse
+        assert_eq!(positions[1].ticker.price(), 0.0);
+
+        let totals = PortfolioTotals::new(positions);
+        assert_eq!(totals.market_value, 2500.0);
+        assert_eq!(totals.total_gain, 100.0);
+        assert!((totals.total_gain_pct - 100.0 / 2400.0 * 100.0).abs() < 1e-9);
+        assert_eq!(totals.days_gain, -10.0);
+        assert_eq!(PortfolioTotals::new(&[]), PortfolioTotals::default());
+    }
+
+    #[test]
+    fn sorts_by_column() {
+        let mut app = app_with_positions();
+        assert_eq!(app.positions_sort, PositionSort::MarketValue);
+        assert_eq!(symbols(&app), vec!["AAPL", "F"]);
+
+        handler(Key::Char('S'), &mut app);
+        assert_eq!(symbols(&app), vec!["F", "AAPL"]);
+
+        handler(Key::Char('s'), &mut app);
+        assert_eq!(app.positions_sort, PositionSort::DaysGain);
+        assert!(app.positions_sort_descending);
+        assert_eq!(symbols(&app), vec!["F", "AAPL"]);
+
+        app.sort_positions_by(PositionSort::Symbol);
+        assert!(!app.positions_sort_descending);
+        assert_eq!(symbols(&app), vec!["AAPL", "F"]);
+    }
+
+    #[test]
+    fn moves_selection() {
+        let mut app = app_with_positions();
+
+        handler(Key::Char('L'), &mut app);
+        assert_eq!(app.selected_position().map(|p| p.ticker.symbol.as_str()), Some("F"));
+        handler(Key::Down, &mut app);
+        assert_eq!(app.selected_position_index, 0);
+
+        // a smaller refresh keeps the selection in range
+        app.set_positions(vec![PortfolioPosition::default()]);
+        handler(Key::Char('L'), &mut app);
+        assert_eq!(app.selected_position_index, 0);
+    }
+}
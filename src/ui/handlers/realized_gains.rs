@@ -0,0 +1,98 @@
+use super::{
+    super::super::app::App,
+    super::key::Key,
+    common_key_events,
+};
+use crate::network::IoEvent;
+
+pub fn handler(key: Key, app: &mut App) {
+    let summaries = app.realized_gains.summaries.clone();
+    match key {
+        k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            app.realized_gains.selected_index =
+                common_key_events::on_down_press_handler(&summaries, Some(app.realized_gains.selected_index));
+        }
+        k if common_key_events::up_event(k) => {
+            app.realized_gains.selected_index =
+                common_key_events::on_up_press_handler(&summaries, Some(app.realized_gains.selected_index));
+        }
+        k if common_key_events::high_event(k) => {
+            app.realized_gains.selected_index = common_key_events::on_high_press_handler();
+        }
+        k if common_key_events::middle_event(k) && !summaries.is_empty() => {
+            app.realized_gains.selected_index = common_key_events::on_middle_press_handler(&summaries);
+        }
+        k if common_key_events::low_event(k) && !summaries.is_empty() => {
+            app.realized_gains.selected_index = common_key_events::on_low_press_handler(&summaries);
+        }
+        Key::Char('m') => {
+            app.realized_gains.toggle_matching();
+        }
+        Key::Char('r') => {
+            app.dispatch(IoEvent::GetRealizedGains);
+        }
+        Key::Char('x') => {
+            app.dispatch(IoEvent::ExportRealizedGains);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ActiveBlock, RouteId};
+    use crate::realized_gains::{Fill, LotMatching, LotSelection, LotSelections, Side};
+    use chrono::NaiveDate;
+
+    fn fill(id: i64, date: NaiveDate, side: Side, quantity: f64, amount: f64) -> Fill {
+        Fill { id, date, symbol: "AAPL".to_string(), security_type: "EQ".to_string(), side, quantity, amount }
+    }
+
+    #[test]
+    fn toggles_the_matching_method() {
+        let mut app = App::default();
+        let fills = vec![
+            fill(1, NaiveDate::from_ymd(2020, 1, 2), Side::Buy, 10.0, 1000.0),
+            fill(2, NaiveDate::from_ymd(2020, 6, 1), Side::Buy, 10.0, 2000.0),
+            fill(3, NaiveDate::from_ymd(2021, 3, 1), Side::Sell, 10.0, 2500.0),
+        ];
+        let mut selections = LotSelections::new();
+        selections.insert(3, vec![LotSelection { buy: 2, quantity: 10.0 }]);
+        app.realized_gains.set_fills(fills, selections, vec![]);
+
+        assert_eq!(app.realized_gains.summaries.len(), 2);
+        assert_eq!(app.realized_gains.selected().map(|s| s.long_term), Some(1500.0));
+
+        handler(Key::Char('m'), &mut app);
+        assert_eq!(app.realized_gains.matching, LotMatching::SpecificLots);
+        assert_eq!(app.realized_gains.selected().map(|s| (s.short_term, s.long_term)), Some((500.0, 0.0)));
+
+        handler(Key::Down, &mut app);
+        assert_eq!(app.realized_gains.selected().and_then(|s| s.symbol.clone()), Some("AAPL".to_string()));
+        assert_eq!(app.realized_gains.selected_gains().len(), 1);
+
+        handler(Key::Char('m'), &mut app);
+        assert_eq!(app.realized_gains.matching, LotMatching::Fifo);
+    }
+
+    #[test]
+    fn input_sets_the_date_range() {
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::RealizedGains, ActiveBlock::RealizedGains);
+        app.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
+
+        app.input = "2020-01-01 2020-12-31".chars().collect();
+        crate::ui::handlers::input_handler(Key::Enter, &mut app);
+        assert_eq!(app.realized_gains.start_date, Some(NaiveDate::from_ymd(2020, 1, 1)));
+        assert_eq!(app.realized_gains.end_date, Some(NaiveDate::from_ymd(2020, 12, 31)));
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::RealizedGains);
+
+        app.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
+        app.input = "next year".chars().collect();
+        crate::ui::handlers::input_handler(Key::Enter, &mut app);
+        assert!(app.realized_gains.message.is_some());
+    }
+}
@@ -57,6 +57,16 @@ pub fn handler(key: Key, app: &mut App) {
                 None => {}
             };
         }
+        Key::Char('w') => {
+            if let (Some(tickers), Some(selected_ticker_index)) =
+                (&app.search_results.tickers, app.search_results.selected_ticker_index)
+            {
+                if let Some(selected_ticker) = tickers.get(selected_ticker_index) {
+                    let symbol = selected_ticker.symbol.to_owned();
+                    app.toggle_watch_list_symbol(symbol);
+                }
+            }
+        }
         Key::Enter => {
             if let (Some(tickers), Some(selected_ticker_index)) =
                 (&app.search_results.tickers, &app.search_results.selected_ticker_index)
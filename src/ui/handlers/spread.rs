@@ -0,0 +1,119 @@
+use super::{
+    super::super::app::App,
+    super::key::Key,
+    common_key_events,
+};
+
+pub fn handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            app.selected_spread_leg_index =
+                common_key_events::on_down_press_handler(&app.spread_legs, Some(app.selected_spread_leg_index));
+        }
+        k if common_key_events::up_event(k) => {
+            app.selected_spread_leg_index =
+                common_key_events::on_up_press_handler(&app.spread_legs, Some(app.selected_spread_leg_index));
+        }
+        Key::Enter => {
+            app.cycle_spread_leg_action();
+        }
+        Key::Char('+') => {
+            app.change_spread_leg_ratio(true);
+        }
+        Key::Char('-') => {
+            app.change_spread_leg_ratio(false);
+        }
+        Key::Char('d') => {
+            app.remove_spread_leg();
+        }
+        Key::Char('P') => {
+            app.new_spread_order();
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ActiveBlock, OptionChain, OptionContract, OrderFormState, RouteId, SelectedOptionChain};
+    use crate::clients::etrade_json_structs::{CallPut, ExpirationDate, OrderAction, OrderType, PriceType, PreviewOrderRequest};
+    use crate::test_helpers::account;
+    use crate::spread::OptionLeg;
+
+    fn leg(call_put: CallPut, strike_price: f64, bid: f64, ask: f64) -> OptionLeg {
+        OptionLeg::new("IBM".to_string(), OptionContract {
+            call_put,
+            strike_price,
+            expiration_date: ExpirationDate { year: 2021, month: 6, day: 18, expiry_type: "MONTHLY".to_string() },
+            display_symbol: format!("IBM Jun 18 '21 ${} {}", strike_price, call_put.to_string()),
+            bid,
+            ask,
+        })
+    }
+
+    fn spread_app() -> App {
+        let mut app = App::default();
+        app.user_accounts = Some(vec![account("84345")]);
+        app.active_account_index = Some(0);
+        app.selected_option_chain = Some(SelectedOptionChain {
+            option_chain: OptionChain::new("IBM".to_string(), vec![]),
+            selected_index: 0,
+            call_put: CallPut::CALL,
+        });
+        app.spread_legs = vec![
+            leg(CallPut::CALL, 100.0, 4.8, 5.0),
+            leg(CallPut::CALL, 110.0, 2.0, 2.2),
+        ];
+        app.push_navigation_stack(RouteId::Spread, ActiveBlock::Spread);
+        app
+    }
+
+    #[test]
+    fn edit_legs() {
+        let mut app = spread_app();
+
+        handler(Key::Down, &mut app);
+        handler(Key::Enter, &mut app);
+        assert_eq!(app.spread_legs[1].order_action, OrderAction::SELL_CLOSE);
+        handler(Key::Enter, &mut app);
+        assert_eq!(app.spread_legs[1].order_action, OrderAction::SELL_OPEN);
+
+        handler(Key::Char('+'), &mut app);
+        assert_eq!(app.spread_legs[1].ratio, 2);
+        handler(Key::Char('-'), &mut app);
+        handler(Key::Char('-'), &mut app);
+        assert_eq!(app.spread_legs[1].ratio, 1);
+
+        handler(Key::Char('d'), &mut app);
+        assert_eq!(app.spread_legs.len(), 1);
+        assert_eq!(app.selected_spread_leg_index, 0);
+    }
+
+    #[test]
+    fn place_spread_builds_multi_leg_order() {
+        let mut app = spread_app();
+        app.spread_legs[1].order_action = OrderAction::SELL_OPEN;
+
+        handler(Key::Char('P'), &mut app);
+
+        assert_eq!(app.order_form_state, OrderFormState::Quantity);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Input);
+        let mut order_form = app.preview_order_form.clone().unwrap();
+        assert_eq!(order_form.order_type, OrderType::SPREADS);
+        assert_eq!(order_form.price_type, PriceType::NET_DEBIT);
+
+        order_form.quantity = "3".to_string();
+        order_form.limit_price = "2.9".to_string();
+        let request: PreviewOrderRequest = order_form.into();
+        let order = &request.preview_order_request.order[0];
+        assert_eq!(order.price_type, "NET_DEBIT");
+        assert_eq!(order.limit_price, "2.9");
+        assert_eq!(order.instrument.len(), 2);
+        assert_eq!(order.instrument[1].order_action, "SELL_OPEN");
+        assert_eq!(order.instrument[1].quantity, "3");
+        assert_eq!(order.instrument[1].product.strike_price, Some(110.0));
+    }
+}
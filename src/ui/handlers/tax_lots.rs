@@ -0,0 +1,163 @@
+use super::{
+    super::super::app::App,
+    super::key::Key,
+    common_key_events,
+};
+
+pub fn handler(key: Key, app: &mut App) {
+    let position_lots = match app.position_lots.as_mut() {
+        Some(position_lots) => position_lots,
+        None => return,
+    };
+    let lots = position_lots.lots.clone();
+    match key {
+        k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            position_lots.selected_index =
+                common_key_events::on_down_press_handler(&lots, Some(position_lots.selected_index));
+        }
+        k if common_key_events::up_event(k) => {
+            position_lots.selected_index =
+                common_key_events::on_up_press_handler(&lots, Some(position_lots.selected_index));
+        }
+        k if common_key_events::high_event(k) => {
+            position_lots.selected_index = common_key_events::on_high_press_handler();
+        }
+        k if common_key_events::middle_event(k) && !lots.is_empty() => {
+            position_lots.selected_index = common_key_events::on_middle_press_handler(&lots);
+        }
+        k if common_key_events::low_event(k) && !lots.is_empty() => {
+            position_lots.selected_index = common_key_events::on_low_press_handler(&lots);
+        }
+        // pick lots to sell
+        Key::Char(' ') => {
+            position_lots.toggle_selected();
+        }
+        Key::Char('S') => {
+            if let Err(e) = app.sell_position_lots() {
+                if let Some(position_lots) = app.position_lots.as_mut() {
+                    position_lots.message = Some(e);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ActiveBlock, OrderFormState, PositionLots, TaxLot};
+    use crate::clients::etrade_json_structs::{Lot, OrderAction, PreviewOrderRequest};
+    use crate::clients::etrade_xml_structs::PositionLotsXML;
+    use crate::test_helpers::account;
+    use crate::utils::HoldingTerm;
+    use chrono::NaiveDate;
+
+    const LOTS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<PositionLotsResponse>
+  <PositionLot>
+    <positionId>140357348131</positionId>
+    <positionLotId>1</positionLotId>
+    <price>100.5</price>
+    <acquiredDate>1577975400000</acquiredDate>
+    <remainingQty>10</remainingQty>
+    <availableQty>10</availableQty>
+    <totalCost>1005</totalCost>
+    <marketValue>1500</marketValue>
+    <totalGain>495</totalGain>
+  </PositionLot>
+  <PositionLot>
+    <positionId>140357348131</positionId>
+    <positionLotId>2</positionLotId>
+    <price>140</price>
+    <acquiredDate>1609770600000</acquiredDate>
+    <remainingQty>5</remainingQty>
+    <availableQty>5</availableQty>
+    <totalCost>700</totalCost>
+    <marketValue>750</marketValue>
+    <totalGain>50</totalGain>
+  </PositionLot>
+</PositionLotsResponse>"#;
+
+    fn app_with_lots() -> App {
+        let lots: PositionLotsXML = serde_xml_rs::from_reader(LOTS.as_bytes()).unwrap();
+        let today = NaiveDate::from_ymd(2021, 6, 1);
+
+        let mut app = App::default();
+        app.user_accounts = Some(vec![account("83405188")]);
+        app.active_account_index = Some(0);
+        app.position_lots = Some(PositionLots::new(
+            "AAPL".to_string(),
+            "83405188-key".to_string(),
+            lots.position_lots.into_iter().map(|lot| TaxLot::new(lot, today)).collect(),
+        ));
+        app
+    }
+
+    #[test]
+    fn parses_lots_with_holding_terms() {
+        let app = app_with_lots();
+        let lots = &app.position_lots.as_ref().unwrap().lots;
+
+        assert_eq!(lots[0].acquired, NaiveDate::from_ymd(2020, 1, 2));
+        assert_eq!(lots[0].term, HoldingTerm::LongTerm);
+        assert_eq!(lots[1].acquired, NaiveDate::from_ymd(2021, 1, 4));
+        assert_eq!(lots[1].term, HoldingTerm::ShortTerm);
+        assert_eq!((lots[1].quantity, lots[1].price, lots[1].total_gain), (5.0, 140.0, 50.0));
+    }
+
+    #[test]
+    fn sells_picked_lots() {
+        let mut app = app_with_lots();
+
+        handler(Key::Char(' '), &mut app);
+        handler(Key::Down, &mut app);
+        handler(Key::Char(' '), &mut app);
+        handler(Key::Char('S'), &mut app);
+
+        assert_eq!(app.order_form_state, OrderFormState::Quantity);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Input);
+        assert_eq!(app.input.iter().collect::<String>(), "15");
+
+        // selling 12 relieves all of the first lot and part of the second
+        let mut order_form = app.preview_order_form.clone().unwrap();
+        assert_eq!(order_form.order_action, OrderAction::SELL);
+        order_form.quantity = "12".to_string();
+        let request: PreviewOrderRequest = order_form.into();
+        let instrument = &request.preview_order_request.order[0].instrument[0];
+        assert_eq!(
+            instrument.lots.as_ref().unwrap().lot,
+            vec![Lot { id: 1, size: 10.0 }, Lot { id: 2, size: 2.0 }]
+        );
+    }
+
+    #[test]
+    fn sells_highlighted_lot_when_none_picked() {
+        let mut app = app_with_lots();
+        handler(Key::Char('L'), &mut app);
+
+        let lots = app.position_lots.as_ref().unwrap().lots_to_sell();
+        assert_eq!(lots, vec![Lot { id: 2, size: 5.0 }]);
+
+        app.user_accounts = None;
+        handler(Key::Char('S'), &mut app);
+        assert_eq!(app.position_lots.unwrap().message, Some("The account holding AAPL isn't loaded".to_string()));
+    }
+
+    #[test]
+    fn sells_from_the_account_holding_the_lots() {
+        let mut app = app_with_lots();
+        // all accounts shown, the one last picked doesn't hold the position
+        app.user_accounts = Some(vec![account("1"), account("83405188")]);
+        app.active_account_index = Some(0);
+        app.all_accounts = true;
+
+        handler(Key::Char('S'), &mut app);
+
+        let order_form = app.preview_order_form.as_ref().unwrap();
+        assert_eq!(order_form.account_id, "83405188");
+        assert_eq!(order_form.account_id_key, "83405188-key");
+    }
+}
@@ -4,6 +4,7 @@ use super::{
     common_key_events,
 };
 use crate::clients::etrade_json_structs::{OrderAction, OrderType};
+use crate::network::IoEvent;
 
 pub fn handler(key: Key, app: &mut App) {
     match key {
@@ -33,6 +34,29 @@ pub fn handler(key: Key, app: &mut App) {
             // focus input to enter # of shares
             app.push_navigation_stack(RouteId::OrderForm, ActiveBlock::Input);
         }
+        Key::Char('w') => {
+            let symbol = app.selected_ticker.as_ref().unwrap().ticker.symbol.to_owned();
+            app.toggle_watch_list_symbol(symbol);
+        }
+        // cycle the quote detail block
+        Key::Char('f') => {
+            if let Some(detail_flag) = app.next_detail_flag() {
+                app.dispatch(IoEvent::GetTickerDetail(detail_flag));
+            }
+        }
+        // chart range
+        Key::Char(']') | Key::Char('[') => {
+            let forward = key == Key::Char(']');
+            let range = app.next_chart_range(forward);
+            app.dispatch(IoEvent::GetPriceHistory(range));
+        }
+        Key::Char('a') => {
+            app.dispatch(IoEvent::GetAnalysis);
+        }
+        Key::Char('o') => {
+            let symbol = app.selected_ticker.as_ref().unwrap().ticker.symbol.to_owned();
+            app.dispatch(IoEvent::GetOptionChain(symbol));
+        }
         k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
         k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
         _ => {}
@@ -41,6 +65,136 @@ pub fn handler(key: Key, app: &mut App) {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::app::{SelectedTicker, Ticker};
+    use crate::clients::etrade_json_structs::DetailFlag;
+    use crate::price_history::ChartRange;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn quote(bid: f64, ask: f64, last_trade: f64) -> Ticker {
+        Ticker {
+            symbol: "IBM".to_string(),
+            date_time: "15:59:59 EDT 06-20-2018".to_string(),
+            bid,
+            ask,
+            last_trade,
+            previous_close: 140.0,
+            ..Default::default()
+        }
+    }
+
+    fn app_with_ticker() -> App {
+        let mut app = App::default();
+        app.selected_ticker = Some(SelectedTicker {
+            ticker: Ticker {
+                symbol: "IBM".to_string(),
+                description: "International Business Machines".to_string(),
+                ..Default::default()
+            },
+            selected_index: 0,
+            detail_flag: DetailFlag::ALL,
+        });
+        app.push_navigation_stack(RouteId::TickerDetail, ActiveBlock::TickerDetail);
+        app
+    }
+
+    #[test]
+    fn change_versus_previous_close() {
+        let ticker = quote(140.9, 141.1, 142.1);
+        assert!((ticker.change().unwrap() - 2.1).abs() < 1e-9);
+        assert!((ticker.percent_change().unwrap() - 1.5).abs() < 1e-9);
+
+        // nothing traded yet, fall back to the middle of the spread
+        let ticker = quote(139.0, 139.2, 0.0);
+        assert!((ticker.change().unwrap() + 0.9).abs() < 1e-9);
+
+        assert_eq!(Ticker::default().change(), None);
+    }
+
     #[test]
-    fn test() {}
+    fn refreshed_quote_updates_selected_ticker_and_flashes() {
+        let mut app = app_with_ticker();
+        if let Some(ref mut selected) = app.selected_ticker {
+            selected.ticker.pe = 22.5;
+            selected.ticker.high52 = 152.8;
+            selected.detail_flag = DetailFlag::FUNDAMENTAL;
+        }
+        let now = Utc.ymd(2021, 7, 1).and_hms(14, 0, 0);
+
+        app.update_quotes(vec![quote(140.9, 141.1, 141.0)], now);
+        assert_eq!(app.quote_flash("IBM", |t| t.bid, now), None);
+
+        let later = now + Duration::seconds(5);
+        app.update_quotes(vec![quote(140.8, 141.3, 141.0)], later);
+
+        let selected = &app.selected_ticker.as_ref().unwrap().ticker;
+        assert_eq!(selected.description, "International Business Machines");
+        assert_eq!(selected.ask, 141.3);
+        // the fundamentals on screen aren't wiped by a quote that doesn't carry them
+        assert_eq!((selected.pe, selected.high52), (22.5, 152.8));
+        assert_eq!(app.quote_flash("ibm", |t| t.bid, later), Some(false));
+        assert_eq!(app.quote_flash("IBM", |t| t.ask, later), Some(true));
+        assert_eq!(app.quote_flash("IBM", |t| t.last_trade, later), None);
+        // the highlight wears off
+        assert_eq!(app.quote_flash("IBM", |t| t.ask, later + Duration::seconds(2)), None);
+    }
+
+    #[test]
+    fn intraday_detail_block() {
+        let xml = r#"<QuoteResponse>
+            <QuoteData>
+                <dateTime>15:17:00 EDT 06-20-2018</dateTime>
+                <dateTimeUTC>1529522220</dateTimeUTC>
+                <quoteStatus>DELAYED</quoteStatus>
+                <ahFlag>false</ahFlag>
+                <Intraday>
+                    <ask>141.75</ask>
+                    <bid>141.72</bid>
+                    <changeClose>1.98</changeClose>
+                    <changeClosePercentage>1.42</changeClosePercentage>
+                    <companyName>INTERNATIONAL BUSINESS MACHS COM</companyName>
+                    <high>142.18</high>
+                    <lastTrade>141.74</lastTrade>
+                    <low>140.21</low>
+                    <totalVolume>4062184</totalVolume>
+                </Intraday>
+                <Product>
+                    <symbol>IBM</symbol>
+                    <securityType>EQ</securityType>
+                </Product>
+            </QuoteData>
+        </QuoteResponse>"#;
+        let ticker_xml: crate::clients::etrade_xml_structs::TickerXML = serde_xml_rs::from_str(xml).unwrap();
+        let ticker: Ticker = ticker_xml.quote_data.into();
+
+        assert_eq!(ticker.description, "INTERNATIONAL BUSINESS MACHS COM");
+        assert_eq!(ticker.total_volume, 4062184);
+        assert_eq!((ticker.low, ticker.high), (140.21, 142.18));
+        // no previous close in the intraday block, E*TRADE's own change is used
+        assert_eq!(ticker.change(), Some(1.98));
+        assert_eq!(ticker.percent_change(), Some(1.42));
+    }
+
+    #[test]
+    fn cycles_detail_flags() {
+        let mut app = app_with_ticker();
+        assert_eq!(app.next_detail_flag(), Some(DetailFlag::FUNDAMENTAL));
+
+        app.selected_ticker.as_mut().unwrap().detail_flag = DetailFlag::OPTIONS;
+        assert_eq!(app.next_detail_flag(), Some(DetailFlag::ALL));
+
+        app.selected_ticker = None;
+        handler(Key::Char('f'), &mut app);
+        assert_eq!(app.next_detail_flag(), None);
+    }
+
+    #[test]
+    fn cycles_chart_ranges() {
+        let mut app = app_with_ticker();
+        assert_eq!(app.next_chart_range(true), ChartRange::SixMonths);
+        assert_eq!(app.next_chart_range(false), ChartRange::FiveDays);
+
+        app.chart_range = ChartRange::OneYear;
+        assert_eq!(app.next_chart_range(true), ChartRange::OneDay);
+    }
 }
@@ -0,0 +1,197 @@
+use super::{
+    super::super::app::App,
+    super::key::Key,
+    common_key_events,
+};
+use crate::network::IoEvent;
+
+pub fn handler(key: Key, app: &mut App) {
+    let visible = app.transactions.visible().iter().map(|t| t.transaction_id).collect::<Vec<i64>>();
+    match key {
+        k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            app.transactions.selected_index =
+                common_key_events::on_down_press_handler(&visible, Some(app.transactions.selected_index));
+        }
+        k if common_key_events::up_event(k) => {
+            app.transactions.selected_index =
+                common_key_events::on_up_press_handler(&visible, Some(app.transactions.selected_index));
+        }
+        k if common_key_events::high_event(k) => {
+            app.transactions.selected_index = common_key_events::on_high_press_handler();
+        }
+        k if common_key_events::middle_event(k) && !visible.is_empty() => {
+            app.transactions.selected_index = common_key_events::on_middle_press_handler(&visible);
+        }
+        k if common_key_events::low_event(k) && !visible.is_empty() => {
+            app.transactions.selected_index = common_key_events::on_low_press_handler(&visible);
+        }
+        Key::Char('t') => {
+            app.transactions.cycle_kind();
+            app.dispatch(IoEvent::GetTransactions);
+        }
+        Key::Char('n') => {
+            if app.transactions.next_page() {
+                app.dispatch(IoEvent::GetTransactions);
+            } else {
+                app.transactions.message = Some("This is the last page".to_string());
+            }
+        }
+        Key::Char('p') => {
+            if app.transactions.previous_page() {
+                app.dispatch(IoEvent::GetTransactions);
+            } else {
+                app.transactions.message = Some("This is the first page".to_string());
+            }
+        }
+        Key::Char('r') => {
+            app.dispatch(IoEvent::GetTransactions);
+        }
+        Key::Char('x') => {
+            app.dispatch(IoEvent::ExportTransactions);
+        }
+        Key::Enter => {
+            if let Some(transaction_id) = app.transactions.selected().map(|transaction| transaction.transaction_id) {
+                app.dispatch(IoEvent::GetTransactionDetails(transaction_id));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ActiveBlock, RouteId, MAX_FILTERED_PAGES};
+    use crate::clients::etrade_json_structs::{Transaction, TransactionListResponse};
+    use crate::transactions::TransactionKind;
+    use chrono::NaiveDate;
+
+    fn transaction(transaction_id: i64, transaction_type: &str) -> Transaction {
+        Transaction {
+            transaction_id,
+            transaction_type: transaction_type.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn page(marker: Option<&str>, ids: &[i64]) -> TransactionListResponse {
+        TransactionListResponse {
+            marker: marker.map(|marker| marker.to_string()),
+            more_transactions: marker.is_some(),
+            transactions: ids.iter().map(|id| transaction(*id, "Bought")).collect(),
+        }
+    }
+
+    #[test]
+    fn pages_through_markers() {
+        let mut app = App::default();
+        app.transactions.set_page(page(Some("page-2"), &[1, 2]));
+
+        handler(Key::Char('p'), &mut app);
+        assert_eq!(app.transactions.message, Some("This is the first page".to_string()));
+
+        handler(Key::Char('n'), &mut app);
+        assert_eq!(app.transactions.marker, Some("page-2".to_string()));
+        app.transactions.set_page(page(None, &[3]));
+
+        handler(Key::Char('n'), &mut app);
+        assert_eq!(app.transactions.message, Some("This is the last page".to_string()));
+        assert_eq!(app.transactions.marker, Some("page-2".to_string()));
+
+        handler(Key::Char('p'), &mut app);
+        assert_eq!(app.transactions.marker, None);
+        assert!(app.transactions.previous_markers.is_empty());
+
+        // a new date range starts from the first page again
+        app.transactions.set_page(page(Some("page-2"), &[1, 2]));
+        handler(Key::Char('n'), &mut app);
+        app.transactions.set_date_range(NaiveDate::from_ymd(2021, 1, 1), NaiveDate::from_ymd(2021, 3, 31));
+        assert_eq!((app.transactions.marker.clone(), app.transactions.previous_markers.len()), (None, 0));
+    }
+
+    #[test]
+    fn filters_by_type() {
+        let mut app = App::default();
+        app.transactions.set_page(TransactionListResponse {
+            transactions: vec![transaction(1, "Bought"), transaction(2, "Qualified Dividend"), transaction(3, "Sold")],
+            ..Default::default()
+        });
+
+        handler(Key::Char('L'), &mut app);
+        assert_eq!(app.transactions.selected().map(|t| t.transaction_id), Some(3));
+
+        handler(Key::Char('t'), &mut app);
+        assert_eq!(app.transactions.kind, Some(TransactionKind::Trades));
+        assert_eq!(app.transactions.selected_index, 0);
+        handler(Key::Down, &mut app);
+        assert_eq!(app.transactions.selected().map(|t| t.transaction_id), Some(3));
+
+        handler(Key::Char('t'), &mut app);
+        assert_eq!(app.transactions.visible().len(), 1);
+        for _ in 0..TransactionKind::ALL.len() - 1 {
+            handler(Key::Char('t'), &mut app);
+        }
+        assert_eq!(app.transactions.kind, None);
+        assert_eq!(app.transactions.visible().len(), 3);
+
+        // another filter starts over from the first page
+        app.transactions.set_page(page(Some("page-2"), &[4]));
+        handler(Key::Char('n'), &mut app);
+        handler(Key::Char('t'), &mut app);
+        assert_eq!((app.transactions.marker.clone(), app.transactions.previous_markers.len()), (None, 0));
+    }
+
+    #[test]
+    fn fills_a_filtered_page_from_the_pages_after_it() {
+        let mut app = App::default();
+        handler(Key::Char('t'), &mut app);
+        assert_eq!(app.transactions.kind, Some(TransactionKind::Trades));
+
+        app.transactions.set_page(TransactionListResponse {
+            transactions: vec![transaction(1, "Qualified Dividend"), transaction(2, "Bought")],
+            marker: Some("page-2".to_string()),
+            more_transactions: true,
+        });
+        assert!(app.transactions.needs_more());
+        app.transactions.extend_page(page(None, &[3, 4]));
+        assert!(!app.transactions.needs_more());
+        assert_eq!(app.transactions.visible().iter().map(|t| t.transaction_id).collect::<Vec<i64>>(), vec![2, 3, 4]);
+
+        // a filter that matches nothing stops after MAX_FILTERED_PAGES, the next page carries on
+        app.transactions.set_page(page(Some("page-2"), &[5]));
+        app.transactions.transactions[0].transaction_type = "Qualified Dividend".to_string();
+        for _ in 1..MAX_FILTERED_PAGES {
+            assert!(app.transactions.needs_more());
+            app.transactions.extend_page(page(Some("page-next"), &[]));
+        }
+        assert!(!app.transactions.needs_more());
+        assert!(app.transactions.next_page());
+
+        // unfiltered, one page is all there is to show
+        app.transactions.kind = None;
+        app.transactions.set_page(page(Some("page-2"), &[1]));
+        assert!(!app.transactions.needs_more());
+    }
+
+    #[test]
+    fn input_sets_the_date_range() {
+        let mut app = App::default();
+        app.push_navigation_stack(RouteId::Transactions, ActiveBlock::Transactions);
+        app.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
+
+        app.input = "2021-03-31 2021-01-01".chars().collect();
+        crate::ui::handlers::input_handler(Key::Enter, &mut app);
+        assert!(app.transactions.message.is_some());
+        assert_eq!(app.transactions.start_date, None);
+
+        app.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
+        app.input = "2021-01-01 2021-03-31".chars().collect();
+        crate::ui::handlers::input_handler(Key::Enter, &mut app);
+        assert_eq!(app.transactions.start_date, Some(NaiveDate::from_ymd(2021, 1, 1)));
+        assert_eq!(app.transactions.end_date, Some(NaiveDate::from_ymd(2021, 3, 31)));
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Transactions);
+        assert!(app.input.is_empty());
+    }
+}
@@ -1,56 +1,126 @@
 use super::{
-    super::super::app::{App, MAJOR_INDICES},
+    super::super::app::{ActiveBlock, App},
     common_key_events,
 };
 use super::super::key::Key;
 use crate::network::IoEvent;
 
 pub fn handler(key: Key, app: &mut App) {
+    let symbols = app.library.watch_lists.active_symbols().to_vec();
     match key {
         k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
         k if common_key_events::down_event(k) => {
             let next_index = common_key_events::on_down_press_handler(
-                &MAJOR_INDICES,
+                &symbols,
                 Some(app.library.selected_index),
             );
             app.library.selected_index = next_index;
         }
         k if common_key_events::up_event(k) => {
             let next_index =
-                common_key_events::on_up_press_handler(&MAJOR_INDICES, Some(app.library.selected_index));
+                common_key_events::on_up_press_handler(&symbols, Some(app.library.selected_index));
             app.library.selected_index = next_index;
         }
         k if common_key_events::high_event(k) => {
             let next_index = common_key_events::on_high_press_handler();
             app.library.selected_index = next_index;
         }
-        k if common_key_events::middle_event(k) => {
-            let next_index = common_key_events::on_middle_press_handler(&MAJOR_INDICES);
+        k if common_key_events::middle_event(k) && !symbols.is_empty() => {
+            let next_index = common_key_events::on_middle_press_handler(&symbols);
             app.library.selected_index = next_index;
         }
-        k if common_key_events::low_event(k) => {
-            let next_index = common_key_events::on_low_press_handler(&MAJOR_INDICES);
+        k if common_key_events::low_event(k) && !symbols.is_empty() => {
+            let next_index = common_key_events::on_low_press_handler(&symbols);
             app.library.selected_index = next_index
         }
-        // `library` should probably be an array of structs with enums rather than just using indexes
-        // like this
-        Key::Enter => match app.library.selected_index {
-            // Nasdaq
-            0 => {
-                app.dispatch(IoEvent::GetNasdaq);
+        Key::Char(']') | Key::Char('[') => {
+            let forward = key == Key::Char(']');
+            app.cycle_watch_list(forward);
+        }
+        // name the new list in the input box
+        Key::Char('n') => {
+            app.library.naming_watch_list = true;
+            app.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
+        }
+        Key::Char('d') => {
+            if let Some(symbol) = app.selected_watch_list_symbol() {
+                app.dispatch(IoEvent::CurrentUserSavedTickerDelete(symbol));
             }
-            // DJIA
-            1 => {
-                app.dispatch(IoEvent::GetDowJones);
+        }
+        Key::Char('X') => {
+            if let Some(list) = app.library.watch_lists.active_list() {
+                let name = list.name.to_owned();
+                app.library.selected_index = 0;
+                app.dispatch(IoEvent::DeleteWatchList(name));
             }
-            // S&P
-            2 => {
-                app.dispatch(IoEvent::GetSandP);
+        }
+        Key::Enter => {
+            if let Some(symbol) = app.selected_watch_list_symbol() {
+                app.dispatch(IoEvent::GetTicker(symbol));
             }
-            // This is required because Rust can't tell if this pattern in exhaustive
-            _ => {}
-        },
+        }
         _ => (),
     };
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watch_lists::WatchLists;
+
+    fn app_with_watch_lists() -> App {
+        let mut watch_lists = WatchLists::default();
+        watch_lists.create("Tech").unwrap();
+        watch_lists.add("AAPL").unwrap();
+        watch_lists.add("MSFT").unwrap();
+        watch_lists.cycle(true);
+
+        let mut app = App::default();
+        app.library.watch_lists = watch_lists;
+        app
+    }
+
+    #[test]
+    fn moves_through_active_list() {
+        let mut app = app_with_watch_lists();
+
+        handler(Key::Char('L'), &mut app);
+        assert_eq!(app.selected_watch_list_symbol(), Some("GSPC".to_string()));
+        handler(Key::Down, &mut app);
+        assert_eq!(app.selected_watch_list_symbol(), Some("NDAQ".to_string()));
+    }
+
+    #[test]
+    fn cycles_lists() {
+        let mut app = app_with_watch_lists();
+        app.library.selected_index = 2;
+
+        handler(Key::Char(']'), &mut app);
+        assert_eq!(app.library.watch_lists.active_list().unwrap().name, "Tech");
+        assert_eq!(app.library.selected_index, 0);
+        assert_eq!(app.selected_watch_list_symbol(), Some("AAPL".to_string()));
+
+        handler(Key::Char('['), &mut app);
+        assert_eq!(app.library.watch_lists.active_list().unwrap().name, "Indices");
+    }
+
+    #[test]
+    fn empty_list_ignores_navigation() {
+        let mut app = App::default();
+        app.library.watch_lists.lists[0].symbols.clear();
+
+        handler(Key::Char('M'), &mut app);
+        handler(Key::Char('L'), &mut app);
+        assert_eq!(app.library.selected_index, 0);
+        assert_eq!(app.selected_watch_list_symbol(), None);
+    }
+
+    #[test]
+    fn new_list_focuses_input() {
+        let mut app = App::default();
+
+        handler(Key::Char('n'), &mut app);
+        assert!(app.library.naming_watch_list);
+        assert_eq!(app.get_current_route().active_block, ActiveBlock::Input);
+    }
+}
@@ -1,18 +1,28 @@
+pub mod analysis;
+pub mod authorization;
+pub mod balance;
 pub mod event;
 pub mod key;
+pub mod realized_gains;
+pub mod transactions;
 pub mod handlers;
 pub mod util;
 
 pub use key::Key;
 
-use crate::app::{ActiveBlock, App, MAJOR_INDICES, OrderFormState, RouteId};
+use crate::app::{self, ActiveBlock, App, OrderFormState, PortfolioTotals, PositionSort, PreviewOrder, RouteId};
+use crate::clients::etrade_json_structs::{DetailFlag, MarketSession, OptionDetails, OrderAction, OrderTerm, PriceType};
+use crate::price_history::{self, ChartRange};
+use crate::spread;
+use crate::utils;
 use util::{get_color, date_from_timestamp};
 use tui::{
     backend::Backend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols,
     text::{Span, Spans, Text},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Row, Table, Wrap},
+    widgets::{Axis, Block, Borders, Cell, Chart, Clear, Dataset, GraphType, List, ListItem, ListState, Paragraph, Row, Sparkline, Table, TableState, Wrap},
     Frame,
 };
 
@@ -53,11 +63,6 @@ use tui::{
 //     width: u16,
 // }
 
-pub struct TableItem {
-    id: String,
-    data: Vec<String>,
-}
-
 pub fn draw_main<B>(f: &mut Frame<B>, app: &App)
     where B: Backend,
           {
@@ -70,6 +75,10 @@ pub fn draw_main<B>(f: &mut Frame<B>, app: &App)
               draw_input_and_help_box(f, &app, parent_layout[0]);
               // Nested main block with potential routes
               draw_user_blocks(f, &app, parent_layout[1]);
+
+              if let ActiveBlock::Dialog(_) = app.get_current_route().active_block {
+                  draw_dialog(f, &app);
+              }
           }
 
 pub fn draw_user_blocks<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
@@ -98,6 +107,9 @@ pub fn draw_a_route<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
         RouteId::OrderForm => {
             draw_order_form(f, app, layout_chunk)
         }
+        RouteId::ConfirmOrderForm if app.preview_order_response.is_some() => {
+            draw_confirm_order_form(f, app, layout_chunk)
+        }
         RouteId::TickerDetail if app.selected_ticker.is_some() => {
             draw_ticker_detail(f, app, layout_chunk)
         }
@@ -113,6 +125,36 @@ pub fn draw_a_route<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
         RouteId::NotificationDetail => {
             draw_notification_detail(f, app, layout_chunk)
         }
+        RouteId::Orders => {
+            draw_orders(f, app, layout_chunk)
+        }
+        RouteId::OptionChain if app.selected_option_chain.is_some() => {
+            draw_option_chain(f, app, layout_chunk)
+        }
+        RouteId::Spread => {
+            draw_spread(f, app, layout_chunk)
+        }
+        RouteId::Portfolio => {
+            draw_positions(f, app, layout_chunk)
+        }
+        RouteId::TaxLots if app.position_lots.is_some() => {
+            draw_tax_lots(f, app, layout_chunk)
+        }
+        RouteId::Analysis if app.analysis.is_some() => {
+            analysis::draw_analysis(f, app, layout_chunk)
+        }
+        RouteId::Balance => {
+            balance::draw_balance(f, app, layout_chunk)
+        }
+        RouteId::Transactions => {
+            transactions::draw_transactions(f, app, layout_chunk)
+        }
+        RouteId::RealizedGains => {
+            realized_gains::draw_realized_gains(f, app, layout_chunk)
+        }
+        RouteId::Authorization if app.authorization.is_some() => {
+            authorization::draw_authorization(f, app, layout_chunk)
+        }
         _ => draw_home(f, app, layout_chunk)
 
     }
@@ -155,65 +197,101 @@ pub fn draw_ticker_detail<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
         current_route.hovered_block == ActiveBlock::TickerDetail,
         );
 
-    let selected_ticker = app.selected_ticker.as_ref();
-    let ticker = &selected_ticker.unwrap().ticker;
+    let selected_ticker = app.selected_ticker.as_ref().unwrap();
+    let ticker = &selected_ticker.ticker;
 
     let style = Style::default().fg(app.user_config.theme.text); // default styling
-
-    let i0 = TableItem {
-        id: ticker.symbol.to_owned(),
-        data: vec![
-            format!("{} ➤ {}", "exch", ticker.primary_exchange.to_owned()),
-            format!("{} ➤ {}", "date", ticker.date_time.to_owned()),
-        ]
-    };
-
-    let i1 = TableItem {
-        id: ticker.symbol.to_owned(),
-        data: vec![
-            format!("{}  |  ${}", "bid", ticker.bid.to_owned()),
-            format!("{}  |  ${}", "ask", ticker.ask.to_owned()),
-            format!("{}  |  ${}", "open", ticker.high52.to_owned()),
-        ]
+    let theme = app.user_config.theme;
+    let now = chrono::Utc::now();
+    let change_style = util::change_style(ticker.change(), theme);
+
+    // quote cells are colored by direction and flash when a refresh moves them
+    let last = || {
+        Cell::from(format!("{}  |  ${:.2}", "last", ticker.price())).style(util::flash_style(
+            change_style,
+            app.quote_flash(&ticker.symbol, app::Ticker::price, now),
+            theme,
+        ))
     };
-
-    let i2 = TableItem {
-        id: ticker.symbol.to_owned(),
-        data: vec![
-            format!("{}  |  {}", "eps", ticker.eps.to_owned()),
-            format!("{}  |  {}", "pe", ticker.pe.to_owned()),
-            format!("{}  |  {}", "beta", ticker.beta.to_owned()),
-        ]
+    let change = || Cell::from(format!("{}  |  {}", "change", util::format_change(ticker))).style(change_style);
+    let bid = || {
+        Cell::from(format!("{}  |  ${:.2} x {}", "bid", ticker.bid, ticker.bid_size))
+            .style(util::flash_style(style, app.quote_flash(&ticker.symbol, |t| t.bid, now), theme))
     };
-    let i3 = TableItem {
-        id: ticker.symbol.to_owned(),
-        data: vec![
-            format!("{}  |  ${}", "high 52", ticker.high52.to_owned()),
-            format!("on {}", date_from_timestamp(ticker.week52_hi_date)),
-        ]
+    let ask = || {
+        Cell::from(format!("{}  |  ${:.2} x {}", "ask", ticker.ask, ticker.ask_size))
+            .style(util::flash_style(style, app.quote_flash(&ticker.symbol, |t| t.ask, now), theme))
     };
-
-    let i4 = TableItem {
-        id: ticker.symbol.to_owned(),
-        data: vec![
-            format!("{}  |  ${}", "low 52", ticker.low52.to_owned()),
-            format!("on {}", date_from_timestamp(ticker.week52_low_date)),
-        ]
-    };
-
-    let i5 = TableItem {
-        id: ticker.symbol.to_owned(),
-        data: vec![
-            format!("{}  |  ${}", "dividend", ticker.dividend.to_owned()),
-            format!("{} ➤ {}", "ex dividend date", date_from_timestamp(ticker.ex_dividend_date)),
-        ]
+    let day_range = || Cell::from(format!("{}  |  ${:.2} - ${:.2}", "day range", ticker.low, ticker.high));
+    let volume = || Cell::from(format!("{}  |  {}", "volume", utils::format_large_number(ticker.total_volume as f64)));
+    let eps = || Cell::from(format!("{}  |  {}", "eps", ticker.eps));
+    let high52 = || Cell::from(format!("{}  |  ${:.2} on {}", "high 52", ticker.high52, date_from_timestamp(ticker.week52_hi_date)));
+    let low52 = || Cell::from(format!("{}  |  ${:.2} on {}", "low 52", ticker.low52, date_from_timestamp(ticker.week52_low_date)));
+
+    let cells = match selected_ticker.detail_flag {
+        DetailFlag::ALL => vec![
+            vec![
+                Cell::from(format!("{} ➤ {}", "exch", ticker.primary_exchange)),
+                Cell::from(format!("{} ➤ {}", "date", ticker.date_time)),
+            ],
+            vec![last(), change(), Cell::from(format!("{}  |  ${:.2}", "prev close", ticker.previous_close))],
+            vec![bid(), ask(), Cell::from(format!("{}  |  ${:.2}", "open", ticker.open))],
+            vec![
+                day_range(),
+                volume(),
+                Cell::from(format!("{}  |  ${}", "market cap", utils::format_large_number(ticker.market_cap))),
+            ],
+            vec![
+                Cell::from(format!("{}  |  {}", "shares out", utils::format_large_number(ticker.shares_outstanding))),
+                Cell::from(format!("{}  |  {}", "next earnings", ticker.next_earning_date)),
+                eps(),
+            ],
+            vec![
+                Cell::from(format!("{}  |  {}", "pe", ticker.pe)),
+                Cell::from(format!("{}  |  {}", "beta", ticker.beta)),
+                Cell::from(format!("{}  |  ${}", "dividend", ticker.dividend)),
+            ],
+            vec![
+                high52(),
+                low52(),
+                Cell::from(format!("{} ➤ {}", "ex dividend date", date_from_timestamp(ticker.ex_dividend_date))),
+            ],
+        ],
+        DetailFlag::FUNDAMENTAL => vec![
+            vec![Cell::from(ticker.description.to_owned()), last()],
+            vec![eps(), Cell::from(format!("{}  |  {}", "est earnings", ticker.est_earnings))],
+            vec![high52(), low52()],
+        ],
+        DetailFlag::INTRADAY => vec![
+            vec![last(), change()],
+            vec![bid(), ask()],
+            vec![day_range(), volume()],
+        ],
+        DetailFlag::OPTIONS => vec![
+            vec![last(), bid(), ask()],
+            vec![
+                Cell::from(format!("{}  |  {}", "open interest", ticker.open_interest)),
+                Cell::from(format!("{}  |  {}", "days to expiration", ticker.days_to_expiration)),
+                Cell::from(format!("{}  |  {}", "multiplier", ticker.option_multiplier)),
+            ],
+            vec![
+                Cell::from(format!("{}  |  ${:.2}", "intrinsic value", ticker.intrinsic_value)),
+                Cell::from(format!("{}  |  ${:.2}", "time premium", ticker.time_premium)),
+            ],
+        ],
     };
 
-    let rows = [i0, i1, i2, i3, i4, i5]
-        .iter()
-        .map(|i| Row::new(i.data.clone()).style(style).height(3))
+    let rows = cells
+        .into_iter()
+        .map(|row| Row::new(row).style(style).height(2))
         .collect::<Vec<Row>>();
 
+    // quote panel on top, price chart under it
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(rows.len() as u16 * 2 + 2), Constraint::Min(0)].as_ref())
+        .split(layout_chunk);
+
     // let widths = header
     //     .items
     //     .iter()
@@ -230,7 +308,12 @@ pub fn draw_ticker_detail<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
             .borders(Borders::ALL)
             .style(Style::default().fg(app.user_config.theme.text))
             .title(Span::styled(
-                    ticker.symbol.to_owned(),
+                    format!(
+                        "{} [{}]  (B: buy  S: sell  o: options  a: analysis  f: detail  w: {})",
+                        ticker.symbol,
+                        selected_ticker.detail_flag.to_string(),
+                        if app.library.watch_lists.contains(&ticker.symbol) { "unwatch" } else { "watch" },
+                    ),
                     get_color(highlight_state, app.user_config.theme),
                     ))
             .border_style(get_color(highlight_state, app.user_config.theme)),
@@ -239,7 +322,96 @@ pub fn draw_ticker_detail<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
         // .widths(&widths);
         .widths(&[Constraint::Percentage(33), Constraint::Percentage(33), Constraint::Percentage(33)]);
 
-    f.render_widget(table, layout_chunk);
+    f.render_widget(table, chunks[0]);
+    draw_price_chart(f, app, chunks[1]);
+}
+
+pub fn draw_price_chart<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let theme = app.user_config.theme;
+    let range_labels = ChartRange::ALL
+        .iter()
+        .map(|range| {
+            let style = if *range == app.chart_range { Style::default().fg(theme.active).add_modifier(Modifier::BOLD) } else { Style::default().fg(theme.inactive) };
+            Span::styled(format!(" {} ", range.label()), style)
+        })
+        .collect::<Vec<Span>>();
+    let mut title = vec![Span::styled("Chart ", Style::default().fg(theme.text))];
+    title.extend(range_labels);
+    title.push(Span::styled("  ([ ]: range)", Style::default().fg(theme.hint)));
+
+    let block = Block::default()
+        .title(Spans::from(title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.inactive));
+
+    // a chart left over from the previous ticker isn't drawn while the new one loads
+    let symbol = app.selected_ticker.as_ref().map(|selected_ticker| selected_ticker.ticker.symbol.as_str());
+    let price_chart = app.price_chart.as_ref().filter(|price_chart| Some(price_chart.symbol.as_str()) == symbol);
+    let bars = match price_chart {
+        Some(price_chart) if !price_chart.bars.is_empty() => &price_chart.bars,
+        Some(price_chart) => {
+            let message = price_chart.message.to_owned().unwrap_or_default();
+            f.render_widget(Paragraph::new(message).block(block).wrap(Wrap { trim: true }), layout_chunk);
+            return;
+        }
+        None => {
+            f.render_widget(block, layout_chunk);
+            return;
+        }
+    };
+
+    let inner = block.inner(layout_chunk);
+    f.render_widget(block, layout_chunk);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(75), Constraint::Percentage(25)].as_ref())
+        .split(inner);
+
+    // x is the bar index so nights and weekends don't leave gaps
+    let closes = bars
+        .iter()
+        .enumerate()
+        .map(|(i, bar)| (i as f64, bar.close))
+        .collect::<Vec<(f64, f64)>>();
+    let low = bars.iter().map(|bar| bar.low).fold(f64::MAX, f64::min);
+    let high = bars.iter().map(|bar| bar.high).fold(f64::MIN, f64::max);
+    let (first, last) = (&bars[0], &bars[bars.len() - 1]);
+    let line_style = util::change_style(Some(last.close - first.close), theme);
+
+    let time_format = if price_chart.is_some_and(|price_chart| price_chart.range.is_intraday()) { "%m-%d %H:%M" } else { "%Y-%m-%d" };
+    let datasets = vec![Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(line_style)
+        .data(&closes)];
+    let chart = Chart::new(datasets)
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(theme.inactive))
+                .bounds([0.0, (bars.len() - 1).max(1) as f64])
+                .labels(vec![
+                    Span::raw(first.time.format(time_format).to_string()),
+                    Span::raw(last.time.format(time_format).to_string()),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(theme.inactive))
+                .bounds([low, high])
+                .labels(vec![Span::raw(format!("{:.2}", low)), Span::raw(format!("{:.2}", high))]),
+        );
+    f.render_widget(chart, chunks[0]);
+
+    let volumes = price_history::bucket_volumes(bars, chunks[1].width as usize);
+    let volume = Sparkline::default()
+        .block(Block::default().title(Span::styled("Volume", Style::default().fg(theme.inactive))))
+        .style(Style::default().fg(theme.inactive))
+        .data(&volumes);
+    f.render_widget(volume, chunks[1]);
 }
 
 pub fn draw_order_form<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
@@ -254,13 +426,24 @@ pub fn draw_order_form<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 
     let mut text = vec![];
     if let Some(ref order_form) = app.preview_order_form {
-        let order_action = order_form.order_action.to_string();
-        text.push(
-            Spans::from(vec![
-                        Span::raw("Order Action ➤ "),
-                        Span::styled(order_action, Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
-            ])
-        );
+        if order_form.legs.is_empty() {
+            let order_action = order_form.order_action.to_string();
+            text.push(
+                Spans::from(vec![
+                            Span::raw("Order Action ➤ "),
+                            Span::styled(order_action, Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                ])
+            );
+        }
+        for leg in &order_form.legs {
+            text.push(
+                Spans::from(vec![
+                            Span::styled(leg.order_action.to_string(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                            Span::raw(format!(" {} x ", leg.ratio)),
+                            Span::raw(leg.contract.display_symbol.to_owned()),
+                ])
+            );
+        }
 
         let symbol = app.preview_order_ticker.as_ref().unwrap_or(&"Error".to_string()).to_string();
         text.push(
@@ -278,33 +461,137 @@ pub fn draw_order_form<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
         );
     }
 
-    if let OrderFormState::Quantity = app.order_form_state {
+    let lots = app.preview_order_form.as_ref().map_or(&[][..], |order_form| order_form.lots.as_slice());
+    if !lots.is_empty() {
+        let lots = lots.iter().map(|lot| format!("#{} ({})", lot.id, lot.size)).collect::<Vec<String>>();
         text.push(
             Spans::from(vec![
-                        Span::raw("1. Input number of shares"),
-            ]),
+                        Span::raw("Tax Lots ➤ "),
+                        Span::raw(lots.join(", ")),
+            ])
+        );
+    }
 
-            );
-    } else if let OrderFormState::Submit = app.order_form_state {
-        if let Some(ref order_form) = app.preview_order_form {
+    let option = app.preview_order_form.as_ref().and_then(|order_form| order_form.option.as_ref());
+    if let Some(option) = option {
+        text.push(
+            Spans::from(vec![
+                        Span::raw("Bid ➤ "),
+                        Span::raw(format!("${:.2}", option.bid)),
+                        Span::raw("  Ask ➤ "),
+                        Span::raw(format!("${:.2}", option.ask)),
+                        Span::raw("  Multiplier ➤ "),
+                        Span::raw(format!("{}", app::OPTION_MULTIPLIER)),
+            ])
+        );
+    } else if let Some(ref selected_ticker) = app.selected_ticker {
+        text.push(
+            Spans::from(vec![
+                        Span::raw("Bid ➤ "),
+                        Span::raw(format!("${:.2}", selected_ticker.ticker.bid)),
+                        Span::raw("  Ask ➤ "),
+                        Span::raw(format!("${:.2}", selected_ticker.ticker.ask)),
+            ])
+        );
+    }
+    text.push(Spans::from(Span::raw(" ")));
+
+    if let Some(ref order_form) = app.preview_order_form {
+        let state = app.order_form_state;
+        let active_style = Style::default().fg(app.user_config.theme.selected).add_modifier(Modifier::BOLD);
+
+        if state == OrderFormState::OptionAction {
+            text.push(Spans::from(Span::styled("Select open/close (j/k, Enter)", active_style)));
+            text.extend(order_form_options(&OrderAction::OPTIONS, order_form.order_action, active_style));
+        }
+
+        let unit = order_form.quantity_unit();
+        if state == OrderFormState::Quantity {
+            text.push(Spans::from(Span::styled(format!("1. Input number of {}", unit), active_style)));
+        } else if state != OrderFormState::OptionAction {
             text.push(
                 Spans::from(vec![
-                            Span::raw("1. Number of shares"),
-                            Span::raw(": "),
+                            Span::raw(format!("1. Number of {}: ", unit)),
                             Span::raw(order_form.quantity.to_owned()),
                 ])
-                );
+            );
+        }
+
+        if state == OrderFormState::PriceType {
+            text.push(Spans::from(Span::styled("2. Select price type (j/k, Enter)", active_style)));
+            text.extend(order_form_options(order_form.price_types(), order_form.price_type, active_style));
+        } else if state != OrderFormState::Quantity {
             text.push(
                 Spans::from(vec![
-                            Span::raw("2. Yay! Press Enter to Submit"),
+                            Span::raw("2. Price type: "),
+                            Span::raw(order_form.price_type.to_string()),
                 ])
-                );
+            );
+        }
+
+        if order_form.price_type.needs_limit_price() {
+            if state == OrderFormState::LimitPrice {
+                text.push(Spans::from(Span::styled("3. Input limit price", active_style)));
+            } else if !order_form.limit_price.is_empty() {
+                text.push(Spans::from(vec![Span::raw("3. Limit price: $"), Span::raw(order_form.limit_price.to_owned())]));
+            }
+        }
+
+        if order_form.price_type.needs_stop_price() {
+            let label = match order_form.price_type {
+                PriceType::TRAILING_STOP_CNST => "trail amount",
+                _ => "stop price",
+            };
+            if state == OrderFormState::StopPrice {
+                text.push(Spans::from(Span::styled(format!("4. Input {}", label), active_style)));
+            } else if !order_form.stop_price.is_empty() {
+                text.push(Spans::from(vec![Span::raw(format!("4. {}: $", label)), Span::raw(order_form.stop_price.to_owned())]));
+            }
+        }
+
+        if state == OrderFormState::OrderTerm {
+            text.push(Spans::from(Span::styled("5. Select order term (j/k, Enter)", active_style)));
+            text.extend(order_form_options(&OrderTerm::ALL, order_form.order_term, active_style));
+        } else if state == OrderFormState::MarketSession || state == OrderFormState::Submit {
+            text.push(Spans::from(vec![Span::raw("5. Order term: "), Span::raw(order_form.order_term.to_string())]));
         }
+
+        if state == OrderFormState::MarketSession {
+            text.push(Spans::from(Span::styled("6. Select market session (j/k, Enter)", active_style)));
+            text.extend(order_form_options(&MarketSession::ALL, order_form.market_session, active_style));
+        } else if state == OrderFormState::Submit {
+            text.push(Spans::from(vec![Span::raw("6. Market session: "), Span::raw(order_form.market_session.to_string())]));
+        }
+
+        if state == OrderFormState::MarketSession || state == OrderFormState::Submit {
+            if let Some(session_warning) = order_form.session_warning(utils::market_hours()) {
+                text.push(Spans::from(Span::styled(session_warning, Style::default().fg(app.user_config.theme.hint))));
+            }
+        }
+
+        if state == OrderFormState::Submit {
+            if let Some(premium) = order_form.estimated_premium() {
+                text.push(Spans::from(vec![Span::raw("Est. premium: "), Span::raw(format!("${:.2}", premium))]));
+            }
+            text.extend(order_changes(app));
+            text.push(Spans::from(Span::styled("Yay! Press Enter to Preview", active_style)));
+        }
+    }
+
+    if let Some(ref warning) = app.order_form_warning {
+        text.push(Spans::from(Span::styled(warning.to_owned(), Style::default().fg(app.user_config.theme.hint))));
+    }
+    if let Some(ref error) = app.order_form_error {
+        text.push(Spans::from(Span::styled(error.to_owned(), Style::default().fg(app.user_config.theme.error_text))));
     }
 
+    let title = match app.preview_order_form {
+        Some(PreviewOrder { order_id: Some(order_id), .. }) => format!("Change Order #{}", order_id),
+        _ => "Order Form".to_string(),
+    };
     let input = Paragraph::new(text).block(
         Block::default()
-        .title("Order Form")
+        .title(title)
         .borders(Borders::ALL)
         .border_style(get_color(highlight_state, app.user_config.theme)),
     ).wrap(Wrap { trim: true });
@@ -312,6 +599,805 @@ pub fn draw_order_form<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
     f.render_widget(input, layout_chunk);
 }
 
+// Diff of a changed order against the open order it replaces
+fn order_changes<'a>(app: &App) -> Vec<Spans<'a>> {
+    if app.original_order_form.is_none() {
+        return vec![];
+    }
+
+    let changes = app.order_form_changes();
+    if changes.is_empty() {
+        return vec![Spans::from(Span::styled("No changes from the open order", Style::default().fg(app.user_config.theme.hint)))];
+    }
+
+    let mut text = vec![Spans::from(Span::raw("Changes:"))];
+    for (field, before, after) in changes {
+        text.push(
+            Spans::from(vec![
+                        Span::raw(format!("  {}: ", field)),
+                        Span::raw(if before.is_empty() { "-".to_string() } else { before }),
+                        Span::raw(" ➤ "),
+                        Span::styled(if after.is_empty() { "-".to_string() } else { after }, Style::default().add_modifier(Modifier::BOLD)),
+            ])
+        );
+    }
+    text
+}
+
+fn order_form_options<'a, T: ToString + PartialEq>(options: &[T], selected: T, active_style: Style) -> Vec<Spans<'a>> {
+    options
+        .iter()
+        .map(|option| {
+            if *option == selected {
+                Spans::from(Span::styled(format!("   ➤ {}", option.to_string()), active_style))
+            } else {
+                Spans::from(Span::raw(format!("     {}", option.to_string())))
+            }
+        })
+        .collect()
+}
+
+pub fn draw_confirm_order_form<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::ConfirmOrderForm,
+        current_route.hovered_block == ActiveBlock::ConfirmOrderForm,
+        );
+
+    let preview = app.preview_order_response.as_ref().unwrap();
+
+    let mut text = vec![];
+    for order in &preview.order {
+        for instrument in &order.instrument {
+            text.push(
+                Spans::from(vec![
+                            Span::styled(instrument.order_action.to_owned(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                            Span::raw(" "),
+                            Span::raw(instrument.quantity.to_owned()),
+                            Span::raw(" "),
+                            Span::styled(instrument.product.display(), Style::default().add_modifier(Modifier::BOLD)),
+                ])
+            );
+        }
+        text.push(
+            Spans::from(vec![
+                        Span::raw("Price Type ➤ "),
+                        Span::raw(order.price_type.to_owned()),
+                        Span::raw("  Term ➤ "),
+                        Span::raw(order.order_term.to_owned()),
+                        Span::raw("  Session ➤ "),
+                        Span::raw(order.market_session.to_owned()),
+            ])
+        );
+        if let Some(estimated_commission) = order.estimated_commission {
+            text.push(
+                Spans::from(vec![
+                            Span::raw("Est. Commission ➤ "),
+                            Span::raw(format!("${:.2}", estimated_commission)),
+                ])
+            );
+        }
+    }
+
+    text.push(Spans::from(Span::raw(" ")));
+    if let Some(premium) = app.preview_order_form.as_ref().and_then(|order_form| order_form.estimated_premium()) {
+        text.push(
+            Spans::from(vec![
+                        Span::raw("Est. Premium ➤ "),
+                        Span::raw(format!("${:.2}", premium)),
+            ])
+        );
+    }
+    text.extend(order_changes(app));
+    text.push(
+        Spans::from(vec![
+                    Span::raw("Total Commission ➤ "),
+                    Span::raw(format!("${:.2}", preview.total_commission)),
+        ])
+    );
+    text.push(
+        Spans::from(vec![
+                    Span::raw("Total Order Value ➤ "),
+                    Span::styled(format!("${:.2}", preview.total_order_value), Style::default().add_modifier(Modifier::BOLD)),
+        ])
+    );
+
+    let message_list = match app.place_order_response {
+        Some(ref placed) => &placed.message_list,
+        None => &preview.message_list,
+    };
+    for message in &message_list.messages {
+        text.push(
+            Spans::from(vec![
+                        Span::styled(format!("{}: ", message.message_type), Style::default().fg(app.user_config.theme.hint)),
+                        Span::raw(message.description.to_owned()),
+            ])
+        );
+    }
+
+    text.push(Spans::from(Span::raw(" ")));
+    if let Some(ref placed) = app.place_order_response {
+        text.push(
+            Spans::from(vec![
+                        Span::raw(if app.original_order_form.is_some() { "Order changed ➤ " } else { "Order placed ➤ " }),
+                        Span::styled(format!("#{}", placed.order_id), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            ])
+        );
+        text.push(Spans::from(Span::raw("Press Enter to close")));
+    } else if app.placing_order {
+        text.push(Spans::from(Span::raw("Placing order...")));
+    } else {
+        text.push(Spans::from(Span::raw("Press Enter to Place Order or Esc to go back")));
+    }
+
+    let title = match app.preview_order_form {
+        Some(PreviewOrder { order_id: Some(order_id), .. }) => format!("Confirm Change #{}", order_id),
+        _ => "Confirm Order".to_string(),
+    };
+    let confirm = Paragraph::new(text).block(
+        Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(get_color(highlight_state, app.user_config.theme)),
+    ).wrap(Wrap { trim: true });
+
+    f.render_widget(confirm, layout_chunk);
+}
+
+pub fn draw_positions<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+        .split(layout_chunk);
+
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::Positions,
+        current_route.hovered_block == ActiveBlock::Positions,
+        );
+    let theme = app.user_config.theme;
+    let positions = app.positions.as_deref().unwrap_or(&[]);
+
+    // the sorted column is marked with its direction
+    let columns = [
+        (Some(PositionSort::Symbol), "Symbol"),
+        (Some(PositionSort::Quantity), "Qty"),
+        (None, "Cost/Share"),
+        (None, "Last"),
+        (Some(PositionSort::MarketValue), "Market Value"),
+        (Some(PositionSort::DaysGain), "Day's Gain"),
+        (Some(PositionSort::TotalGain), "Total Gain"),
+        (Some(PositionSort::TotalGainPct), "Gain %"),
+        (None, "% of Port"),
+    ];
+    let header = Row::new(columns.iter().map(|(sort, name)| {
+        match sort {
+            Some(sort) if *sort == app.positions_sort => {
+                format!("{} {}", name, if app.positions_sort_descending { "▼" } else { "▲" })
+            }
+            _ => name.to_string(),
+        }
+    }).collect::<Vec<String>>())
+        .style(Style::default().fg(theme.header))
+        .bottom_margin(1);
+
+    let rows = positions
+        .iter()
+        .map(|position| {
+            let gain_style = util::change_style(Some(position.total_gain), theme);
+            Row::new(vec![
+                Cell::from(match position.position_type.as_str() {
+                    "SHORT" => format!("{} (short)", position.ticker.symbol),
+                    _ => position.ticker.symbol.to_owned(),
+                }),
+                Cell::from(format!("{}", position.quantity)),
+                Cell::from(format!("${:.2}", position.cost_per_share)),
+                Cell::from(format!("${:.2}", position.ticker.price())),
+                Cell::from(format!("${:.2}", position.market_value)),
+                Cell::from(format!("{:+.2} ({:+.2}%)", position.days_gain, position.days_gain_pct))
+                    .style(util::change_style(Some(position.days_gain), theme)),
+                Cell::from(format!("{:+.2}", position.total_gain)).style(gain_style),
+                Cell::from(format!("{:+.2}%", position.total_gain_pct)).style(gain_style),
+                Cell::from(format!("{:.2}%", position.pct_of_portfolio)),
+            ])
+            .style(Style::default().fg(theme.text))
+        })
+        .collect::<Vec<Row>>();
+
+    let mut state = TableState::default();
+    if !positions.is_empty() {
+        state.select(Some(app.selected_position_index));
+    }
+
+    let title = format!(
+        "Positions {}  (s: sort by {}  S: reverse  r: refresh  t: tax lots  Enter: detail)",
+        app.portfolio_label(),
+        app.next_positions_sort().label()
+    );
+    let widths = [
+        Constraint::Percentage(10),
+        Constraint::Percentage(8),
+        Constraint::Percentage(11),
+        Constraint::Percentage(10),
+        Constraint::Percentage(14),
+        Constraint::Percentage(12),
+        Constraint::Percentage(12),
+        Constraint::Percentage(11),
+        Constraint::Percentage(12),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, get_color(highlight_state, theme)))
+            .border_style(get_color(highlight_state, theme)),
+            )
+        .highlight_style(get_color(highlight_state, theme).add_modifier(Modifier::BOLD))
+        .widths(&widths);
+
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let totals = PortfolioTotals::new(positions);
+    let gain_style = util::change_style(Some(totals.total_gain), theme);
+    let text = Spans::from(vec![
+        Span::raw(format!("Market Value ${:.2}   Cost ${:.2}   Day's Gain ", totals.market_value, totals.total_cost)),
+        Span::styled(format!("{:+.2}", totals.days_gain), util::change_style(Some(totals.days_gain), theme)),
+        Span::raw("   Total Gain "),
+        Span::styled(format!("{:+.2} ({:+.2}%)", totals.total_gain, totals.total_gain_pct), gain_style),
+    ]);
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled("Totals", Style::default().fg(theme.inactive)))
+            .border_style(Style::default().fg(theme.inactive)),
+            );
+    f.render_widget(paragraph, chunks[1]);
+}
+
+pub fn draw_tax_lots<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let position_lots = match app.position_lots {
+        Some(ref position_lots) => position_lots,
+        None => return,
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+        .split(layout_chunk);
+
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::TaxLots,
+        current_route.hovered_block == ActiveBlock::TaxLots,
+        );
+    let theme = app.user_config.theme;
+
+    let header = Row::new(vec!["", "Lot #", "Acquired", "Term", "Qty", "Available", "Price", "Cost", "Market Value", "Gain"])
+        .style(Style::default().fg(theme.header))
+        .bottom_margin(1);
+
+    let rows = position_lots.lots
+        .iter()
+        .map(|lot| {
+            let picked = if position_lots.sell_lot_ids.contains(&lot.id) { "✔" } else { "" };
+            let term = match lot.term {
+                utils::HoldingTerm::ShortTerm => "Short",
+                utils::HoldingTerm::LongTerm => "Long",
+            };
+            Row::new(vec![
+                Cell::from(picked),
+                Cell::from(lot.id.to_string()),
+                Cell::from(lot.acquired.format("%Y-%m-%d").to_string()),
+                Cell::from(term),
+                Cell::from(format!("{}", lot.quantity)),
+                Cell::from(format!("{}", lot.available)),
+                Cell::from(format!("${:.2}", lot.price)),
+                Cell::from(format!("${:.2}", lot.total_cost)),
+                Cell::from(format!("${:.2}", lot.market_value)),
+                Cell::from(format!("{:+.2}", lot.total_gain)).style(util::change_style(Some(lot.total_gain), theme)),
+            ])
+            .style(Style::default().fg(theme.text))
+        })
+        .collect::<Vec<Row>>();
+
+    let mut state = TableState::default();
+    if !position_lots.lots.is_empty() {
+        state.select(Some(position_lots.selected_index));
+    }
+
+    let title = format!("{} Tax Lots  (Space: pick lot  S: sell picked lots)", position_lots.symbol);
+    let widths = [
+        Constraint::Length(2),
+        Constraint::Percentage(12),
+        Constraint::Percentage(12),
+        Constraint::Percentage(7),
+        Constraint::Percentage(8),
+        Constraint::Percentage(9),
+        Constraint::Percentage(10),
+        Constraint::Percentage(12),
+        Constraint::Percentage(13),
+        Constraint::Percentage(12),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, get_color(highlight_state, theme)))
+            .border_style(get_color(highlight_state, theme)),
+            )
+        .highlight_style(get_color(highlight_state, theme).add_modifier(Modifier::BOLD))
+        .widths(&widths);
+
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let picked = position_lots.lots_to_sell();
+    let text = match position_lots.message {
+        Some(ref message) => Span::styled(message.to_owned(), Style::default().fg(theme.error_text)),
+        None => Span::raw(format!(
+            "Selling {} shares from {} lot(s)",
+            picked.iter().map(|lot| lot.size).sum::<f64>(),
+            picked.len()
+        )),
+    };
+    let paragraph = Paragraph::new(Spans::from(text))
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.inactive)),
+            );
+    f.render_widget(paragraph, chunks[1]);
+}
+
+pub fn draw_orders<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+        .split(layout_chunk);
+
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::Orders,
+        current_route.hovered_block == ActiveBlock::Orders,
+        );
+
+    let status = match app.orders_status_filter {
+        Some(status) => status.to_string(),
+        None => "ALL".to_string(),
+    };
+    let title = match app.orders_symbol_filter {
+        Some(ref symbol) => format!("Orders ({}, {})", status, symbol),
+        None => format!("Orders ({})", status),
+    };
+
+    let header = Row::new(vec!["Order #", "Placed", "Symbol", "Action", "Qty", "Filled", "Avg Price", "Type", "Status"])
+        .style(Style::default().fg(app.user_config.theme.header))
+        .bottom_margin(1);
+
+    let rows = app.orders
+        .iter()
+        .flatten()
+        .map(|order| {
+            let mut data = vec![order.order_id.to_string()];
+            if let Some(detail) = order.order_detail.first() {
+                data.push(detail.placed_time.map(|t| date_from_timestamp(t / 1000)).unwrap_or_default());
+                if let Some(instrument) = detail.instrument.first() {
+                    data.push(instrument.product.symbol.to_owned());
+                    data.push(instrument.order_action.to_owned());
+                    data.push(instrument.ordered_quantity.to_string());
+                    data.push(instrument.filled_quantity.to_string());
+                    data.push(instrument.average_execution_price.map(|p| format!("${:.2}", p)).unwrap_or_default());
+                } else {
+                    data.extend(vec!["".to_string(); 5]);
+                }
+                data.push(detail.price_type.to_owned());
+                data.push(detail.status.to_owned());
+            }
+            Row::new(data).style(Style::default().fg(app.user_config.theme.text))
+        })
+        .collect::<Vec<Row>>();
+
+    let mut state = TableState::default();
+    if matches!(app.orders, Some(ref orders) if !orders.is_empty()) {
+        state.select(Some(app.selected_order_index));
+    }
+
+    let widths = [
+        Constraint::Percentage(13),
+        Constraint::Percentage(12),
+        Constraint::Percentage(10),
+        Constraint::Percentage(10),
+        Constraint::Percentage(8),
+        Constraint::Percentage(8),
+        Constraint::Percentage(11),
+        Constraint::Percentage(13),
+        Constraint::Percentage(15),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, get_color(highlight_state, app.user_config.theme)))
+            .border_style(get_color(highlight_state, app.user_config.theme)),
+            )
+        .highlight_style(get_color(highlight_state, app.user_config.theme).add_modifier(Modifier::BOLD))
+        .widths(&widths);
+
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let mut text = vec![];
+    if let Some(order) = app.selected_order() {
+        for detail in &order.order_detail {
+            text.push(
+                Spans::from(vec![
+                            Span::raw(format!("#{} ➤ ", order.order_id)),
+                            Span::raw(format!("{} {} {}", detail.price_type, detail.order_term, detail.market_session)),
+                ])
+            );
+            let mut prices = vec![];
+            if let Some(limit_price) = detail.limit_price.filter(|p| *p > 0.0) {
+                prices.push(format!("limit ${:.2}", limit_price));
+            }
+            if let Some(stop_price) = detail.stop_price.filter(|p| *p > 0.0) {
+                prices.push(format!("stop ${:.2}", stop_price));
+            }
+            if let Some(order_value) = detail.order_value {
+                prices.push(format!("value ${:.2}", order_value));
+            }
+            if !prices.is_empty() {
+                text.push(Spans::from(Span::raw(prices.join("  "))));
+            }
+            for instrument in &detail.instrument {
+                let mut fill = format!(
+                    "{} {} filled {} of {}",
+                    instrument.order_action,
+                    instrument.product.display(),
+                    instrument.filled_quantity,
+                    instrument.ordered_quantity,
+                );
+                if let Some(average_execution_price) = instrument.average_execution_price {
+                    fill.push_str(&format!(" @ ${:.2}", average_execution_price));
+                }
+                if let Some(estimated_commission) = instrument.estimated_commission {
+                    fill.push_str(&format!("  commission ${:.2}", estimated_commission));
+                }
+                text.push(Spans::from(Span::raw(fill)));
+            }
+            if let Some(executed_time) = detail.executed_time {
+                text.push(Spans::from(Span::raw(format!("Executed on {}", date_from_timestamp(executed_time / 1000)))));
+            }
+        }
+    }
+    if let Some(ref message) = app.orders_message {
+        text.push(Spans::from(Span::styled(message.to_owned(), Style::default().fg(app.user_config.theme.hint))));
+    }
+    text.push(Spans::from(Span::styled("s: status filter  /: symbol filter  e: change  c: cancel  r: refresh", Style::default().fg(app.user_config.theme.inactive))));
+
+    let details = Paragraph::new(text).block(
+        Block::default()
+        .title("Details")
+        .borders(Borders::ALL)
+        .border_style(get_color(highlight_state, app.user_config.theme)),
+    ).wrap(Wrap { trim: true });
+
+    f.render_widget(details, chunks[1]);
+}
+
+pub fn draw_option_chain<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(75), Constraint::Percentage(25)].as_ref())
+        .split(layout_chunk);
+
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::OptionChain,
+        current_route.hovered_block == ActiveBlock::OptionChain,
+        );
+
+    let selected = app.selected_option_chain.as_ref().unwrap();
+    let option_chain = &selected.option_chain;
+
+    let expiry = match option_chain.selected_expiry() {
+        Some(expiration_date) => format!(
+            "{} {} ({}/{})",
+            expiration_date.to_string(),
+            expiration_date.expiry_type,
+            option_chain.selected_expiry_index + 1,
+            option_chain.expiration_dates.len(),
+        ),
+        None => "".to_string(),
+    };
+    let title = format!("{} Options ➤ {}  near ${:.2}", option_chain.symbol, expiry, option_chain.near_price);
+
+    let header = Row::new(vec![
+        "Bid", "Ask", "Last", "Vol", "OI", "Delta", "IV",
+        "Strike",
+        "Bid", "Ask", "Last", "Vol", "OI", "Delta", "IV",
+    ])
+        .style(Style::default().fg(app.user_config.theme.header))
+        .bottom_margin(1);
+
+    let text_style = Style::default().fg(app.user_config.theme.text);
+    let in_the_money_style = Style::default().fg(app.user_config.theme.active);
+    let option_cells = |option: &Option<OptionDetails>| -> Vec<Cell> {
+        match option {
+            Some(option) => {
+                let style = if option.is_in_the_money() { in_the_money_style } else { text_style };
+                vec![
+                    format!("{:.2}", option.bid),
+                    format!("{:.2}", option.ask),
+                    format!("{:.2}", option.last_price),
+                    option.volume.to_string(),
+                    option.open_interest.to_string(),
+                    format!("{:.2}", option.option_greeks.delta),
+                    format!("{:.1}%", option.option_greeks.iv * 100.0),
+                ]
+                    .into_iter()
+                    .map(|data| Cell::from(data).style(style))
+                    .collect()
+            }
+            None => vec![Cell::from(""); 7],
+        }
+    };
+
+    let rows = option_chain.option_pairs
+        .iter()
+        .map(|pair| {
+            let mut cells = option_cells(&pair.call);
+            cells.push(Cell::from(format!("{:.2}", pair.strike_price())).style(text_style.add_modifier(Modifier::BOLD)));
+            cells.extend(option_cells(&pair.put));
+            Row::new(cells)
+        })
+        .collect::<Vec<Row>>();
+
+    let mut state = TableState::default();
+    if !option_chain.option_pairs.is_empty() {
+        state.select(Some(selected.selected_index));
+    }
+
+    let widths = [Constraint::Percentage(6); 15];
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, get_color(highlight_state, app.user_config.theme)))
+            .border_style(get_color(highlight_state, app.user_config.theme)),
+            )
+        .highlight_style(get_color(highlight_state, app.user_config.theme).add_modifier(Modifier::BOLD))
+        .widths(&widths);
+
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let mut text = vec![];
+    if let Some(pair) = option_chain.option_pairs.get(selected.selected_index) {
+        for option in pair.call.iter().chain(pair.put.iter()) {
+            let greeks = &option.option_greeks;
+            let marker = if option.option_type == selected.call_put.to_string() { "➤ " } else { "  " };
+            text.push(
+                Spans::from(vec![
+                            Span::raw(marker),
+                            Span::styled(option.display_symbol.to_owned(), Style::default().add_modifier(Modifier::BOLD)),
+                            Span::raw(format!("  {}x{}  chg {:.2}", option.bid_size, option.ask_size, option.net_change)),
+                ])
+            );
+            text.push(
+                Spans::from(Span::raw(format!(
+                    "  delta {:.4}  gamma {:.4}  theta {:.4}  vega {:.4}  rho {:.4}  iv {:.2}%",
+                    greeks.delta,
+                    greeks.gamma,
+                    greeks.theta,
+                    greeks.vega,
+                    greeks.rho,
+                    greeks.iv * 100.0,
+                )))
+            );
+        }
+    }
+    text.push(Spans::from(Span::styled(
+        format!(
+            "Calls | Strike | Puts ({})   [ ]: expiration  t: call/put  B: buy  S: sell  a: add leg ({})  v: spread  r: refresh",
+            option_chain.quote_type,
+            app.spread_legs.len(),
+        ),
+        Style::default().fg(app.user_config.theme.inactive),
+    )));
+
+    let details = Paragraph::new(text).block(
+        Block::default()
+        .title("Details")
+        .borders(Borders::ALL)
+        .border_style(get_color(highlight_state, app.user_config.theme)),
+    ).wrap(Wrap { trim: true });
+
+    f.render_widget(details, chunks[1]);
+}
+
+pub fn draw_spread<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(layout_chunk);
+
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::Spread,
+        current_route.hovered_block == ActiveBlock::Spread,
+        );
+
+    let header = Row::new(vec!["Action", "Ratio", "Contract", "Bid", "Ask", "Natural"])
+        .style(Style::default().fg(app.user_config.theme.header))
+        .bottom_margin(1);
+
+    let rows = app.spread_legs
+        .iter()
+        .map(|leg| {
+            Row::new(vec![
+                leg.order_action.to_string(),
+                leg.ratio.to_string(),
+                leg.contract.display_symbol.to_owned(),
+                format!("{:.2}", leg.contract.bid),
+                format!("{:.2}", leg.contract.ask),
+                format!("{:.2}", leg.natural_price()),
+            ])
+            .style(Style::default().fg(app.user_config.theme.text))
+        })
+        .collect::<Vec<Row>>();
+
+    let mut state = TableState::default();
+    if !app.spread_legs.is_empty() {
+        state.select(Some(app.selected_spread_leg_index));
+    }
+
+    let widths = [
+        Constraint::Percentage(14),
+        Constraint::Percentage(8),
+        Constraint::Percentage(42),
+        Constraint::Percentage(12),
+        Constraint::Percentage(12),
+        Constraint::Percentage(12),
+    ];
+    let title = match app.spread_legs.first() {
+        Some(leg) => format!("{} {}", leg.symbol, spread::order_type(&app.spread_legs).to_string()),
+        None => "Spread".to_string(),
+    };
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, get_color(highlight_state, app.user_config.theme)))
+            .border_style(get_color(highlight_state, app.user_config.theme)),
+            )
+        .highlight_style(get_color(highlight_state, app.user_config.theme).add_modifier(Modifier::BOLD))
+        .widths(&widths);
+
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let mut text = vec![];
+    if app.spread_legs.is_empty() {
+        text.push(Spans::from(Span::raw("Add legs from the option chain with a")));
+    } else {
+        let net_price = spread::net_price(&app.spread_legs);
+        let summary = spread::summary(&app.spread_legs, net_price);
+        let money = |value: Option<f64>| match value {
+            Some(value) => format!("${:.2}", value),
+            None => "unlimited".to_string(),
+        };
+
+        let net = if net_price >= 0.0 { "Net debit" } else { "Net credit" };
+        text.push(
+            Spans::from(vec![
+                        Span::raw(format!("{} ➤ ", net)),
+                        Span::styled(format!("${:.2}", net_price.abs()), Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(format!("  (${:.2} per spread)", net_price.abs() * app::OPTION_MULTIPLIER)),
+            ])
+        );
+        match summary {
+            Some(summary) => {
+                text.push(
+                    Spans::from(vec![
+                                Span::raw("Max profit ➤ "),
+                                Span::styled(money(summary.max_profit), Style::default().fg(Color::Green)),
+                                Span::raw("  Max loss ➤ "),
+                                Span::styled(money(summary.max_loss), Style::default().fg(Color::Red)),
+                    ])
+                );
+                let breakevens = summary.breakevens
+                    .iter()
+                    .map(|breakeven| format!("${:.2}", breakeven))
+                    .collect::<Vec<String>>();
+                text.push(Spans::from(Span::raw(format!("Breakevens ➤ {}", breakevens.join(", ")))));
+            }
+            None => {
+                text.push(Spans::from(Span::raw("Legs expire on different dates, there's no single expiration to analyze")));
+            }
+        }
+    }
+    text.push(Spans::from(Span::raw(" ")));
+    text.push(Spans::from(Span::styled(
+        "Enter: open/close  +/-: ratio  d: remove leg  P: preview order",
+        Style::default().fg(app.user_config.theme.inactive),
+    )));
+
+    let details = Paragraph::new(text).block(
+        Block::default()
+        .title("At Expiration")
+        .borders(Borders::ALL)
+        .border_style(get_color(highlight_state, app.user_config.theme)),
+    ).wrap(Wrap { trim: true });
+
+    f.render_widget(details, chunks[1]);
+}
+
+pub fn draw_dialog<B>(f: &mut Frame<B>, app: &App)
+    where
+    B: Backend,
+{
+    let message = app.dialog.as_ref().map(|d| d.to_owned()).unwrap_or_default();
+
+    let size = f.size();
+    let width = std::cmp::min(size.width.saturating_sub(2), 50);
+    let height = 7;
+    let rect = Rect::new(
+        size.width.saturating_sub(width) / 2,
+        size.height.saturating_sub(height) / 2,
+        width,
+        std::cmp::min(height, size.height),
+    );
+
+    f.render_widget(Clear, rect);
+
+    let block = Block::default()
+        .title(Span::styled("Confirm", Style::default().fg(app.user_config.theme.hint)))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.user_config.theme.hint));
+
+    let selected = Style::default().fg(app.user_config.theme.selected).add_modifier(Modifier::BOLD | Modifier::REVERSED);
+    let unselected = Style::default().fg(app.user_config.theme.inactive);
+    let (yes_style, no_style) = if app.confirm { (selected, unselected) } else { (unselected, selected) };
+
+    let text = vec![
+        Spans::from(Span::raw(message)),
+        Spans::from(Span::raw(" ")),
+        Spans::from(vec![
+                    Span::styled(" Yes ", yes_style),
+                    Span::raw("   "),
+                    Span::styled(" No ", no_style),
+        ]),
+    ];
+
+    let dialog = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(dialog, rect);
+}
+
 pub fn draw_search_results<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
     where
     B: Backend,
@@ -333,13 +1419,17 @@ pub fn draw_search_results<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
     let list_items: Vec<ListItem> = search_results.tickers
         .iter()
         .flatten()
-        .map(|i| ListItem::new(Span::raw(i.symbol.to_string())))
+        .map(|i| {
+            // mark symbols already on the active watch list
+            let marker = if app.library.watch_lists.contains(&i.symbol) { "★ " } else { "  " };
+            ListItem::new(Span::raw(format!("{}{}", marker, i.symbol)))
+        })
         .collect();
 
     let list = List::new(list_items)
         .block(
             Block::default()
-            .title(Span::styled("Search Results", get_color(highlight_state, app.user_config.theme)))
+            .title(Span::styled("Search Results  (w: watch)", get_color(highlight_state, app.user_config.theme)))
             .borders(Borders::ALL)
             .border_style(get_color(highlight_state, app.user_config.theme)),
         )
@@ -372,7 +1462,7 @@ pub fn draw_home<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
     f.render_widget(welcome, layout_chunk);
 
     // Banner text with correct styling
-    let mut top_text = Text::from("Accounts");
+    let mut top_text = Text::from("Accounts  (j/k: select  Enter: switch  B: balance  T: transactions  G: realized gains  ●: active)");
     top_text.patch_style(Style::default().fg(Color::Yellow));
 
     // Contains the banner
@@ -383,38 +1473,49 @@ pub fn draw_home<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 
 
     if let Some(ref user_accounts) = app.user_accounts {
-        let mut bottom_text = String::new();
-        for acc in user_accounts {
-            bottom_text.push_str(" ➤ ");
-            bottom_text.push_str(&acc.account_id);
-            bottom_text.push_str("\n    ");
-            bottom_text.push_str(&acc.account_id_key);
-            bottom_text.push_str("\n    ");
-            if !acc.account_name.is_empty() {
-                bottom_text.push_str(&acc.account_name);
+        let choices = app.account_choices();
+        let list_items = choices
+            .iter()
+            .map(|choice| {
+                let marker = if app.is_active_account(*choice) { " ● " } else { " ➤ " };
+                let acc = match choice {
+                    Some(index) => &user_accounts[*index],
+                    None => return ListItem::new(format!("{}All accounts\n    Positions merged across brokerage accounts\n", marker)),
+                };
+
+                let mut bottom_text = String::new();
+                bottom_text.push_str(marker);
+                bottom_text.push_str(&acc.account_id);
                 bottom_text.push_str("\n    ");
-            }
-            if let Some(balance) = &acc.account_balance {
-                if let Some(computed) = &balance.computed {
-                    bottom_text.push_str("Account Value: ");
-                    bottom_text.push_str(&computed.real_time_values.total_account_value);
-                    bottom_text.push_str("\n    ");
-                    bottom_text.push_str("Net Market Value: ");
-                    bottom_text.push_str(&computed.real_time_values.net_mv);
+                bottom_text.push_str(&acc.account_id_key);
+                bottom_text.push_str("\n    ");
+                if !acc.account_name.is_empty() {
+                    bottom_text.push_str(&acc.account_name);
                     bottom_text.push_str("\n    ");
                 }
-            }
-            bottom_text.push_str(&acc.account_type);
+                if let Some(balance) = &acc.account_balance {
+                    if let Some(computed) = &balance.computed {
+                        bottom_text.push_str("Account Value: ");
+                        bottom_text.push_str(&computed.real_time_values.total_account_value);
+                        bottom_text.push_str("\n    ");
+                        bottom_text.push_str("Net Market Value: ");
+                        bottom_text.push_str(&computed.real_time_values.net_mv);
+                        bottom_text.push_str("\n    ");
+                    }
+                }
+                bottom_text.push_str(&acc.account_type);
+                bottom_text.push('\n');
+                ListItem::new(bottom_text)
+            })
+            .collect::<Vec<ListItem>>();
 
-            // TODO: add account balance
-            //
-            bottom_text.push_str("\n\n");
-        }
-        let bottom_text = Paragraph::new(bottom_text)
+        let mut state = ListState::default();
+        state.select(app.selected_account_index);
+
+        let list = List::new(list_items)
             .style(Style::default().fg(app.user_config.theme.text))
-            .block(Block::default())
-            .wrap(Wrap { trim: false });
-        f.render_widget(bottom_text, chunks[1]);
+            .highlight_style(get_color(highlight_state, app.user_config.theme).add_modifier(Modifier::BOLD));
+        f.render_stateful_widget(list, chunks[1], &mut state);
     }
 }
 
@@ -531,9 +1632,11 @@ pub fn draw_watch_list_block<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
     let mut state = ListState::default();
     state.select(Some(app.library.selected_index));
 
-    let list_items: Vec<ListItem> = MAJOR_INDICES
+    let watch_lists = &app.library.watch_lists;
+    let list_items: Vec<ListItem> = watch_lists
+        .active_symbols()
         .iter()
-        .map(|i| ListItem::new(Span::raw(*i)))
+        .map(|symbol| ListItem::new(quote_line(app, symbol, app.quotes.get(&symbol.to_uppercase()))))
         .collect();
 
     let current_route = app.get_current_route();
@@ -545,7 +1648,16 @@ pub fn draw_watch_list_block<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
     let list = List::new(list_items)
         .block(
             Block::default()
-            .title(Span::styled("Watch List", get_color(highlight_state, app.user_config.theme)))
+            .title(Span::styled(
+                    match watch_lists.active_list() {
+                        Some(list) if watch_lists.lists.len() > 1 => {
+                            format!("{} ({}/{})", list.name, watch_lists.active + 1, watch_lists.lists.len())
+                        }
+                        Some(list) => list.name.to_string(),
+                        None => "Watch List (n: new)".to_string(),
+                    },
+                    get_color(highlight_state, app.user_config.theme),
+                    ))
             .borders(Borders::ALL)
             .border_style(get_color(highlight_state, app.user_config.theme)),
             )
@@ -554,6 +1666,26 @@ pub fn draw_watch_list_block<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
     f.render_stateful_widget(list, layout_chunk, &mut state);
 }
 
+// Sidebar row with the latest polled price and change once a quote has come in
+fn quote_line<'a>(app: &App, symbol: &str, quote: Option<&app::Ticker>) -> Spans<'a> {
+    let theme = app.user_config.theme;
+    match quote {
+        Some(quote) if quote.has_quote() => {
+            let flash = app.quote_flash(symbol, app::Ticker::price, chrono::Utc::now());
+            let style = util::change_style(quote.change(), theme);
+            Spans::from(vec![
+                Span::raw(format!("{:<6} ", symbol)),
+                Span::styled(format!("{:.2}", quote.price()), util::flash_style(style, flash, theme)),
+                Span::styled(
+                    format!(" {}", quote.percent_change().map_or("".to_string(), |p| format!("{:+.2}%", p))),
+                    style,
+                ),
+            ])
+        }
+        _ => Spans::from(symbol.to_string()),
+    }
+}
+
 pub fn draw_portfolio_block<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
     where
     B: Backend,
@@ -561,10 +1693,10 @@ pub fn draw_portfolio_block<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
     let mut state = ListState::default();
     state.select(app.selected_watch_list_index);
 
-    if let Some(tickers) = &app.portfolio_tickers {
-        let list_items: Vec<ListItem> = tickers
+    if let Some(positions) = &app.positions {
+        let list_items: Vec<ListItem> = positions
             .iter()
-            .map(|i| ListItem::new(Span::raw(i.symbol.to_string())))
+            .map(|p| ListItem::new(quote_line(app, &p.ticker.symbol, Some(&p.ticker))))
             .collect();
 
         let current_route = app.get_current_route();
@@ -576,7 +1708,10 @@ pub fn draw_portfolio_block<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
         let list = List::new(list_items)
             .block(
                 Block::default()
-                .title(Span::styled("Portfolio", get_color(highlight_state, app.user_config.theme)))
+                .title(Span::styled(
+                    format!("Portfolio {}  (P: positions)", app.portfolio_label()),
+                    get_color(highlight_state, app.user_config.theme),
+                ))
                 .borders(Borders::ALL)
                 .border_style(get_color(highlight_state, app.user_config.theme)),
             )
@@ -604,16 +1739,36 @@ pub fn draw_input_and_help_box<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rec
 
     let input_string: String = app.input.iter().collect();
     let title = match current_route.id {
+        _ if app.library.naming_watch_list => "New watch list name",
         RouteId::OrderForm => {
             match app.order_form_state {
                 OrderFormState::Quantity => {
-                    "No. of shares"
+                    match app.preview_order_form {
+                        Some(ref order_form) if !order_form.legs.is_empty() => "No. of spreads",
+                        Some(ref order_form) if order_form.option.is_some() => "No. of contracts",
+                        _ => "No. of shares",
+                    }
+                }
+                OrderFormState::LimitPrice => {
+                    match app.preview_order_form {
+                        Some(ref order_form) if order_form.price_type == PriceType::NET_CREDIT => "Net credit",
+                        Some(ref order_form) if order_form.price_type == PriceType::NET_DEBIT => "Net debit",
+                        _ => "Limit price",
+                    }
+                }
+                OrderFormState::StopPrice => {
+                    match app.preview_order_form {
+                        Some(ref order_form) if order_form.price_type == PriceType::TRAILING_STOP_CNST => "Trail amount",
+                        _ => "Stop price",
+                    }
                 }
                 _ => {
                     "Preview Order"
                 }
             }
         }
+        RouteId::Orders => "Filter symbol",
+        RouteId::Authorization => "Verification code",
         _ => "Search"
     };
     let lines = Text::from((&input_string).as_str());
@@ -0,0 +1,142 @@
+use crate::app::{ActiveBlock, App};
+use crate::realized_gains::LotMatching;
+use crate::utils::HoldingTerm;
+use super::util::{self, get_color};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
+};
+
+pub fn draw_realized_gains<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
+        .split(layout_chunk);
+
+    let theme = app.user_config.theme;
+    let view = &app.realized_gains;
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::RealizedGains,
+        current_route.hovered_block == ActiveBlock::RealizedGains,
+    );
+
+    let range = match (view.start_date, view.end_date) {
+        (Some(start_date), Some(end_date)) => format!("{} to {}", start_date, end_date),
+        _ => "last two years".to_string(),
+    };
+    let title = format!(
+        "Realized Gains {} ({}, {})  (m: matching  /: dates  x: export  r: refresh)",
+        app.active_account().map_or("", |account| account.account_id.as_str()),
+        range,
+        view.matching.label(),
+    );
+
+    let header = Row::new(vec!["Year", "Symbol", "Proceeds", "Cost Basis", "Short Term", "Long Term", "Total"])
+        .style(Style::default().fg(theme.header))
+        .bottom_margin(1);
+
+    let rows = view.summaries
+        .iter()
+        .map(|summary| {
+            // year totals stand out from the symbols under them
+            let (year, symbol, style) = match &summary.symbol {
+                Some(symbol) => (String::new(), symbol.to_owned(), Style::default().fg(theme.text)),
+                None => (summary.year.to_string(), "All".to_string(), Style::default().fg(theme.text).add_modifier(Modifier::BOLD)),
+            };
+            Row::new(vec![
+                Cell::from(year),
+                Cell::from(symbol),
+                Cell::from(format!("${:.2}", summary.proceeds)),
+                Cell::from(format!("${:.2}", summary.cost_basis)),
+                Cell::from(format!("{:+.2}", summary.short_term)).style(util::change_style(Some(summary.short_term), theme)),
+                Cell::from(format!("{:+.2}", summary.long_term)).style(util::change_style(Some(summary.long_term), theme)),
+                Cell::from(format!("{:+.2}", summary.total())).style(util::change_style(Some(summary.total()), theme)),
+            ])
+            .style(style)
+        })
+        .collect::<Vec<Row>>();
+
+    let mut state = TableState::default();
+    if !view.summaries.is_empty() {
+        state.select(Some(view.selected_index));
+    }
+
+    let widths = [
+        Constraint::Percentage(8),
+        Constraint::Percentage(12),
+        Constraint::Percentage(16),
+        Constraint::Percentage(16),
+        Constraint::Percentage(16),
+        Constraint::Percentage(16),
+        Constraint::Percentage(16),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, get_color(highlight_state, theme)))
+            .border_style(get_color(highlight_state, theme)),
+            )
+        .highlight_style(get_color(highlight_state, theme).add_modifier(Modifier::BOLD))
+        .widths(&widths);
+
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let mut text = vec![];
+    for gain in view.selected_gains() {
+        let term = match gain.term {
+            HoldingTerm::ShortTerm => "short",
+            HoldingTerm::LongTerm => "long",
+        };
+        text.push(Spans::from(vec![
+            Span::raw(format!(
+                "{} {} ➤ bought {} #{} sold {} #{}  ${:.2} - ${:.2} = ",
+                gain.quantity, gain.symbol, gain.acquired, gain.buy_id, gain.sold, gain.sell_id, gain.proceeds, gain.cost_basis,
+            )),
+            Span::styled(format!("{:+.2}", gain.gain()), util::change_style(Some(gain.gain()), theme)),
+            Span::raw(format!("  {}", term)),
+        ]));
+    }
+    // positions opened before the range have no basis to match
+    for unmatched in &view.report.unmatched {
+        text.push(Spans::from(Span::styled(
+            format!(
+                "{} {} closed {} #{} wasn't opened in range, widen the dates to include it",
+                unmatched.quantity, unmatched.symbol, unmatched.sold, unmatched.sell_id,
+            ),
+            Style::default().fg(theme.hint),
+        )));
+    }
+    for skipped in &view.skipped {
+        text.push(Spans::from(Span::styled(skipped.to_owned(), Style::default().fg(theme.hint))));
+    }
+    if view.matching == LotMatching::SpecificLots {
+        text.push(Spans::from(Span::styled(
+            "lot_selections.yml next to client.yml picks the buys each sell closes, by the #ids here and on the transactions screen",
+            Style::default().fg(theme.hint),
+        )));
+    }
+    if let Some(message) = &view.message {
+        text.push(Spans::from(Span::styled(message.to_owned(), Style::default().fg(theme.hint))));
+    }
+
+    let lots = Paragraph::new(text)
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled("Lots", get_color(highlight_state, theme)))
+            .border_style(get_color(highlight_state, theme)),
+            )
+        .wrap(Wrap { trim: true });
+    f.render_widget(lots, chunks[1]);
+}
@@ -0,0 +1,147 @@
+use crate::app::{ActiveBlock, App};
+use crate::transactions;
+use super::util::{self, date_from_timestamp, get_color};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
+};
+
+pub fn draw_transactions<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+    where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+        .split(layout_chunk);
+
+    let theme = app.user_config.theme;
+    let view = &app.transactions;
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::Transactions,
+        current_route.hovered_block == ActiveBlock::Transactions,
+    );
+
+    let range = match (view.start_date, view.end_date) {
+        (Some(start_date), Some(end_date)) => format!("{} to {}", start_date, end_date),
+        _ => "recent".to_string(),
+    };
+    let kind = view.kind.map_or("all types", |kind| kind.label());
+    let title = format!(
+        "Transactions {} ({}, {}, page {})  (t: type  /: dates  n/p: page  x: export  Enter: details)",
+        app.active_account().map_or("", |account| account.account_id.as_str()),
+        range,
+        kind,
+        view.previous_markers.len() + 1,
+    );
+
+    let header = Row::new(vec!["Id", "Date", "Type", "Symbol", "Qty", "Price", "Amount", "Description"])
+        .style(Style::default().fg(theme.header))
+        .bottom_margin(1);
+
+    let visible = view.visible();
+    let rows = visible
+        .iter()
+        .map(|transaction| {
+            let brokerage = transaction.brokerage.clone().unwrap_or_default();
+            let quantity = if brokerage.quantity == 0.0 { String::new() } else { brokerage.quantity.to_string() };
+            let price = if brokerage.price == 0.0 { String::new() } else { format!("${:.2}", brokerage.price) };
+            Row::new(vec![
+                transaction.transaction_id.to_string(),
+                transactions::date(transaction).format("%Y-%m-%d").to_string(),
+                transaction.transaction_type.to_owned(),
+                brokerage.product.symbol,
+                quantity,
+                price,
+                format!("{:+.2}", transaction.amount),
+                transaction.description.to_owned(),
+            ])
+            .style(Style::default().fg(theme.text))
+        })
+        .collect::<Vec<Row>>();
+
+    let mut state = TableState::default();
+    if !visible.is_empty() {
+        state.select(Some(view.selected_index));
+    }
+
+    let widths = [
+        Constraint::Percentage(11),
+        Constraint::Percentage(10),
+        Constraint::Percentage(13),
+        Constraint::Percentage(8),
+        Constraint::Percentage(6),
+        Constraint::Percentage(8),
+        Constraint::Percentage(10),
+        Constraint::Percentage(34),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, get_color(highlight_state, theme)))
+            .border_style(get_color(highlight_state, theme)),
+            )
+        .highlight_style(get_color(highlight_state, theme).add_modifier(Modifier::BOLD))
+        .widths(&widths);
+
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let mut text = vec![];
+    if let Some(transaction) = view.selected() {
+        text.push(Spans::from(vec![
+            Span::raw(format!("#{} ➤ {}  ", transaction.transaction_id, transaction.transaction_type)),
+            Span::styled(format!("{:+.2}", transaction.amount), util::change_style(Some(transaction.amount), theme)),
+        ]));
+        text.push(Spans::from(Span::raw(transaction.description.to_owned())));
+
+        // the details endpoint adds the category, settlement and order number
+        match &view.details {
+            Some(details) if details.transaction_id == transaction.transaction_id => {
+                if let Some(category) = details.category.as_ref().filter(|category| !category.category_name.is_empty()) {
+                    text.push(Spans::from(Span::raw(format!("category {} / {}", category.parent_name, category.category_name))));
+                }
+                if let Some(post_date) = details.post_date {
+                    text.push(Spans::from(Span::raw(format!("posted {}", date_from_timestamp(post_date / 1000)))));
+                }
+                if let Some(brokerage) = &details.brokerage {
+                    let mut fields = vec![];
+                    if let Some(settlement_date) = brokerage.settlement_date {
+                        fields.push(format!("settles {}", date_from_timestamp(settlement_date / 1000)));
+                    }
+                    if brokerage.fee != 0.0 {
+                        fields.push(format!("fee ${:.2}", brokerage.fee));
+                    }
+                    if let Some(order_no) = brokerage.order_no.as_ref().filter(|order_no| !order_no.is_empty()) {
+                        fields.push(format!("order #{}", order_no));
+                    }
+                    if let Some(memo) = brokerage.memo.as_ref().filter(|memo| !memo.is_empty()) {
+                        fields.push(memo.to_owned());
+                    }
+                    text.push(Spans::from(Span::raw(fields.join("  "))));
+                }
+            }
+            _ => text.push(Spans::from(Span::styled("Enter: load details", Style::default().fg(theme.hint)))),
+        }
+    }
+    if let Some(message) = &view.message {
+        text.push(Spans::from(Span::styled(message.to_owned(), Style::default().fg(theme.hint))));
+    }
+
+    let details = Paragraph::new(text)
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled("Details", get_color(highlight_state, theme)))
+            .border_style(get_color(highlight_state, theme)),
+            )
+        .wrap(Wrap { trim: true });
+    f.render_widget(details, chunks[1]);
+}
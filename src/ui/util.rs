@@ -1,6 +1,6 @@
 use crate::config::Theme;
 use chrono::prelude::*;
-use tui::style::Style;
+use tui::style::{Modifier, Style};
 
 pub fn get_color((is_active, is_hovered): (bool, bool), theme: Theme) -> Style {
     match (is_active, is_hovered) {
@@ -10,6 +10,31 @@ pub fn get_color((is_active, is_hovered): (bool, bool), theme: Theme) -> Style {
     }
 }
 
+// Green for up, red for down
+pub fn change_style(change: Option<f64>, theme: Theme) -> Style {
+    match change {
+        Some(change) if change > 0.0 => Style::default().fg(theme.gain),
+        Some(change) if change < 0.0 => Style::default().fg(theme.loss),
+        _ => Style::default().fg(theme.text),
+    }
+}
+
+// A value that just moved on a refresh flashes in the direction it moved
+pub fn flash_style(style: Style, flash: Option<bool>, theme: Theme) -> Style {
+    match flash {
+        Some(true) => style.fg(theme.gain).add_modifier(Modifier::REVERSED),
+        Some(false) => style.fg(theme.loss).add_modifier(Modifier::REVERSED),
+        None => style,
+    }
+}
+
+pub fn format_change(ticker: &crate::app::Ticker) -> String {
+    match (ticker.change(), ticker.percent_change()) {
+        (Some(change), Some(percent)) => format!("{:+.2} ({:+.2}%)", change, percent),
+        _ => "--".to_string(),
+    }
+}
+
 // `percentage` param needs to be between 0 and 1
 pub fn get_percentage_width(width: u16, percentage: f32) -> u16 {
     let padding = 3;
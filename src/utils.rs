@@ -1,33 +1,134 @@
 use chrono::prelude::*;
-use chrono::Duration;
-use chrono_tz::{EST5EDT};
+use chrono_tz::{Tz, EST5EDT};
 use rand::prelude::*;
-use std::ops::Add;
+use std::{fs, path::{Path, PathBuf}, process::{Command, Stdio}};
 
-// today midnight auth_token expires
-// convert to midnight
-pub fn midnight_eastern(days: i64) -> DateTime<Utc> {
-    let nows = Utc::now().naive_utc();
-    let est = EST5EDT.from_utc_datetime(&nows).date().add(Duration::days(days)).and_hms(0, 0, 0);
-    est.with_timezone(&Utc)
+// The first midnight in New York after `time`, when E*TRADE expires access tokens.
+// Goes by the date there, so the UTC hour moves with daylight saving time
+pub fn next_midnight_eastern(time: DateTime<Utc>) -> DateTime<Utc> {
+    // clocks change at 2am, so midnight always happens, once
+    time.with_timezone(&EST5EDT).date().succ().and_hms(0, 0, 0).with_timezone(&Utc)
+}
+
+pub fn now_eastern() -> DateTime<Tz> {
+    Utc::now().with_timezone(&EST5EDT)
+}
+
+// The trading day's date in New York
+pub fn today_eastern() -> NaiveDate {
+    now_eastern().date().naive_local()
+}
 
-    // // get us to EST time
-    // let mut now = Utc::now() - Duration::hours(4);
-    // // today midnight auth_token expires
-    // // convert to midnight
-    // now = (now + Duration::days(days)).date().and_hms(0, 0, 0);
+// The date in New York of an E*TRADE timestamp in milliseconds, an evening trade there is already tomorrow in UTC
+pub fn date_eastern(millis: i64) -> NaiveDate {
+    Utc.timestamp_millis(millis).with_timezone(&EST5EDT).date().naive_local()
 }
 
-pub fn now_eastern() -> DateTime<Utc> {
-    let now = Utc::now().naive_utc();
-    let est = EST5EDT.from_utc_datetime(&now);
-    // TODO take into account ESD vs EST (fall winter)
-    // let res = Utc::now() - Duration::hours(4);
-    est.with_timezone(&Utc)
+// US equity sessions in Eastern time, using E*TRADE's extended hours windows.
+// Exchange holidays are not accounted for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MarketHours {
+    PreMarket,
+    Regular,
+    AfterHours,
+    Closed,
 }
 
-pub fn now_plus_hours(hours: i64) -> DateTime<Utc> {
-    now_eastern().add(Duration::hours(hours))
+pub fn market_hours() -> MarketHours {
+    market_hours_at(now_eastern())
+}
+
+pub fn market_hours_at<Z: TimeZone>(time: DateTime<Z>) -> MarketHours {
+    let eastern = time.with_timezone(&EST5EDT);
+    if let Weekday::Sat | Weekday::Sun = eastern.weekday() {
+        return MarketHours::Closed;
+    }
+
+    match eastern.hour() * 60 + eastern.minute() {
+        // 7:00am - 9:25am
+        420..=564 => MarketHours::PreMarket,
+        // 9:30am - 4:00pm
+        570..=959 => MarketHours::Regular,
+        // 4:05pm - 8:00pm
+        965..=1199 => MarketHours::AfterHours,
+        _ => MarketHours::Closed,
+    }
+}
+
+// How long the quote refresher waits between polls, None when polling is turned off.
+// Extended hours still move prices so only a closed market slows it down.
+pub fn quote_refresh_interval(hours: MarketHours, open_secs: u64, closed_secs: u64) -> Option<std::time::Duration> {
+    if open_secs == 0 {
+        return None;
+    }
+
+    let secs = match hours {
+        MarketHours::Closed => closed_secs.max(open_secs),
+        _ => open_secs,
+    };
+    Some(std::time::Duration::from_secs(secs))
+}
+
+// Volumes, market caps and share counts in K/M/B/T
+pub fn format_large_number(value: f64) -> String {
+    let units = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+    match units.iter().find(|(size, _)| value.abs() >= *size) {
+        Some((size, unit)) => format!("{:.2}{}", value / size, unit),
+        None => format!("{}", value),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HoldingTerm {
+    ShortTerm,
+    LongTerm,
+}
+
+// Held more than a year is long term, counted from the day after acquisition
+pub fn holding_term(acquired: NaiveDate, sold: NaiveDate) -> HoldingTerm {
+    // a lot bought on Feb 29th turns a year old on Mar 1st
+    let anniversary = acquired
+        .with_year(acquired.year() + 1)
+        .unwrap_or_else(|| NaiveDate::from_ymd(acquired.year() + 1, 3, 1));
+    if sold > anniversary {
+        HoldingTerm::LongTerm
+    } else {
+        HoldingTerm::ShortTerm
+    }
+}
+
+// Quotes a csv field when it holds a delimiter, quote or newline
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Writes <name>-<timestamp>.csv into `dir`, creating it the first time
+pub fn write_export(dir: &Path, name: &str, contents: &str, now: NaiveDateTime) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}-{}.csv", name, now.format("%Y%m%d-%H%M%S")));
+    fs::write(&path, contents)?;
+
+    Ok(path)
+}
+
+// Hands a url to the desktop's browser, without letting it write over the UI
+pub fn open_in_browser(url: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(url).stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
+
+    Ok(())
 }
 
 pub fn gen_id(size: usize) -> String {
@@ -89,8 +190,84 @@ mod tests {
 
     #[test]
     fn gen_simple_id() {
-        assert_eq!(simple_id!(), "".to_string());;
+        assert_eq!(simple_id().len(), 18);
     }
-}
 
+    #[test]
+    fn market_hours_in_eastern_time() {
+        // 10:00am EDT
+        assert_eq!(market_hours_at(Utc.ymd(2021, 7, 1).and_hms(14, 0, 0)), MarketHours::Regular);
+        // 9:00am EST, same UTC hour is regular hours in the summer
+        assert_eq!(market_hours_at(Utc.ymd(2021, 1, 4).and_hms(14, 0, 0)), MarketHours::PreMarket);
+        // 4:30pm EST
+        assert_eq!(market_hours_at(Utc.ymd(2021, 1, 4).and_hms(21, 30, 0)), MarketHours::AfterHours);
+        // 9:27am EDT, between pre-market and the open
+        assert_eq!(market_hours_at(Utc.ymd(2021, 7, 1).and_hms(13, 27, 0)), MarketHours::Closed);
+        // Saturday
+        assert_eq!(market_hours_at(Utc.ymd(2021, 7, 3).and_hms(15, 0, 0)), MarketHours::Closed);
+    }
+
+    #[test]
+    fn date_eastern_goes_by_new_york() {
+        // 8:30pm EST on January 4th, 1:30am UTC on the 5th
+        assert_eq!(date_eastern(1609810200000), NaiveDate::from_ymd(2021, 1, 4));
+        // 8:30pm EDT on July 1st
+        assert_eq!(date_eastern(1625185800000), NaiveDate::from_ymd(2021, 7, 1));
+    }
+
+    #[test]
+    fn now_eastern_is_new_york_time() {
+        // EST or EDT, not UTC
+        assert!([-5 * 3600, -4 * 3600].contains(&now_eastern().offset().fix().local_minus_utc()));
+    }
+
+    #[test]
+    fn midnight_eastern_follows_daylight_saving_time() {
+        // 11pm EST, an hour before midnight
+        assert_eq!(next_midnight_eastern(Utc.ymd(2021, 1, 5).and_hms(4, 0, 0)), Utc.ymd(2021, 1, 5).and_hms(5, 0, 0));
+        // 1am EST is already the 5th in New York
+        assert_eq!(next_midnight_eastern(Utc.ymd(2021, 1, 5).and_hms(6, 0, 0)), Utc.ymd(2021, 1, 6).and_hms(5, 0, 0));
+        assert_eq!(next_midnight_eastern(Utc.ymd(2021, 7, 1).and_hms(14, 0, 0)), Utc.ymd(2021, 7, 2).and_hms(4, 0, 0));
+        // the days clocks spring forward and fall back start at EST and end at EDT, or the other way around
+        assert_eq!(next_midnight_eastern(Utc.ymd(2021, 3, 14).and_hms(12, 0, 0)), Utc.ymd(2021, 3, 15).and_hms(4, 0, 0));
+        assert_eq!(next_midnight_eastern(Utc.ymd(2021, 11, 7).and_hms(12, 0, 0)), Utc.ymd(2021, 11, 8).and_hms(5, 0, 0));
+    }
+
+    #[test]
+    fn quote_refresh_slows_down_when_closed() {
+        let interval = |hours| quote_refresh_interval(hours, 5, 300).map(|d| d.as_secs());
+
+        assert_eq!(interval(MarketHours::Regular), Some(5));
+        assert_eq!(interval(MarketHours::AfterHours), Some(5));
+        assert_eq!(interval(MarketHours::Closed), Some(300));
+        // a closed interval shorter than the open one doesn't speed polling up
+        assert_eq!(quote_refresh_interval(MarketHours::Closed, 60, 10).map(|d| d.as_secs()), Some(60));
+        assert_eq!(quote_refresh_interval(MarketHours::Regular, 0, 300), None);
+    }
+
+    #[test]
+    fn csv_fields() {
+        assert_eq!(csv_field("AAPL"), "AAPL");
+        assert_eq!(csv_field("DIVIDEND, QUALIFIED"), "\"DIVIDEND, QUALIFIED\"");
+        assert_eq!(csv_field("5\" PIPE"), "\"5\"\" PIPE\"");
+    }
+
+    #[test]
+    fn holding_terms() {
+        let bought = NaiveDate::from_ymd(2020, 3, 15);
+        assert_eq!(holding_term(bought, NaiveDate::from_ymd(2021, 3, 15)), HoldingTerm::ShortTerm);
+        assert_eq!(holding_term(bought, NaiveDate::from_ymd(2021, 3, 16)), HoldingTerm::LongTerm);
+
+        let leap_day = NaiveDate::from_ymd(2020, 2, 29);
+        assert_eq!(holding_term(leap_day, NaiveDate::from_ymd(2021, 3, 1)), HoldingTerm::ShortTerm);
+        assert_eq!(holding_term(leap_day, NaiveDate::from_ymd(2021, 3, 2)), HoldingTerm::LongTerm);
+    }
 
+    #[test]
+    fn large_numbers() {
+        assert_eq!(format_large_number(950.0), "950");
+        assert_eq!(format_large_number(12_345.0), "12.35K");
+        assert_eq!(format_large_number(4_062_000.0), "4.06M");
+        assert_eq!(format_large_number(2_030_000_000_000.0), "2.03T");
+    }
+}
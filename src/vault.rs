@@ -0,0 +1,265 @@
+use crate::config::ConfigPaths;
+use crate::stonks_error::RuntimeError;
+use libsodium_sys as sodium;
+use log::debug;
+use secstr::{SecStr, SecUtf8};
+use std::{fs, io::Write, path::Path, sync::Once};
+
+// Sealed files start with this, which tells them apart from plaintext ones written before encryption
+const MAGIC: &[u8] = b"stonks-terminal sealed v1\n";
+const KEY_BYTES: usize = sodium::crypto_secretbox_KEYBYTES as usize;
+const NONCE_BYTES: usize = sodium::crypto_secretbox_NONCEBYTES as usize;
+const MAC_BYTES: usize = sodium::crypto_secretbox_MACBYTES as usize;
+const SALT_BYTES: usize = sodium::crypto_pwhash_SALTBYTES as usize;
+
+// Set to derive the key from a passphrase instead of keeping it in a key file
+pub const PASSPHRASE_VAR: &str = "STONKS_TERMINAL_PASSPHRASE";
+
+static SODIUM_INIT: Once = Once::new();
+
+fn init() {
+    SODIUM_INIT.call_once(|| {
+        // 1 means another library got there first
+        if unsafe { sodium::sodium_init() } < 0 {
+            panic!("libsodium failed to initialize");
+        }
+    });
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    init();
+    let mut bytes = vec![0u8; len];
+    unsafe { sodium::randombytes_buf(bytes.as_mut_ptr() as *mut _, len) };
+    bytes
+}
+
+// Readable by the user alone, whatever the umask
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // create() leaves the mode of a file that is already there alone
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(contents)
+}
+
+// Encrypts the files holding the consumer secret and OAuth tokens with XSalsa20-Poly1305
+pub struct Vault {
+    key: SecStr,
+}
+
+impl Vault {
+    // A passphrase from the environment when there is one, the key file otherwise
+    pub fn new(config_paths: &ConfigPaths) -> Result<Vault, RuntimeError> {
+        match std::env::var(PASSPHRASE_VAR) {
+            Ok(passphrase) => Vault::from_passphrase(&SecUtf8::from(passphrase), &config_paths.salt_path),
+            Err(_) => Vault::from_key_file(&config_paths.key_path),
+        }
+    }
+
+    // A random key, created on first run
+    pub fn from_key_file(key_path: &Path) -> Result<Vault, RuntimeError> {
+        if key_path.exists() {
+            let key = SecStr::new(fs::read(key_path)?);
+            if key.unsecure().len() != KEY_BYTES {
+                return Err(RuntimeError { message: format!("{} is not a stonks-terminal key file", key_path.display()) });
+            }
+            return Ok(Vault { key });
+        }
+
+        debug!("Creating key file {}", key_path.display());
+        let key = SecStr::new(random_bytes(KEY_BYTES));
+        write_private(key_path, key.unsecure())?;
+        Ok(Vault { key })
+    }
+
+    // Argon2id over the passphrase, with a salt kept next to the files it protects
+    pub fn from_passphrase(passphrase: &SecUtf8, salt_path: &Path) -> Result<Vault, RuntimeError> {
+        let salt = match fs::read(salt_path) {
+            Ok(salt) if salt.len() == SALT_BYTES => salt,
+            Ok(_) => return Err(RuntimeError { message: format!("{} is not a stonks-terminal salt file", salt_path.display()) }),
+            // a new salt for a salt that's there but unreadable would lock out everything sealed with the old one
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let salt = random_bytes(SALT_BYTES);
+                write_private(salt_path, &salt)?;
+                salt
+            }
+            Err(e) => return Err(RuntimeError { message: format!("Can't read the salt file {}: {}", salt_path.display(), e) }),
+        };
+
+        init();
+        let passphrase = passphrase.unsecure();
+        let mut key = SecStr::new(vec![0u8; KEY_BYTES]);
+        let derived = unsafe {
+            sodium::crypto_pwhash(
+                key.unsecure_mut().as_mut_ptr(),
+                KEY_BYTES as _,
+                passphrase.as_ptr() as *const _,
+                passphrase.len() as _,
+                salt.as_ptr(),
+                sodium::crypto_pwhash_OPSLIMIT_INTERACTIVE as _,
+                sodium::crypto_pwhash_MEMLIMIT_INTERACTIVE as _,
+                sodium::crypto_pwhash_ALG_DEFAULT as _,
+            )
+        };
+        if derived != 0 {
+            return Err(RuntimeError { message: "Not enough memory to derive a key from the passphrase".to_string() });
+        }
+
+        Ok(Vault { key })
+    }
+
+    // MAGIC, then a random nonce, then the ciphertext and its MAC
+    pub fn seal(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = random_bytes(NONCE_BYTES);
+        let mut ciphertext = vec![0u8; plaintext.len() + MAC_BYTES];
+        unsafe {
+            sodium::crypto_secretbox_easy(
+                ciphertext.as_mut_ptr(),
+                plaintext.as_ptr(),
+                plaintext.len() as _,
+                nonce.as_ptr(),
+                self.key.unsecure().as_ptr(),
+            );
+        }
+
+        [MAGIC, &nonce, &ciphertext].concat()
+    }
+
+    pub fn open(&self, sealed: &[u8]) -> Result<SecStr, RuntimeError> {
+        let corrupt = || RuntimeError { message: "Encrypted data is damaged, or was sealed with another key or passphrase".to_string() };
+        let sealed = sealed.strip_prefix(MAGIC).ok_or_else(corrupt)?;
+        if sealed.len() < NONCE_BYTES + MAC_BYTES {
+            return Err(corrupt());
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_BYTES);
+        let mut plaintext = SecStr::new(vec![0u8; ciphertext.len() - MAC_BYTES]);
+        let opened = unsafe {
+            sodium::crypto_secretbox_open_easy(
+                plaintext.unsecure_mut().as_mut_ptr(),
+                ciphertext.as_ptr(),
+                ciphertext.len() as _,
+                nonce.as_ptr(),
+                self.key.unsecure().as_ptr(),
+            )
+        };
+        if opened != 0 {
+            return Err(corrupt());
+        }
+
+        Ok(plaintext)
+    }
+
+    // None when there's no file yet. One still in plaintext from an older version is sealed in place
+    pub fn read(&self, path: &Path) -> Result<Option<SecUtf8>, RuntimeError> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => SecStr::new(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let plaintext = if bytes.unsecure().starts_with(MAGIC) {
+            self.open(bytes.unsecure())
+                .map_err(|e| RuntimeError { message: format!("Couldn't read {}: {}", path.display(), e.message) })?
+        } else {
+            debug!("Encrypting {}", path.display());
+            self.write(path, bytes.unsecure())?;
+            bytes
+        };
+
+        String::from_utf8(plaintext.unsecure().to_vec())
+            .map(|contents| Some(SecUtf8::from(contents)))
+            .map_err(|_| RuntimeError { message: format!("{} is not text", path.display()) })
+    }
+
+    pub fn write(&self, path: &Path, contents: &[u8]) -> Result<(), RuntimeError> {
+        Ok(write_private(path, &self.seal(contents))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::temp_dir;
+
+    #[test]
+    fn seals_and_opens() {
+        let dir = temp_dir("seal");
+        let vault = Vault::from_key_file(&dir.join("key")).unwrap();
+
+        let sealed = vault.seal(b"consumer_secret: abc123");
+        assert!(sealed.starts_with(MAGIC));
+        assert!(!sealed.windows(6).any(|window| window == b"abc123"));
+        assert_eq!(vault.open(&sealed).unwrap().unsecure(), b"consumer_secret: abc123");
+
+        // the same key file opens it again, another key doesn't
+        let reopened = Vault::from_key_file(&dir.join("key")).unwrap();
+        assert_eq!(reopened.open(&sealed).unwrap().unsecure(), b"consumer_secret: abc123");
+        let other = Vault::from_key_file(&dir.join("other-key")).unwrap();
+        assert!(other.open(&sealed).is_err());
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(vault.open(&tampered).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn derives_the_key_from_a_passphrase() {
+        let dir = temp_dir("passphrase");
+        let salt_path = dir.join("salt");
+        let sealed = Vault::from_passphrase(&SecUtf8::from("correct horse"), &salt_path).unwrap().seal(b"token");
+
+        let vault = Vault::from_passphrase(&SecUtf8::from("correct horse"), &salt_path).unwrap();
+        assert_eq!(vault.open(&sealed).unwrap().unsecure(), b"token");
+        let wrong = Vault::from_passphrase(&SecUtf8::from("battery staple"), &salt_path).unwrap();
+        assert!(wrong.open(&sealed).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_a_salt_it_cannot_read() {
+        let dir = temp_dir("unreadable-salt");
+        // there, but not a file that reads
+        let salt_path = dir.join("salt");
+        fs::create_dir_all(&salt_path).unwrap();
+
+        let error = Vault::from_passphrase(&SecUtf8::from("correct horse"), &salt_path).err().unwrap();
+        assert!(error.message.starts_with("Can't read the salt file"));
+        assert!(salt_path.is_dir());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn migrates_plaintext_files() {
+        let dir = temp_dir("migrate");
+        let vault = Vault::from_key_file(&dir.join("key")).unwrap();
+        let path = dir.join("client.yml");
+        assert_eq!(vault.read(&path).unwrap(), None);
+
+        fs::write(&path, "consumer_key: key\nconsumer_secret: secret\n").unwrap();
+        let contents = vault.read(&path).unwrap().unwrap();
+        assert_eq!(contents.unsecure(), "consumer_key: key\nconsumer_secret: secret\n");
+
+        // sealed on the first read, and still the same afterwards
+        assert!(fs::read(&path).unwrap().starts_with(MAGIC));
+        assert_eq!(vault.read(&path).unwrap(), Some(contents));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+use crate::stonks_error::RuntimeError;
+use serde::{Serialize, Deserialize};
+use std::{fs, path::Path};
+
+// what a fresh install starts with, the indices the sidebar used to hardcode
+const DEFAULT_LIST_NAME: &str = "Indices";
+const DEFAULT_SYMBOLS: [&str; 3] = ["NDAQ", "DJI", "GSPC"];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WatchList {
+    pub name: String,
+    pub symbols: Vec<String>,
+}
+
+impl WatchList {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            symbols: vec![],
+        }
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.symbols.iter().any(|s| s.eq_ignore_ascii_case(symbol))
+    }
+}
+
+// User managed watch lists, saved as yaml next to client.yml
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WatchLists {
+    pub active: usize,
+    pub lists: Vec<WatchList>,
+}
+
+impl Default for WatchLists {
+    fn default() -> Self {
+        Self {
+            active: 0,
+            lists: vec![WatchList {
+                name: DEFAULT_LIST_NAME.to_string(),
+                symbols: DEFAULT_SYMBOLS.iter().map(|s| s.to_string()).collect(),
+            }],
+        }
+    }
+}
+
+impl WatchLists {
+    pub fn load(path: &Path) -> Result<Self, RuntimeError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let yaml = fs::read_to_string(path)?;
+        let mut watch_lists: WatchLists = serde_yaml::from_str(&yaml)?;
+        if watch_lists.active >= watch_lists.lists.len() {
+            watch_lists.active = 0;
+        }
+
+        Ok(watch_lists)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), RuntimeError> {
+        let yaml = serde_yaml::to_string(self)?;
+        fs::write(path, yaml)?;
+
+        Ok(())
+    }
+
+    pub fn active_list(&self) -> Option<&WatchList> {
+        self.lists.get(self.active)
+    }
+
+    pub fn active_symbols(&self) -> &[String] {
+        self.active_list().map_or(&[], |list| list.symbols.as_slice())
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.active_list().is_some_and(|list| list.contains(symbol))
+    }
+
+    // Adds to the active list, creating a default list if the user deleted them all
+    pub fn add(&mut self, symbol: &str) -> Result<(), String> {
+        if self.lists.is_empty() {
+            self.lists.push(WatchList::new(DEFAULT_LIST_NAME));
+            self.active = 0;
+        }
+
+        let list = &mut self.lists[self.active];
+        if list.contains(symbol) {
+            return Err(format!("{} is already on {}", symbol.to_uppercase(), list.name));
+        }
+        list.symbols.push(symbol.to_uppercase());
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, symbol: &str) -> Result<(), String> {
+        match self.lists.get_mut(self.active) {
+            Some(list) if list.contains(symbol) => {
+                list.symbols.retain(|s| !s.eq_ignore_ascii_case(symbol));
+                Ok(())
+            }
+            Some(list) => Err(format!("{} is not on {}", symbol.to_uppercase(), list.name)),
+            None => Err("No watch list selected".to_string()),
+        }
+    }
+
+    // New lists become the active one
+    pub fn create(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Watch list name can't be empty".to_string());
+        }
+        if self.lists.iter().any(|list| list.name.eq_ignore_ascii_case(name)) {
+            return Err(format!("A watch list named {} already exists", name));
+        }
+
+        self.lists.push(WatchList::new(name));
+        self.active = self.lists.len() - 1;
+
+        Ok(())
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<(), String> {
+        match self.lists.iter().position(|list| list.name == name) {
+            Some(index) => {
+                self.lists.remove(index);
+                if self.active >= index && self.active > 0 {
+                    self.active -= 1;
+                }
+                Ok(())
+            }
+            None => Err(format!("No watch list named {}", name)),
+        }
+    }
+
+    pub fn cycle(&mut self, forward: bool) {
+        let len = self.lists.len();
+        if len == 0 {
+            return;
+        }
+
+        self.active = if forward {
+            (self.active + 1) % len
+        } else {
+            (self.active + len - 1) % len
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::temp_dir;
+
+    #[test]
+    fn load_missing_file_seeds_indices() {
+        let dir = temp_dir("watch-lists-missing");
+
+        let watch_lists = WatchLists::load(&dir.join("watch_lists.yml")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(watch_lists, WatchLists::default());
+        assert_eq!(watch_lists.active_symbols(), ["NDAQ", "DJI", "GSPC"]);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = temp_dir("watch-lists-round-trip");
+        let path = dir.join("watch_lists.yml");
+
+        let mut watch_lists = WatchLists::default();
+        watch_lists.create("Tech").unwrap();
+        watch_lists.add("aapl").unwrap();
+        watch_lists.add("MSFT").unwrap();
+        watch_lists.save(&path).unwrap();
+
+        let loaded = WatchLists::load(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded, watch_lists);
+        assert_eq!(loaded.active_list().unwrap().name, "Tech");
+        assert_eq!(loaded.active_symbols(), ["AAPL", "MSFT"]);
+    }
+
+    #[test]
+    fn add_and_remove_symbols() {
+        let mut watch_lists = WatchLists::default();
+
+        assert_eq!(watch_lists.add("ndaq"), Err("NDAQ is already on Indices".to_string()));
+        watch_lists.add("tsla").unwrap();
+        assert!(watch_lists.contains("TSLA"));
+
+        watch_lists.remove("Tsla").unwrap();
+        assert!(!watch_lists.contains("TSLA"));
+        assert_eq!(watch_lists.remove("TSLA"), Err("TSLA is not on Indices".to_string()));
+    }
+
+    #[test]
+    fn create_delete_and_cycle_lists() {
+        let mut watch_lists = WatchLists::default();
+
+        assert!(watch_lists.create(" ").is_err());
+        watch_lists.create("Tech").unwrap();
+        assert!(watch_lists.create("tech").is_err());
+        watch_lists.create("Energy").unwrap();
+        assert_eq!(watch_lists.active, 2);
+
+        watch_lists.cycle(true);
+        assert_eq!(watch_lists.active, 0);
+        watch_lists.cycle(false);
+        assert_eq!(watch_lists.active_list().unwrap().name, "Energy");
+
+        watch_lists.delete("Energy").unwrap();
+        assert_eq!(watch_lists.active_list().unwrap().name, "Tech");
+        watch_lists.delete("Indices").unwrap();
+        watch_lists.delete("Tech").unwrap();
+        assert_eq!(watch_lists.active_list(), None);
+
+        // adding with no lists left starts a fresh one
+        watch_lists.add("AAPL").unwrap();
+        assert_eq!(watch_lists.active_symbols(), ["AAPL"]);
+    }
+}